@@ -0,0 +1,56 @@
+use std::{path::Path, fs};
+
+use criterion::{criterion_group, criterion_main, Criterion, BenchmarkId};
+use tic_solved::{
+    tic_array::{
+        tic::*,
+        bitmap_tic::*,
+    },
+    minmax::{
+        alphabeta::{self, SearchResult},
+        ordering::*,
+        transposition::TranspositionTable,
+    },
+};
+
+const DEPTH: usize = 6;
+
+fn read_position(name: &str) -> BitBoard {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("positions").join(name);
+    let board_str = fs::read_to_string(path).expect("Failed to read board file");
+    let board_result: BoardResult = board_str.as_str().into();
+    board_result.into_inner().unwrap().into()
+}
+
+fn search_with(name: &str, board: &BitBoard) -> SearchResult<BitBoard, i32> {
+    match name {
+        "Generation" => alphabeta::search_ordered::<_, WinHeuristic, _>(board, DEPTH, true, &mut GenerationOrder, None),
+        "Heuristic" => alphabeta::search_ordered::<_, WinHeuristic, _>(board, DEPTH, true, &mut HeuristicOrder::<WinHeuristic>::new(), None),
+        "Killers" => alphabeta::search_ordered::<_, WinHeuristic, _>(board, DEPTH, true, &mut KillerMoves::new(GenerationOrder), None),
+        "History" => alphabeta::search_ordered::<_, WinHeuristic, _>(board, DEPTH, true, &mut HistoryHeuristic::new(), None),
+        "Killers + History" => alphabeta::search_ordered::<_, WinHeuristic, _>(board, DEPTH, true, &mut KillerMoves::new(HistoryHeuristic::new()), None),
+        _ => {
+            let mut table = TranspositionTable::new(1 << 18);
+            alphabeta::search_ordered::<_, WinHeuristic, _>(board, DEPTH, true, &mut KillerMoves::new(HistoryHeuristic::new()), Some(&mut table))
+        },
+    }
+}
+
+fn bench_move_ordering(c: &mut Criterion) {
+    let orderings = ["Generation", "Heuristic", "Killers", "History", "Killers + History", "Killers + History + TT"];
+    let mut group = c.benchmark_group("Move Ordering");
+    group.sample_size(10);
+
+    for position in ["early_game_position.txt", "late_game_position.txt"] {
+        let board = read_position(position);
+        for ordering in orderings {
+            // Criterion only reports time, so print the tree size each ordering searches too.
+            let result = search_with(ordering, &board);
+            println!("{} {}: {} nodes, {} cutoffs, score {}", position, ordering, result.nodes, result.cutoffs, result.score);
+            group.bench_with_input(BenchmarkId::new(ordering, position), &board, |b, board| b.iter(|| search_with(ordering, board)));
+        }
+    }
+}
+
+criterion_group!(benches, bench_move_ordering);
+criterion_main!(benches);
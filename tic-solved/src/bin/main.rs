@@ -1,16 +1,230 @@
-use tic_solved::tic_array::{
-    tic::*,
-    alphabeta_strategy_bitboard::AlphaBetaBitBoardStrategy as S4,
-    alphabeta_strategy::AlphaBetaStrategy as S3,
-    naive_strategy::MinimaxStrategy as S2,
-    tic_simulator::*
+use std::{env, io, path::{Path, PathBuf}, process, time::{Instant, SystemTime, UNIX_EPOCH}};
+
+use tic_solved::{
+    tic_array::{
+        tic::*,
+        bitmap_tic::*,
+        tic_move::Move,
+        tic_terminal_interface::TerminalInterface,
+        game_record::{SavedGame, SavedTree},
+        tournament::{Tournament, openings, play_pairing},
+        engine_protocol::Engine,
+    },
+    minmax::{alphabeta, ordering::HistoryHeuristic, perft::{perft, perft_divide}},
+    solve::{Solver, tablebase::Tablebase},
+    game::{play_local, Seat},
+    net::{server::Server, client::{RemoteGame, spectate}},
+    tui::{self, app::App},
+    cli::*,
 };
 
+fn exit_with(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1);
+}
+
+fn position(path: Option<&PathBuf>) -> Board {
+    read_position(path).unwrap_or_else(|e| exit_with(&e))
+}
+
+// The log keeps every line tried with undo, not just the one the game ended on.
+fn play_full_screen(player1: PlayerSpec, player2: PlayerSpec, start: Board) -> SavedTree {
+    let mut app = App::new(start);
+    for (player, spec) in [(Player::Player1, player1), (Player::Player2, player2)] {
+        if spec != PlayerSpec::Human {
+            app = app.with_engine(player, spec.to_string(), spec.strategy());
+        }
+    }
+    let app = tui::run(app).unwrap_or_else(|e| exit_with(&format!("Terminal error: {}", e)));
+    app.session().export()
+}
+
+fn play(player1: PlayerSpec, player2: PlayerSpec, start: Board, log: Option<PathBuf>, full_screen: bool) {
+    let mut game = if full_screen {
+        play_full_screen(player1, player2, start)
+    } else {
+        SavedGame::new(play_local(start, player1.seat(), player2.seat(), &mut TerminalInterface::new())).to_tree()
+    };
+
+    if let Some(path) = log {
+        let date = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        game = game
+            .with_header("Player1", player1.to_string())
+            .with_header("Player2", player2.to_string())
+            .with_header("Date", date.to_string());
+        game.save(&path).expect("Failed to write game log");
+    }
+}
+
+fn analyze_position(board: &Board, depth: usize) {
+    let outcome = board.outcome();
+    if outcome.is_over() {
+        println!("{}\nThe game is over: {}", board, outcome);
+        return;
+    }
+    let scores = analyze(board, depth);
+    print_on_board(board, scores.iter().map(|(_, score)| *score).collect());
+    let mut ranked = scores;
+    ranked.sort_by_key(|(_, score)| -score);
+    for (m, score) in ranked {
+        println!("{} {}", m, score);
+    }
+}
+
+fn perft_position(board: &Board, depth: usize, divide: bool) {
+    let bitboard: BitBoard = board.clone().into();
+    if divide {
+        // Root moves either engine generates, with both counts side by side.
+        let board_counts = perft_divide(board, depth);
+        let bitboard_counts = perft_divide(&bitboard, depth);
+        let mut keys: Vec<usize> = board_counts.iter().chain(&bitboard_counts).map(|(key, _)| *key).collect();
+        keys.sort();
+        keys.dedup();
+        let count = |counts: &[(usize, u64)], key| counts.iter().find(|(k, _)| *k == key).map(|(_, n)| *n);
+        println!("move board bitboard");
+        for key in keys {
+            let (a, b) = (count(&board_counts, key), count(&bitboard_counts, key));
+            let mismatch = if a == b { "" } else { " MISMATCH" };
+            let show = |n: Option<u64>| n.map_or("-".to_string(), |n| n.to_string());
+            println!("{} {} {}{}", Move::from_index(key).unwrap(), show(a), show(b), mismatch);
+        }
+    }
+    println!("depth board bitboard time");
+    for d in 1..=depth {
+        let start = Instant::now();
+        let nodes = perft(board, d);
+        let bitboard_nodes = perft(&bitboard, d);
+        let mismatch = if nodes == bitboard_nodes { "" } else { " MISMATCH" };
+        println!("{} {} {} {:?}{}", d, nodes, bitboard_nodes, start.elapsed(), mismatch);
+    }
+}
+
+fn selfplay(player1: PlayerSpec, player2: PlayerSpec, games: usize, opening_moves: usize, seed: u64) {
+    let mut game = 0;
+    let pairing = play_pairing(player1.strategy().as_ref(), player2.strategy().as_ref(), &openings(games, opening_moves, seed), |record| {
+        game += 1;
+        println!("Game {}: {:?} after {} moves", game, record.result, record.moves.len());
+    });
+    println!("{} vs {}: {:?}, player 1 win rate {:.3}", player1, player2, pairing, pairing.player1_win_rate());
+}
+
+fn bench(board: &Board, depth: usize) {
+    let maximize = board.who_turn() == Player::Player2;
+    let start = Instant::now();
+    let result = alphabeta::search_ordered::<Board, AlmostWinHeuristic, _>(board, depth, maximize, &mut HistoryHeuristic::new(), None);
+    let elapsed = start.elapsed();
+    println!("board:    {} nodes in {:?}, {:.0} nodes/s", result.nodes, elapsed, result.nodes as f64 / elapsed.as_secs_f64());
+
+    let bitboard: BitBoard = board.clone().into();
+    let start = Instant::now();
+    let result = alphabeta::search_ordered::<BitBoard, WinHeuristic, _>(&bitboard, depth, maximize, &mut HistoryHeuristic::new(), None);
+    let elapsed = start.elapsed();
+    println!("bitboard: {} nodes in {:?}, {:.0} nodes/s", result.nodes, elapsed, result.nodes as f64 / elapsed.as_secs_f64());
+}
+
+fn solve(board: &Board, checkpoint: Option<PathBuf>, interval: usize) {
+    let mut solver = match checkpoint {
+        Some(path) => Solver::with_checkpoint(&path, interval).unwrap_or_else(|e| exit_with(&format!("{}: {}", path.display(), e))),
+        None => Solver::new(),
+    };
+    let bitboard: BitBoard = board.clone().into();
+    println!("{}", board);
+    let solution = solver.solve(&bitboard).expect("Failed to write checkpoint");
+    println!("Result: {:?} in {} plies", solution.outcome, solution.distance);
+    for (ply, position) in solver.principal_line(&bitboard).unwrap().into_iter().enumerate() {
+        let position: Board = position.into();
+        println!("Ply {}:\n{}", ply + 1, position);
+    }
+    solver.save_checkpoint().expect("Failed to write checkpoint");
+    println!("{:?}, solved positions: {}", solver.stats(), solver.solved_positions());
+}
+
+fn tablebase(max_empty: usize, output: &Path) {
+    let tablebase = Tablebase::generate(max_empty).unwrap_or_else(|e| exit_with(&e));
+    tablebase.save(output).expect("Failed to write tablebase");
+    println!("Wrote {} positions to {}", tablebase.len(), output.display());
+}
+
+// Without `all`, waits for enter before every move.
+fn replay(path: &Path, all: bool) {
+    let game = SavedGame::load(path).unwrap_or_else(|e| exit_with(&format!("{}: {}", path.display(), e)));
+    for (key, value) in &game.headers {
+        println!("{}: {}", key, value);
+    }
+    println!("Start:\n{}", game.record.start);
+    for (i, (record, m)) in game.record.moves.iter().zip(game.moves()).enumerate() {
+        if !all {
+            let mut line = String::new();
+            io::stdin().read_line(&mut line).expect("Failed to read stdin");
+        }
+        println!("{}. {:?} {} ({:?})\n{}", i + 1, record.player, m, record.elapsed, record.board);
+    }
+    println!("Result: {:?}", game.record.result);
+}
+
+fn serve(address: &str, start: Board) {
+    let server = Server::bind(address)
+        .unwrap_or_else(|e| exit_with(&format!("Failed to listen on {}: {}", address, e)))
+        .with_start(start);
+    println!("Serving games on {}", server.local_addr().map_or(address.to_string(), |a| a.to_string()));
+    server.run().unwrap_or_else(|e| exit_with(&format!("Server error: {}", e)));
+}
+
+fn connect(address: &str, player: PlayerSpec, watch: bool, game: Option<usize>) {
+    let mut interface = TerminalInterface::new();
+    if watch {
+        if spectate(address, game, &mut interface).unwrap_or_else(|e| exit_with(&format!("{}: {}", address, e))).is_none() {
+            println!("The server closed the connection");
+        }
+        return;
+    }
+    println!("Waiting for an opponent on {}", address);
+    let remote = RemoteGame::join(address).unwrap_or_else(|e| exit_with(&format!("{}: {}", address, e)));
+    println!("Playing game {} as {:?}", remote.game, remote.seat);
+    let opponent = Seat::Engine(Box::new(remote.opponent.clone()));
+    let (player1, player2) = match remote.seat {
+        Player::Player1 => (player.seat(), opponent),
+        Player::Player2 => (opponent, player.seat()),
+    };
+    let record = play_local(remote.start, player1, player2, &mut interface);
+    if let Err(e) = remote.opponent.finish(record.final_board()) {
+        exit_with(&format!("{}: {}", address, e));
+    }
+}
+
 fn main() {
     dotenv::dotenv().ok();
     env_logger::init();
-    let board = Board::new();
-    let strategy1 = S3::new(2);
-    let strategy2 = S4::new(12);
-    run_game(board, strategy2, strategy1);
-}
\ No newline at end of file
+    let args: Vec<String> = env::args().skip(1).collect();
+    let command = match Command::parse(&args) {
+        Ok(command) => command,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            process::exit(2);
+        }
+    };
+    match command {
+        Command::Play { player1, player2, position: path, log, tui } => play(player1, player2, position(path.as_ref()), log, tui),
+        Command::Analyze { position: path, depth } => analyze_position(&position(Some(&path)), depth),
+        Command::Perft { depth, position: path, divide } => perft_position(&position(path.as_ref()), depth, divide),
+        Command::SelfPlay { player1, player2, games, opening_moves, seed } => selfplay(player1, player2, games, opening_moves, seed),
+        Command::Tournament { players, games, opening_moves, seed, csv } => {
+            let mut tournament = Tournament::new(games).with_opening_moves(opening_moves).with_seed(seed);
+            for player in players {
+                tournament = tournament.with_entrant(player.to_string(), player.strategy());
+            }
+            let results = tournament.run();
+            print!("{}", results);
+            if let Some(path) = csv {
+                results.write_csv(Path::new(&path)).expect("Failed to write csv");
+            }
+        }
+        Command::Bench { position: path, depth } => bench(&position(path.as_ref()), depth),
+        Command::Solve { position: path, checkpoint, interval } => solve(&position(Some(&path)), checkpoint, interval),
+        Command::Tablebase { max_empty, output } => tablebase(max_empty, &output),
+        Command::Replay { game, all } => replay(&game, all),
+        Command::Engine => Engine::new(io::stdout()).run(io::stdin().lock()).unwrap_or_else(|e| exit_with(&format!("Engine error: {}", e))),
+        Command::Serve { address, position: path } => serve(&address, position(path.as_ref())),
+        Command::Connect { address, player, watch, game } => connect(&address, player, watch, game),
+    }
+}
@@ -0,0 +1,433 @@
+use std::{fmt, fs, path::PathBuf, str::FromStr, time::Duration};
+
+use crate::{
+    tic_array::{
+        tic::*,
+        bitmap_tic::*,
+        tic_move::Move,
+        tic_simulator::Strategy,
+        naive_strategy::MinimaxStrategy,
+        alphabeta_strategy::AlphaBetaStrategy,
+        alphabeta_strategy_bitboard::AlphaBetaBitBoardStrategy,
+        mcts_strategy::MctsStrategy,
+        tic_terminal_interface::HumanTerminalStrategy,
+    },
+    minmax::alphabeta,
+    game::Seat,
+    net::DEFAULT_ADDRESS,
+};
+
+pub const USAGE: &str = "usage: main <command> [options]
+
+commands:
+  play [--player1 PLAYER] [--player2 PLAYER] [--position FILE] [--log FILE] [--tui]
+  analyze <position file> [--depth N]
+  perft <depth> [--position FILE] [--divide]
+  selfplay [--player1 PLAYER] [--player2 PLAYER] [--games N] [--opening-moves N] [--seed N]
+  tournament [--games N] [--opening-moves N] [--seed N] [--csv FILE] <PLAYER>...
+  bench [--position FILE] [--depth N]
+  solve <position file> [--checkpoint FILE] [--interval N]
+  tablebase --max-empty N --output FILE
+  replay <game file> [--all]
+  engine
+  serve [--address ADDR] [--position FILE]
+  connect [ADDR] [--player PLAYER] [--watch] [--game N]
+
+players:
+  human
+  minimax:DEPTH
+  alphabeta:DEPTH[:TIME]
+  bitboard:DEPTH[:TIME]
+  mcts:ITERATIONS[:TIME]
+TIME is a budget per move like 500ms or 10s.";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlayerSpec {
+    Human,
+    Minimax(usize),
+    AlphaBeta(usize, Option<Duration>),
+    BitBoard(usize, Option<Duration>),
+    Mcts(usize, Option<Duration>),
+}
+
+impl PlayerSpec {
+    // Humans play through the game's interface rather than as a strategy.
+    pub fn seat(&self) -> Seat {
+        match self {
+            PlayerSpec::Human => Seat::Human,
+            _ => Seat::Engine(self.strategy()),
+        }
+    }
+
+    pub fn strategy(&self) -> Box<dyn Strategy + Send + Sync> {
+        match *self {
+            PlayerSpec::Human => Box::new(HumanTerminalStrategy {}),
+            PlayerSpec::Minimax(depth) => Box::new(MinimaxStrategy::new(depth)),
+            PlayerSpec::AlphaBeta(depth, None) => Box::new(AlphaBetaStrategy::new(depth)),
+            PlayerSpec::AlphaBeta(depth, Some(budget)) => Box::new(AlphaBetaStrategy::new(depth).with_time_budget(budget)),
+            PlayerSpec::BitBoard(depth, None) => Box::new(AlphaBetaBitBoardStrategy::new(depth)),
+            PlayerSpec::BitBoard(depth, Some(budget)) => Box::new(AlphaBetaBitBoardStrategy::new(depth).with_time_budget(budget)),
+            PlayerSpec::Mcts(iterations, None) => Box::new(MctsStrategy::new(iterations)),
+            PlayerSpec::Mcts(iterations, Some(budget)) => Box::new(MctsStrategy::new(iterations).with_time_budget(budget)),
+        }
+    }
+}
+
+fn parse_duration(s: &str) -> Result<Duration, String> {
+    let invalid = || format!("Invalid time: {}", s);
+    if let Some(millis) = s.strip_suffix("ms") {
+        millis.parse().map(Duration::from_millis).map_err(|_| invalid())
+    } else if let Some(secs) = s.strip_suffix('s') {
+        secs.parse().map(Duration::from_secs).map_err(|_| invalid())
+    } else {
+        Err(invalid())
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    if duration.subsec_millis() == 0 {
+        format!("{}s", duration.as_secs())
+    } else {
+        format!("{}ms", duration.as_millis())
+    }
+}
+
+impl FromStr for PlayerSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid player: {}", s);
+        let number = |field: &str| field.parse::<usize>().map_err(|_| invalid());
+        let (kind, n, time) = match s.split(':').collect::<Vec<&str>>()[..] {
+            ["human"] => return Ok(PlayerSpec::Human),
+            [kind, n] => (kind, number(n)?, None),
+            [kind, n, time] => (kind, number(n)?, Some(parse_duration(time)?)),
+            _ => return Err(invalid()),
+        };
+        match (kind, time) {
+            ("minimax", None) => Ok(PlayerSpec::Minimax(n)),
+            ("alphabeta", _) => Ok(PlayerSpec::AlphaBeta(n, time)),
+            ("bitboard", _) => Ok(PlayerSpec::BitBoard(n, time)),
+            ("mcts", _) => Ok(PlayerSpec::Mcts(n, time)),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl fmt::Display for PlayerSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (kind, n, time) = match *self {
+            PlayerSpec::Human => return write!(f, "human"),
+            PlayerSpec::Minimax(depth) => return write!(f, "minimax:{}", depth),
+            PlayerSpec::AlphaBeta(depth, time) => ("alphabeta", depth, time),
+            PlayerSpec::BitBoard(depth, time) => ("bitboard", depth, time),
+            PlayerSpec::Mcts(iterations, time) => ("mcts", iterations, time),
+        };
+        write!(f, "{}:{}", kind, n)?;
+        if let Some(time) = time {
+            write!(f, ":{}", format_duration(time))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Play { player1: PlayerSpec, player2: PlayerSpec, position: Option<PathBuf>, log: Option<PathBuf>, tui: bool },
+    Analyze { position: PathBuf, depth: usize },
+    Perft { depth: usize, position: Option<PathBuf>, divide: bool },
+    SelfPlay { player1: PlayerSpec, player2: PlayerSpec, games: usize, opening_moves: usize, seed: u64 },
+    Tournament { players: Vec<PlayerSpec>, games: usize, opening_moves: usize, seed: u64, csv: Option<PathBuf> },
+    Bench { position: Option<PathBuf>, depth: usize },
+    Solve { position: PathBuf, checkpoint: Option<PathBuf>, interval: usize },
+    Tablebase { max_empty: usize, output: PathBuf },
+    Replay { game: PathBuf, all: bool },
+    Engine,
+    Serve { address: String, position: Option<PathBuf> },
+    Connect { address: String, player: PlayerSpec, watch: bool, game: Option<usize> },
+}
+
+// Positional arguments, `--key value` options in the order given and `--flag`s.
+struct Arguments {
+    positional: Vec<String>,
+    options: Vec<(String, String)>,
+    flags: Vec<String>,
+}
+
+impl Arguments {
+    fn parse(args: &[String], allowed: &[&str]) -> Result<Arguments, String> {
+        Arguments::parse_with_flags(args, allowed, &[])
+    }
+
+    fn parse_with_flags(args: &[String], allowed: &[&str], allowed_flags: &[&str]) -> Result<Arguments, String> {
+        let mut positional = Vec::new();
+        let mut options = Vec::new();
+        let mut flags = Vec::new();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.strip_prefix("--") {
+                Some(key) if allowed.contains(&key) => {
+                    let value = args.next().ok_or(format!("Missing value for --{}", key))?;
+                    options.push((key.to_string(), value.clone()));
+                }
+                Some(flag) if allowed_flags.contains(&flag) => flags.push(flag.to_string()),
+                Some(key) => return Err(format!("Unknown option: --{}", key)),
+                None => positional.push(arg.clone()),
+            }
+        }
+        Ok(Arguments { positional, options, flags })
+    }
+
+    fn flag(&self, flag: &str) -> bool {
+        self.flags.iter().any(|f| f == flag)
+    }
+
+    fn get<T: FromStr>(&self, key: &str, default: T) -> Result<T, String> {
+        match self.options.iter().rev().find(|(k, _)| k == key) {
+            Some((_, value)) => value.parse().map_err(|_| format!("Invalid value for --{}: {}", key, value)),
+            None => Ok(default),
+        }
+    }
+
+    fn optional<T: FromStr>(&self, key: &str) -> Result<Option<T>, String> {
+        match self.options.iter().rev().find(|(k, _)| k == key) {
+            Some((_, value)) => value.parse().map(Some).map_err(|_| format!("Invalid value for --{}: {}", key, value)),
+            None => Ok(None),
+        }
+    }
+
+    fn path(&self, key: &str) -> Option<PathBuf> {
+        self.options.iter().rev().find(|(k, _)| k == key).map(|(_, value)| PathBuf::from(value))
+    }
+
+    fn expect_positional(&self, count: usize) -> Result<(), String> {
+        if self.positional.len() == count {
+            Ok(())
+        } else {
+            Err(format!("Expected {} argument(s), got {}", count, self.positional.len()))
+        }
+    }
+}
+
+const DEFAULT_PLAYER1: PlayerSpec = PlayerSpec::Human;
+const DEFAULT_PLAYER2: PlayerSpec = PlayerSpec::BitBoard(12, Some(Duration::from_secs(10)));
+// Positions solved between checkpoints.
+const DEFAULT_CHECKPOINT_INTERVAL: usize = 1_000_000;
+
+impl Command {
+    // `args` without the program name.
+    pub fn parse(args: &[String]) -> Result<Command, String> {
+        let Some((command, rest)) = args.split_first() else {
+            return Err("Missing command".to_string());
+        };
+        match command.as_str() {
+            "play" => {
+                let args = Arguments::parse_with_flags(rest, &["player1", "player2", "position", "log"], &["tui"])?;
+                args.expect_positional(0)?;
+                Ok(Command::Play {
+                    player1: args.get("player1", DEFAULT_PLAYER1)?,
+                    player2: args.get("player2", DEFAULT_PLAYER2)?,
+                    position: args.path("position"),
+                    log: args.path("log"),
+                    tui: args.flag("tui"),
+                })
+            }
+            "analyze" => {
+                let args = Arguments::parse(rest, &["depth"])?;
+                args.expect_positional(1)?;
+                Ok(Command::Analyze { position: PathBuf::from(&args.positional[0]), depth: args.get("depth", 6)? })
+            }
+            "perft" => {
+                let args = Arguments::parse_with_flags(rest, &["position"], &["divide"])?;
+                args.expect_positional(1)?;
+                let depth = args.positional[0].parse().map_err(|_| format!("Invalid depth: {}", args.positional[0]))?;
+                Ok(Command::Perft { depth, position: args.path("position"), divide: args.flag("divide") })
+            }
+            "selfplay" => {
+                let args = Arguments::parse(rest, &["player1", "player2", "games", "opening-moves", "seed"])?;
+                args.expect_positional(0)?;
+                Ok(Command::SelfPlay {
+                    player1: args.get("player1", PlayerSpec::BitBoard(4, None))?,
+                    player2: args.get("player2", PlayerSpec::BitBoard(4, None))?,
+                    games: args.get("games", 10)?,
+                    opening_moves: args.get("opening-moves", 2)?,
+                    seed: args.get("seed", 0)?,
+                })
+            }
+            "tournament" => {
+                let args = Arguments::parse(rest, &["games", "opening-moves", "seed", "csv"])?;
+                let players = args.positional.iter().map(|s| s.parse()).collect::<Result<Vec<PlayerSpec>, String>>()?;
+                if players.is_empty() {
+                    return Err("A tournament needs at least one player".to_string());
+                }
+                if players.contains(&PlayerSpec::Human) {
+                    return Err("Humans can't enter a tournament".to_string());
+                }
+                Ok(Command::Tournament {
+                    players,
+                    games: args.get("games", 2)?,
+                    opening_moves: args.get("opening-moves", 2)?,
+                    seed: args.get("seed", 0)?,
+                    csv: args.path("csv"),
+                })
+            }
+            "bench" => {
+                let args = Arguments::parse(rest, &["position", "depth"])?;
+                args.expect_positional(0)?;
+                Ok(Command::Bench { position: args.path("position"), depth: args.get("depth", 6)? })
+            }
+            "solve" => {
+                let args = Arguments::parse(rest, &["checkpoint", "interval"])?;
+                args.expect_positional(1)?;
+                Ok(Command::Solve {
+                    position: PathBuf::from(&args.positional[0]),
+                    checkpoint: args.path("checkpoint"),
+                    interval: args.get("interval", DEFAULT_CHECKPOINT_INTERVAL)?,
+                })
+            }
+            "tablebase" => {
+                let args = Arguments::parse(rest, &["max-empty", "output"])?;
+                args.expect_positional(0)?;
+                Ok(Command::Tablebase {
+                    max_empty: args.optional("max-empty")?.ok_or("Missing --max-empty")?,
+                    output: args.path("output").ok_or("Missing --output")?,
+                })
+            }
+            "replay" => {
+                let args = Arguments::parse_with_flags(rest, &[], &["all"])?;
+                args.expect_positional(1)?;
+                Ok(Command::Replay { game: PathBuf::from(&args.positional[0]), all: args.flag("all") })
+            }
+            "engine" => {
+                Arguments::parse(rest, &[])?.expect_positional(0)?;
+                Ok(Command::Engine)
+            }
+            "serve" => {
+                let args = Arguments::parse(rest, &["address", "position"])?;
+                args.expect_positional(0)?;
+                Ok(Command::Serve { address: args.get("address", DEFAULT_ADDRESS.to_string())?, position: args.path("position") })
+            }
+            "connect" => {
+                let args = Arguments::parse_with_flags(rest, &["player", "game"], &["watch"])?;
+                if args.positional.len() > 1 {
+                    return Err(format!("Expected at most 1 argument, got {}", args.positional.len()));
+                }
+                let game = args.optional("game")?;
+                if game.is_some() && !args.flag("watch") {
+                    return Err("--game is only for --watch".to_string());
+                }
+                Ok(Command::Connect {
+                    address: args.positional.first().cloned().unwrap_or(DEFAULT_ADDRESS.to_string()),
+                    player: args.get("player", DEFAULT_PLAYER1)?,
+                    watch: args.flag("watch"),
+                    game,
+                })
+            }
+            _ => Err(format!("Unknown command: {}", command)),
+        }
+    }
+}
+
+// Reads a board in the positions/ format, or the empty board without a file.
+pub fn read_position(path: Option<&PathBuf>) -> Result<Board, String> {
+    let Some(path) = path else {
+        return Ok(Board::new());
+    };
+    let board_str = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    BoardResult::from(board_str.as_str()).into_inner()
+}
+
+// Scores every legal move with a `depth` ply search, from the point of view of the player making
+// it, in `legal_moves` order.
+pub fn analyze(board: &Board, depth: usize) -> Vec<(Move, i32)> {
+    if board.outcome().is_over() {
+        return Vec::new();
+    }
+    let mover = board.who_turn();
+    board.legal_moves()
+        .into_iter()
+        .map(|m| {
+            let child: BitBoard = board.play(m).expect("Legal move").into();
+            let remaining = depth.saturating_sub(1);
+            let (_, score) = match mover {
+                Player::Player1 => alphabeta::max_score::<BitBoard, WinHeuristic>(&child, remaining, None, None),
+                Player::Player2 => alphabeta::min_score::<BitBoard, WinHeuristic>(&child, remaining, None, None),
+            };
+            (m, if mover == Player::Player2 { score } else { -score })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(s: &str) -> Vec<String> {
+        s.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_player_spec_round_trip() {
+        for spec in ["human", "minimax:3", "alphabeta:4", "alphabeta:4:500ms", "bitboard:12:10s", "mcts:1000"] {
+            assert_eq!(spec.parse::<PlayerSpec>().unwrap().to_string(), spec);
+        }
+        assert_eq!("bitboard:12:10s".parse(), Ok(PlayerSpec::BitBoard(12, Some(Duration::from_secs(10)))));
+        assert!("minimax:3:1s".parse::<PlayerSpec>().is_err());
+        assert!("bitboard".parse::<PlayerSpec>().is_err());
+        assert!("alphabeta:4:10".parse::<PlayerSpec>().is_err());
+        assert!("random:1".parse::<PlayerSpec>().is_err());
+    }
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(
+            Command::parse(&args("play --player2 mcts:500 --log game.txt")),
+            Ok(Command::Play { player1: PlayerSpec::Human, player2: PlayerSpec::Mcts(500, None), position: None, log: Some("game.txt".into()), tui: false })
+        );
+        assert_eq!(
+            Command::parse(&args("play --tui")),
+            Ok(Command::Play { player1: DEFAULT_PLAYER1, player2: DEFAULT_PLAYER2, position: None, log: None, tui: true })
+        );
+        assert_eq!(
+            Command::parse(&args("tournament --games 3 bitboard:2 bitboard:4")),
+            Ok(Command::Tournament {
+                players: vec![PlayerSpec::BitBoard(2, None), PlayerSpec::BitBoard(4, None)],
+                games: 3, opening_moves: 2, seed: 0, csv: None,
+            })
+        );
+        assert_eq!(Command::parse(&args("perft 4")), Ok(Command::Perft { depth: 4, position: None, divide: false }));
+        assert_eq!(Command::parse(&args("perft --divide 2")), Ok(Command::Perft { depth: 2, position: None, divide: true }));
+        assert_eq!(
+            Command::parse(&args("serve --address 0.0.0.0:9000")),
+            Ok(Command::Serve { address: "0.0.0.0:9000".to_string(), position: None })
+        );
+        assert_eq!(
+            Command::parse(&args("connect --player bitboard:4")),
+            Ok(Command::Connect { address: DEFAULT_ADDRESS.to_string(), player: PlayerSpec::BitBoard(4, None), watch: false, game: None })
+        );
+        assert_eq!(
+            Command::parse(&args("connect host:7881 --watch --game 2")),
+            Ok(Command::Connect { address: "host:7881".to_string(), player: PlayerSpec::Human, watch: true, game: Some(2) })
+        );
+        assert_eq!(
+            Command::parse(&args("solve late.txt --checkpoint cache.bin")),
+            Ok(Command::Solve { position: "late.txt".into(), checkpoint: Some("cache.bin".into()), interval: DEFAULT_CHECKPOINT_INTERVAL })
+        );
+        assert_eq!(
+            Command::parse(&args("tablebase --max-empty 2 --output endgame.tb")),
+            Ok(Command::Tablebase { max_empty: 2, output: "endgame.tb".into() })
+        );
+        assert_eq!(Command::parse(&args("replay game.txt --all")), Ok(Command::Replay { game: "game.txt".into(), all: true }));
+        assert_eq!(Command::parse(&args("engine")), Ok(Command::Engine));
+        assert!(Command::parse(&args("tablebase --output endgame.tb")).is_err());
+        assert!(Command::parse(&args("tablebase late.txt --max-empty 2 --output endgame.tb")).is_err());
+        assert!(Command::parse(&args("engine --depth 3")).is_err());
+        assert!(Command::parse(&args("connect --game 2")).is_err());
+        assert!(Command::parse(&args("perft")).is_err());
+        assert!(Command::parse(&args("analyze")).is_err());
+        assert!(Command::parse(&args("play --depth 3")).is_err());
+        assert!(Command::parse(&args("tournament human bitboard:2")).is_err());
+        assert!(Command::parse(&args("selfplay --games")).is_err());
+        assert!(Command::parse(&[]).is_err());
+    }
+}
@@ -1,6 +1,95 @@
+use std::cell::RefCell;
 
+use crate::tic_array::{tic::*, tic_move::Move, tic_simulator::*};
 
+// What a front end provides so people can play. The game loop below only talks to this trait, so
+// it works the same whether the interface is a terminal or anything else.
 pub trait HumanPlayerInterface {
-    // TODO:
-    // Make a trait that could be run asynchronously for game_terminal_interface.
-}
\ No newline at end of file
+    // Called before an engine moves.
+    fn show_position(&mut self, board: &Board);
+
+    // Shows the position and asks for a move. `legal_moves` is in Board::legal_moves order. None
+    // means the player gives up.
+    fn request_move(&mut self, board: &Board, player: Player, legal_moves: &[Move]) -> Option<Move>;
+
+    fn report_illegal_input(&mut self, message: &str);
+
+    fn show_result(&mut self, result: GameResult, board: &Board);
+}
+
+impl<I: HumanPlayerInterface + ?Sized> HumanPlayerInterface for &mut I {
+    fn show_position(&mut self, board: &Board) {
+        (**self).show_position(board)
+    }
+
+    fn request_move(&mut self, board: &Board, player: Player, legal_moves: &[Move]) -> Option<Move> {
+        (**self).request_move(board, player, legal_moves)
+    }
+
+    fn report_illegal_input(&mut self, message: &str) {
+        (**self).report_illegal_input(message)
+    }
+
+    fn show_result(&mut self, result: GameResult, board: &Board) {
+        (**self).show_result(result, board)
+    }
+}
+
+// Asks until the player picks a legal move, or None once they give up.
+pub fn ask_for_move<I: HumanPlayerInterface + ?Sized>(interface: &mut I, board: &Board) -> Option<Move> {
+    let legal_moves = board.legal_moves();
+    loop {
+        let m = interface.request_move(board, board.who_turn(), &legal_moves)?;
+        match board.play(m) {
+            Ok(_) => return Some(m),
+            Err(e) => interface.report_illegal_input(&e.to_string()),
+        }
+    }
+}
+
+// Who plays one side of a local game.
+pub enum Seat {
+    Human,
+    Engine(Box<dyn Strategy>),
+}
+
+// Both seats share the interface, so every position is shown once: by show_position before an
+// engine moves, by request_move before a human does.
+struct SeatAgent<'a, I> {
+    seat: Seat,
+    interface: &'a RefCell<I>,
+}
+
+impl<I: HumanPlayerInterface> Agent for SeatAgent<'_, I> {
+    fn name(&self) -> String {
+        match self.seat {
+            Seat::Human => "human".to_string(),
+            Seat::Engine(_) => "engine".to_string(),
+        }
+    }
+
+    fn choose_move(&mut self, board: &Board) -> Result<Decision, AgentError> {
+        match &self.seat {
+            Seat::Human => ask_for_move(&mut *self.interface.borrow_mut(), board)
+                .map(|chosen| Decision { chosen, info: MoveInfo::default() })
+                .ok_or(AgentError::Failed("gave up".to_string())),
+            Seat::Engine(strategy) => {
+                self.interface.borrow_mut().show_position(board);
+                StrategyAgent::new("engine", strategy).choose_move(board)
+            }
+        }
+    }
+}
+
+// Plays a game on one interface: human against human, human against engine or engine against
+// engine. A human who gives up forfeits.
+pub fn play_local<I: HumanPlayerInterface>(board: Board, player1: Seat, player2: Seat, interface: &mut I) -> GameRecord {
+    let interface = RefCell::new(interface);
+    let record = play_game(
+        board,
+        SeatAgent { seat: player1, interface: &interface },
+        SeatAgent { seat: player2, interface: &interface },
+    );
+    interface.borrow_mut().show_result(record.result, record.final_board());
+    record
+}
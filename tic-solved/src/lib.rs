@@ -1,2 +1,8 @@
 pub mod tic_array;
-pub mod minmax;
\ No newline at end of file
+pub mod minmax;
+pub mod solve;
+pub mod cli;
+pub mod game;
+pub mod tui;
+pub mod session;
+pub mod net;
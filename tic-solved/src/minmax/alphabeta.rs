@@ -1,4 +1,11 @@
-use crate::minmax::{GameState, Heuristic};
+use std::marker::PhantomData;
+
+use crate::minmax::{
+    GameState, Heuristic, ZobristHash,
+    iterative::Deadline,
+    ordering::{GenerationOrder, MoveOrdering},
+    transposition::*,
+};
 
 fn optional_op<T, F>(a: Option<T>, b: Option<T>, op: F) -> Option<T>
 where
@@ -20,90 +27,377 @@ fn optional_min<T: PartialOrd>(a: Option<T>, b: Option<T>) -> Option<T> {
     optional_op(a, b, |a, b| if a <= b {a} else {b})
 }
 
-#[derive(PartialEq, Clone, Debug)]
-struct ScoredGameState<T, S> 
-where
-{
-    game_state: T,
-    score: S,
+fn is_better<S: PartialOrd>(score: S, best: Option<S>, maximize: bool) -> bool {
+    match best {
+        None => true,
+        Some(b) if maximize => score > b,
+        Some(b) => score < b,
+    }
 }
 
-impl<T, S> PartialOrd for ScoredGameState<T, S>
-where
-    S: PartialOrd,
-    T: PartialEq
-{
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        self.score.partial_cmp(&other.score)
+fn bound_for<S: PartialOrd>(score: S, alpha: Option<S>, beta: Option<S>) -> Bound {
+    if alpha.is_some_and(|a| score <= a) {
+        Bound::Upper
+    } else if beta.is_some_and(|b| score >= b) {
+        Bound::Lower
+    } else {
+        Bound::Exact
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchResult<T, S> {
+    pub score: S,
+    pub principal_variation: Vec<T>, // Starts with the chosen move.
+    pub depth: usize,
+    pub nodes: u64,
+    pub cutoffs: u64,
+    pub root_scores: Vec<(T, S)>, // In `next_states` order.
+}
+
+impl<T, S> SearchResult<T, S> {
+    pub fn best_move(&self) -> Option<&T> {
+        self.principal_variation.first()
+    }
+}
+
+// The root moves a search scored, before they are turned into a SearchResult.
+pub(crate) struct RootScores<T, S> {
+    pub best: usize, // Index into the root's `next_states`.
+    pub score: S,
+    pub principal_variation: Vec<T>,
+    pub scores: Vec<Option<S>>, // None for moves that weren't searched.
+}
+
+type NoProbe<T, S> = fn(&T) -> Option<S>;
+
+// The one alphabeta every search in this module runs. What it does besides alphabeta is
+// configured with the `with_` methods:
+//   ordering   the order children are visited in, `next_states` order by default
+//   table      where results are remembered and looked up, nothing by default
+//   probe      exact scores for positions below the root (e.g. an endgame tablebase), used as is
+//   deadline   gives up on the search once it passes
+//   principal variation, the line the search expects rather than just the move
+pub struct Search<'a, T, U, O, M, P> {
+    ordering: O,
+    table: M,
+    probe: P,
+    deadline: Option<&'a Deadline>,
+    principal_variation: bool,
+    nodes: u64,
+    cutoffs: u64,
+    heuristic: PhantomData<fn(&T) -> U>,
+}
+
+impl<T: GameState, U: Heuristic<T>> Search<'_, T, U, GenerationOrder, NoTable, NoProbe<T, U::Score>> {
+    pub fn new() -> Self {
+        Search {
+            ordering: GenerationOrder,
+            table: NoTable,
+            probe: |_| None,
+            deadline: None,
+            principal_variation: false,
+            nodes: 0,
+            cutoffs: 0,
+            heuristic: PhantomData,
+        }
     }
 }
 
-pub fn max_score<T, U>(root: &T, depth: usize, mut alpha: Option<U::Score>, beta: Option<U::Score>) -> (T, U::Score)
+impl<T: GameState, U: Heuristic<T>> Default for Search<'_, T, U, GenerationOrder, NoTable, NoProbe<T, U::Score>> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, T, U, O, M, P> Search<'a, T, U, O, M, P>
 where
-    T: GameState + Clone + PartialEq,
-    U: Heuristic<T>
+    T: GameState + Clone,
+    U: Heuristic<T>,
+    O: MoveOrdering<T>,
+    M: SearchTable<T, U::Score>,
+    P: Fn(&T) -> Option<U::Score>
 {
-    let states = root.next_states();
-    if states.is_empty() || depth == 0 {
-        return (root.clone(), U::score(root));
-    }
-    let mut best_score = None;
-    for state in states {
-        let (_, score) = min_score::<T, U>(&state, depth-1, alpha, beta);
-        best_score = optional_max(
-            best_score,
-            Some(ScoredGameState{game_state: state, score: score})
-        );
-        
-        alpha = optional_max(alpha, best_score.as_ref().map(|s| s.score));
-        if let Some(ref s) = best_score {
-            if let Some(b) = beta {
-                if s.score >= b {
-                    break;
+    pub fn with_ordering<O2: MoveOrdering<T>>(self, ordering: O2) -> Search<'a, T, U, O2, M, P> {
+        Search {
+            ordering,
+            table: self.table,
+            probe: self.probe,
+            deadline: self.deadline,
+            principal_variation: self.principal_variation,
+            nodes: self.nodes,
+            cutoffs: self.cutoffs,
+            heuristic: PhantomData,
+        }
+    }
+
+    pub fn with_table<M2: SearchTable<T, U::Score>>(self, table: M2) -> Search<'a, T, U, O, M2, P> {
+        Search {
+            ordering: self.ordering,
+            table,
+            probe: self.probe,
+            deadline: self.deadline,
+            principal_variation: self.principal_variation,
+            nodes: self.nodes,
+            cutoffs: self.cutoffs,
+            heuristic: PhantomData,
+        }
+    }
+
+    pub fn with_probe<P2: Fn(&T) -> Option<U::Score>>(self, probe: P2) -> Search<'a, T, U, O, M, P2> {
+        Search {
+            ordering: self.ordering,
+            table: self.table,
+            probe,
+            deadline: self.deadline,
+            principal_variation: self.principal_variation,
+            nodes: self.nodes,
+            cutoffs: self.cutoffs,
+            heuristic: PhantomData,
+        }
+    }
+
+    pub fn with_deadline(mut self, deadline: &'a Deadline) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    pub fn with_principal_variation(mut self) -> Self {
+        self.principal_variation = true;
+        self
+    }
+
+    // Positions searched so far, over every call.
+    pub fn nodes(&self) -> u64 {
+        self.nodes
+    }
+
+    pub fn cutoffs(&self) -> u64 {
+        self.cutoffs
+    }
+
+    // The score of `root` and the line the search expects, which is just the best move without
+    // `with_principal_variation` and empty for a position that isn't searched. None if the
+    // deadline passed first.
+    pub fn score(&mut self, root: &T, depth: usize, maximize: bool, alpha: Option<U::Score>, beta: Option<U::Score>) -> Option<(U::Score, Vec<T>)> {
+        self.node(root, depth, 0, maximize, alpha, beta)
+    }
+
+    // The best child of `root` and its score, or `root` itself where there is nothing to search.
+    pub fn max_score(&mut self, root: &T, depth: usize, alpha: Option<U::Score>, beta: Option<U::Score>) -> Option<(T, U::Score)> {
+        self.best_child(root, depth, true, alpha, beta)
+    }
+
+    pub fn min_score(&mut self, root: &T, depth: usize, alpha: Option<U::Score>, beta: Option<U::Score>) -> Option<(T, U::Score)> {
+        self.best_child(root, depth, false, alpha, beta)
+    }
+
+    fn best_child(&mut self, root: &T, depth: usize, maximize: bool, alpha: Option<U::Score>, beta: Option<U::Score>) -> Option<(T, U::Score)> {
+        let (score, line) = self.score(root, depth, maximize, alpha, beta)?;
+        let best = line.into_iter().next().unwrap_or_else(|| root.clone());
+        Some((best, score))
+    }
+
+    fn child(&mut self, state: &T, depth: usize, ply: usize, maximize: bool, alpha: Option<U::Score>, beta: Option<U::Score>) -> Option<(U::Score, Vec<T>)> {
+        match (self.probe)(state) {
+            Some(score) => Some((score, Vec::new())),
+            None => self.node(state, depth, ply, maximize, alpha, beta),
+        }
+    }
+
+    fn node(&mut self, root: &T, depth: usize, ply: usize, maximize: bool, mut alpha: Option<U::Score>, mut beta: Option<U::Score>) -> Option<(U::Score, Vec<T>)> {
+        self.nodes += 1;
+        if self.deadline.is_some_and(Deadline::poll) {
+            return None;
+        }
+        let mut states = root.next_states();
+        if states.is_empty() || depth == 0 {
+            return Some((U::score(root), Vec::new()));
+        }
+        let key = self.table.key(root);
+        let mut table_move = None;
+        if let Some(entry) = key.and_then(|key| self.table.probe(key)) {
+            if let Some(i) = entry.best_move.filter(|&i| i < states.len()) {
+                table_move = Some(i);
+                if entry.depth >= depth {
+                    match entry.bound {
+                        Bound::Exact => return Some((entry.score, vec![states.swap_remove(i)])),
+                        Bound::Lower => alpha = optional_max(alpha, Some(entry.score)),
+                        Bound::Upper => beta = optional_min(beta, Some(entry.score)),
+                    }
+                    if let (Some(a), Some(b)) = (alpha, beta) {
+                        if a >= b {
+                            return Some((entry.score, vec![states.swap_remove(i)]));
+                        }
+                    }
                 }
             }
         }
+        let (alpha_searched, beta_searched) = (alpha, beta);
+
+        let mut order = self.ordering.order(root, &states, ply, maximize);
+        if let Some(position) = table_move.and_then(|i| order.iter().position(|&j| j == i)) {
+            order[..=position].rotate_right(1);
+        }
+        let mut best: Option<(usize, U::Score, Vec<T>)> = None;
+        for i in order {
+            let (score, line) = self.child(&states[i], depth-1, ply+1, !maximize, alpha, beta)?;
+            if is_better(score, best.as_ref().map(|(_, b, _)| *b), maximize) {
+                best = Some((i, score, line));
+            }
+            let cutoff = if maximize {
+                alpha = optional_max(alpha, Some(score));
+                beta.is_some_and(|b| score >= b)
+            } else {
+                beta = optional_min(beta, Some(score));
+                alpha.is_some_and(|a| score <= a)
+            };
+            if cutoff {
+                self.cutoffs += 1;
+                self.ordering.record_cutoff(root, &states[i], ply, depth);
+                break;
+            }
+        }
+
+        let (index, score, mut line) = best.unwrap();
+        if let Some(key) = key {
+            self.table.store(TableEntry::new(key, depth, score, bound_for(score, alpha_searched, beta_searched), Some(index)));
+        }
+        // Without a principal variation only the root's best move is of interest.
+        if !self.principal_variation {
+            if ply > 0 {
+                return Some((score, Vec::new()));
+            }
+            line.clear();
+        }
+        line.insert(0, states.swap_remove(index));
+        Some((score, line))
     }
 
-    let value = best_score.unwrap();
-    (value.game_state, value.score)
+    // Scores the root moves `states`, visiting them in `order`. With `full_window` each one
+    // is searched with an open window, so every score is exact. Otherwise they share one window
+    // and only the best score is exact; the others are bounds. None if there are no moves
+    // or the deadline passed first.
+    pub(crate) fn root_scores(&mut self, states: &[T], order: Vec<usize>, depth: usize, maximize: bool, full_window: bool) -> Option<RootScores<T, U::Score>> {
+        self.nodes += 1;
+        let (mut alpha, mut beta) = (None, None);
+        let mut scores = vec![None; states.len()];
+        let mut best: Option<(usize, U::Score, Vec<T>)> = None;
+        for i in order {
+            let (score, line) = self.child(&states[i], depth.saturating_sub(1), 1, !maximize, alpha, beta)?;
+            if is_better(score, best.as_ref().map(|(_, b, _)| *b), maximize) {
+                best = Some((i, score, line));
+                if !full_window {
+                    if maximize { alpha = Some(score) } else { beta = Some(score) }
+                }
+            }
+            scores[i] = Some(score);
+        }
+        let (best, score, mut line) = best?;
+        line.insert(0, states[best].clone());
+        Some(RootScores { best, score, principal_variation: line, scores })
+    }
+
+    // Scores every root move, in the order the ordering asks for, and reports why it chose
+    // its move.
+    pub fn search(&mut self, root: &T, depth: usize, maximize: bool, full_window: bool) -> SearchResult<T, U::Score> {
+        let states = root.next_states();
+        let order = self.ordering.order(root, &states, 0, maximize);
+        let searched = if depth == 0 { None } else { self.root_scores(&states, order, depth, maximize, full_window) };
+        let Some(RootScores { score, principal_variation, scores, .. }) = searched else {
+            return SearchResult {
+                score: U::score(root),
+                principal_variation: Vec::new(),
+                depth: 0,
+                nodes: self.nodes.max(1),
+                cutoffs: self.cutoffs,
+                root_scores: Vec::new(),
+            };
+        };
+        SearchResult {
+            score,
+            principal_variation,
+            depth,
+            nodes: self.nodes,
+            cutoffs: self.cutoffs,
+            root_scores: states.into_iter().zip(scores).map(|(state, score)| (state, score.unwrap())).collect(),
+        }
+    }
 }
 
-pub fn min_score<T, U>(root: &T, depth: usize, alpha: Option<U::Score>, mut beta: Option<U::Score>) -> (T, U::Score)
+pub fn max_score<T, U>(root: &T, depth: usize, alpha: Option<U::Score>, beta: Option<U::Score>) -> (T, U::Score)
 where
     T: GameState + Clone + PartialEq,
     U: Heuristic<T>
 {
-    let states = root.next_states();
-    if states.is_empty() || depth == 0 {
-        return (root.clone(), U::score(root));
-    }
-    let mut best_score = None;
-    for state in states {
-        let (_, score) = max_score::<T, U>(&state, depth-1, alpha.clone(), beta.clone());
-        best_score = optional_min(
-            best_score,
-            Some(ScoredGameState{game_state: state, score: score})
-        );
-        beta = optional_min(beta, best_score.as_ref().map(|s| s.score));
-        if let Some(ref s) = best_score {
-            if let Some(a) = alpha {
-                if s.score <= a {
-                    break;
-                }
-            }
-        }
-    }
+    Search::<T, U, _, _, _>::new().max_score(root, depth, alpha, beta).unwrap()
+}
 
-    let value = best_score.unwrap();
-    (value.game_state, value.score)
+pub fn min_score<T, U>(root: &T, depth: usize, alpha: Option<U::Score>, beta: Option<U::Score>) -> (T, U::Score)
+where
+    T: GameState + Clone + PartialEq,
+    U: Heuristic<T>
+{
+    Search::<T, U, _, _, _>::new().min_score(root, depth, alpha, beta).unwrap()
+}
+
+// Alphabeta that also reports why it chose its move. Every root move is searched with a full
+// window, so `root_scores` are exact rather than bounds.
+pub fn search<T, U>(root: &T, depth: usize, maximize: bool) -> SearchResult<T, U::Score>
+where
+    T: GameState + Clone,
+    U: Heuristic<T>
+{
+    Search::<T, U, _, _, _>::new().with_principal_variation().search(root, depth, maximize, true)
+}
+
+pub fn max_score_tt<T, U>(root: &T, depth: usize, alpha: Option<U::Score>, beta: Option<U::Score>, table: &mut TranspositionTable<U::Score>) -> (T, U::Score)
+where
+    T: GameState + ZobristHash + Clone + PartialEq,
+    U: Heuristic<T>
+{
+    Search::<T, U, _, _, _>::new().with_table(table).max_score(root, depth, alpha, beta).unwrap()
+}
+
+pub fn min_score_tt<T, U>(root: &T, depth: usize, alpha: Option<U::Score>, beta: Option<U::Score>, table: &mut TranspositionTable<U::Score>) -> (T, U::Score)
+where
+    T: GameState + ZobristHash + Clone + PartialEq,
+    U: Heuristic<T>
+{
+    Search::<T, U, _, _, _>::new().with_table(table).min_score(root, depth, alpha, beta).unwrap()
+}
+
+// Alphabeta that visits moves in the order `ordering` asks for, trying the transposition table's
+// move first when a table is given. Root moves share one window, so only the best root score is
+// exact; the others are bounds.
+pub fn search_ordered<T, U, O>(
+    root: &T,
+    depth: usize,
+    maximize: bool,
+    ordering: &mut O,
+    table: Option<&mut TranspositionTable<U::Score>>
+) -> SearchResult<T, U::Score>
+where
+    T: GameState + ZobristHash + Clone,
+    U: Heuristic<T>,
+    O: MoveOrdering<T>
+{
+    Search::<T, U, _, _, _>::new()
+        .with_ordering(ordering)
+        .with_table(table)
+        .with_principal_variation()
+        .search(root, depth, maximize, false)
 }
 
 #[cfg(test)]
 mod tests {
     use crate::minmax::alphabeta::{optional_max, optional_min};
 
-    use super::ScoredGameState;
+    #[derive(PartialEq, PartialOrd, Clone, Debug)]
+    struct ScoredGameState {
+        score: i32,
+        game_state: i32,
+    }
 
     #[test]
     fn test_optional_max() {
@@ -126,4 +420,4 @@ mod tests {
         assert!(!(None >= Some(3)));
         assert!(!(None >= Some(-3)));
     }
-}
\ No newline at end of file
+}
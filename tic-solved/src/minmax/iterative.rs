@@ -0,0 +1,173 @@
+use std::{
+    cell::Cell,
+    sync::{Arc, atomic::{AtomicBool, Ordering}},
+    time::{Duration, Instant},
+};
+
+use log::info;
+
+use crate::minmax::{
+    GameState, Heuristic,
+    alphabeta::{RootScores, Search},
+    ordering::MoveOrdering,
+    transposition::SearchTable,
+};
+
+// Checking the clock on every node is measurably slow, so only every CHECK_INTERVAL nodes look at it.
+const CHECK_INTERVAL: u64 = 256;
+
+// Ends a search when its time is up or when someone raises the stop flag, whichever comes first.
+pub struct Deadline {
+    end: Option<Instant>,
+    stop: Option<Arc<AtomicBool>>,
+    nodes: Cell<u64>,
+    expired: Cell<bool>,
+}
+
+impl Deadline {
+    pub fn after(budget: Duration) -> Deadline {
+        Deadline { end: Instant::now().checked_add(budget), stop: None, nodes: Cell::new(0), expired: Cell::new(false) }
+    }
+
+    pub fn never() -> Deadline {
+        Deadline { end: None, stop: None, nodes: Cell::new(0), expired: Cell::new(false) }
+    }
+
+    pub fn with_stop(mut self, stop: Arc<AtomicBool>) -> Deadline {
+        self.stop = Some(stop);
+        self
+    }
+
+    pub fn passed(&self) -> bool {
+        self.expired.get()
+            || self.end.is_some_and(|end| Instant::now() >= end)
+            || self.stop.as_ref().is_some_and(|stop| stop.load(Ordering::Relaxed))
+    }
+
+    // Cheap check for use inside the search.
+    pub fn poll(&self) -> bool {
+        if self.expired.get() {
+            return true;
+        }
+        let nodes = self.nodes.get() + 1;
+        self.nodes.set(nodes);
+        if nodes.is_multiple_of(CHECK_INTERVAL) && self.passed() {
+            self.expired.set(true);
+        }
+        self.expired.get()
+    }
+
+    // Nodes polled so far.
+    pub fn nodes(&self) -> u64 {
+        self.nodes.get()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IterationResult<T, S> {
+    pub best: T,
+    pub score: S,
+    pub depth: usize, // Deepest iteration that completed.
+    pub principal_variation: Vec<T>, // Starts with `best`.
+    pub nodes: u64, // Over all iterations, the aborted one included.
+}
+
+// Searches depth 1, 2, ... up to `max_depth` until `budget` runs out, returning the best move of the
+// last iteration that completed. The first iteration always completes so there is always a move.
+// Each iteration searches the previous best move first. `search` is used for every iteration, so
+// its table and move ordering carry what earlier iterations learned over to the later ones.
+pub fn iterative_deepening<T, U, O, M, P>(
+    root: &T,
+    maximize: bool,
+    max_depth: usize,
+    budget: Duration,
+    search: Search<'_, T, U, O, M, P>
+) -> Option<IterationResult<T, U::Score>>
+where
+    T: GameState + Clone,
+    U: Heuristic<T>,
+    O: MoveOrdering<T>,
+    M: SearchTable<T, U::Score>,
+    P: Fn(&T) -> Option<U::Score>
+{
+    iterative_deepening_until(root, maximize, max_depth, &Deadline::after(budget), search, |_| {})
+}
+
+// Like iterative_deepening, stopping at `deadline` and calling `on_iteration` after every
+// completed iteration.
+pub fn iterative_deepening_until<'a, T, U, O, M, P, F>(
+    root: &T,
+    maximize: bool,
+    max_depth: usize,
+    deadline: &'a Deadline,
+    search: Search<'a, T, U, O, M, P>,
+    mut on_iteration: F
+) -> Option<IterationResult<T, U::Score>>
+where
+    T: GameState + Clone,
+    U: Heuristic<T>,
+    O: MoveOrdering<T>,
+    M: SearchTable<T, U::Score>,
+    P: Fn(&T) -> Option<U::Score>,
+    F: FnMut(&IterationResult<T, U::Score>)
+{
+    let states = root.next_states();
+    let mut order: Vec<usize> = (0..states.len()).collect();
+    let mut result: Option<IterationResult<T, U::Score>> = None;
+    let mut search = search.with_principal_variation();
+    for depth in 1..=max_depth.max(1) {
+        if depth == 2 {
+            search = search.with_deadline(deadline);
+        }
+        let Some(RootScores { best, score, principal_variation, .. }) = search.root_scores(&states, order.clone(), depth, maximize, false) else {
+            if let Some(r) = result.as_mut() {
+                r.nodes = search.nodes();
+            }
+            break;
+        };
+        let position = order.iter().position(|&i| i == best).unwrap();
+        order[..=position].rotate_right(1);
+        let iteration = IterationResult { best: states[best].clone(), score, depth, principal_variation, nodes: search.nodes() };
+        on_iteration(&iteration);
+        result = Some(iteration);
+        if deadline.passed() {
+            break;
+        }
+    }
+    if let Some(r) = &result {
+        info!("Iterative deepening reached depth {} with score {:?}", r.depth, r.score);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deadline_expires() {
+        let deadline = Deadline::after(Duration::ZERO);
+        assert!(deadline.passed());
+        let expired = (0..CHECK_INTERVAL).any(|_| deadline.poll());
+        assert!(expired);
+        assert!(deadline.poll());
+    }
+
+    #[test]
+    fn test_deadline_stop_flag() {
+        let stop = Arc::new(AtomicBool::new(false));
+        let deadline = Deadline::never().with_stop(stop.clone());
+        assert!(!(0..CHECK_INTERVAL * 4).any(|_| deadline.poll()));
+        stop.store(true, Ordering::Relaxed);
+        assert!(deadline.passed());
+        assert!((0..CHECK_INTERVAL).any(|_| deadline.poll()));
+        assert_eq!(deadline.nodes(), CHECK_INTERVAL * 5);
+    }
+
+    #[test]
+    fn test_deadline_not_expired() {
+        let deadline = Deadline::after(Duration::from_secs(3600));
+        assert!(!(0..CHECK_INTERVAL * 4).any(|_| deadline.poll()));
+        assert!(!deadline.passed());
+    }
+}
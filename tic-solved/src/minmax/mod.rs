@@ -4,10 +4,25 @@ pub trait GameState: Sized {
     fn next_states(&self) -> Vec<Self>;
 }
 
+pub trait ZobristHash {
+    fn zobrist_hash(&self) -> u64;
+}
+
+// Identifies the move leading from a position to one of its children, so moves can be compared
+// across different positions.
+pub trait MoveKey {
+    fn move_key(&self, child: &Self) -> usize;
+}
+
 pub trait Heuristic<T: GameState> {
     type Score: Copy + PartialEq + PartialOrd + Debug;
     fn score(game_state: &T) -> Self::Score;
 }
 
 pub mod naive;
-pub mod alphabeta;
\ No newline at end of file
+pub mod alphabeta;
+pub mod transposition;
+pub mod iterative;
+pub mod ordering;
+pub mod negamax;
+pub mod perft;
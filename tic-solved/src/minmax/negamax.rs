@@ -0,0 +1,183 @@
+use std::ops::{Add, Neg, Sub};
+
+use crate::minmax::{GameState, Heuristic};
+
+// Scores the negamax family can search with. They need negating to switch sides, and a smallest
+// step so MTD(f) and principal variation search can build null windows.
+pub trait NegamaxScore: Copy + PartialOrd + Neg<Output = Self> + Add<Output = Self> + Sub<Output = Self> {
+    // Symmetric around zero so negating never overflows.
+    const WORST: Self;
+    const BEST: Self;
+    const STEP: Self;
+}
+
+impl NegamaxScore for i32 {
+    const WORST: Self = -i32::MAX;
+    const BEST: Self = i32::MAX;
+    const STEP: Self = 1;
+}
+
+impl NegamaxScore for i64 {
+    const WORST: Self = -i64::MAX;
+    const BEST: Self = i64::MAX;
+    const STEP: Self = 1;
+}
+
+// Heuristic scores favour player 2; negamax wants them from the side to move.
+fn relative<S: NegamaxScore>(score: S, maximize: bool) -> S {
+    if maximize { score } else { -score }
+}
+
+// Fail soft alphabeta on relative scores. With `pvs` every move after the first is searched with
+// a null window and only searched again if it turns out to be better.
+fn negamax<T, U>(root: &T, depth: usize, maximize: bool, mut alpha: U::Score, beta: U::Score, pvs: bool) -> U::Score
+where
+    T: GameState,
+    U: Heuristic<T>,
+    U::Score: NegamaxScore
+{
+    let states = root.next_states();
+    if states.is_empty() || depth == 0 {
+        return relative(U::score(root), maximize);
+    }
+    let mut best = U::Score::WORST;
+    for (i, state) in states.iter().enumerate() {
+        let score = if pvs && i > 0 {
+            let null = -negamax::<T, U>(state, depth-1, !maximize, -alpha - U::Score::STEP, -alpha, pvs);
+            if null > alpha && null < beta {
+                -negamax::<T, U>(state, depth-1, !maximize, -beta, -alpha, pvs)
+            } else {
+                null
+            }
+        } else {
+            -negamax::<T, U>(state, depth-1, !maximize, -beta, -alpha, pvs)
+        };
+        if score > best {
+            best = score;
+        }
+        if score > alpha {
+            alpha = score;
+        }
+        if alpha >= beta {
+            break;
+        }
+    }
+    best
+}
+
+// Same as `negamax` but also reports which child produced the score.
+fn negamax_root<T, U>(root: &T, depth: usize, maximize: bool, mut alpha: U::Score, beta: U::Score, pvs: bool) -> (Option<usize>, U::Score)
+where
+    T: GameState,
+    U: Heuristic<T>,
+    U::Score: NegamaxScore
+{
+    let states = root.next_states();
+    if states.is_empty() || depth == 0 {
+        return (None, relative(U::score(root), maximize));
+    }
+    let mut best = (None, U::Score::WORST);
+    for (i, state) in states.iter().enumerate() {
+        let score = -negamax::<T, U>(state, depth-1, !maximize, -beta, -alpha, pvs);
+        if best.0.is_none() || score > best.1 {
+            best = (Some(i), score);
+        }
+        if score > alpha {
+            alpha = score;
+        }
+        if alpha >= beta {
+            break;
+        }
+    }
+    best
+}
+
+fn into_result<T, S>(root: &T, index: Option<usize>, score: S, maximize: bool) -> (T, S)
+where
+    T: GameState + Clone,
+    S: NegamaxScore
+{
+    let best = index.map_or_else(|| root.clone(), |i| root.next_states().swap_remove(i));
+    (best, relative(score, maximize))
+}
+
+// The functions below return the same (move, score) pair as `alphabeta::max_score` when
+// `maximize` is true and `alphabeta::min_score` otherwise.
+
+pub fn negamax_score<T, U>(root: &T, depth: usize, maximize: bool) -> (T, U::Score)
+where
+    T: GameState + Clone,
+    U: Heuristic<T>,
+    U::Score: NegamaxScore
+{
+    let (index, score) = negamax_root::<T, U>(root, depth, maximize, U::Score::WORST, U::Score::BEST, false);
+    into_result(root, index, score, maximize)
+}
+
+pub fn pvs_score<T, U>(root: &T, depth: usize, maximize: bool) -> (T, U::Score)
+where
+    T: GameState + Clone,
+    U: Heuristic<T>,
+    U::Score: NegamaxScore
+{
+    let (index, score) = negamax_root::<T, U>(root, depth, maximize, U::Score::WORST, U::Score::BEST, true);
+    into_result(root, index, score, maximize)
+}
+
+// Converges on the score with null window searches only, starting from `first_guess` (in the
+// heuristic's orientation). The closer the guess, the fewer passes it needs.
+pub fn mtdf_score<T, U>(root: &T, depth: usize, maximize: bool, first_guess: U::Score) -> (T, U::Score)
+where
+    T: GameState + Clone,
+    U: Heuristic<T>,
+    U::Score: NegamaxScore
+{
+    let mut guess = relative(first_guess, maximize);
+    let mut lower = U::Score::WORST;
+    let mut upper = U::Score::BEST;
+    let mut best = None;
+    while lower < upper {
+        let beta = if guess == lower { guess + U::Score::STEP } else { guess };
+        let (index, score) = negamax_root::<T, U>(root, depth, maximize, beta - U::Score::STEP, beta, false);
+        guess = score;
+        if score < beta {
+            upper = score;
+            // A failed low pass only bounds its move, so keep the move of the last pass that failed high.
+            best = best.or(index);
+        } else {
+            lower = score;
+            best = index;
+        }
+    }
+    into_result(root, best, guess, maximize)
+}
+
+fn widen<S: NegamaxScore>(delta: S) -> S {
+    if delta < S::BEST - delta { delta + delta } else { S::BEST }
+}
+
+// Searches a window of `delta` around `guess` (in the heuristic's orientation), doubling it on the
+// side that failed until the score lands inside.
+pub fn aspiration_score<T, U>(root: &T, depth: usize, maximize: bool, guess: U::Score, delta: U::Score) -> (T, U::Score)
+where
+    T: GameState + Clone,
+    U: Heuristic<T>,
+    U::Score: NegamaxScore
+{
+    let guess = relative(guess, maximize);
+    let delta = if delta < U::Score::STEP { U::Score::STEP } else { delta };
+    let mut below = delta;
+    let mut above = delta;
+    loop {
+        let alpha = if guess > U::Score::WORST + below { guess - below } else { U::Score::WORST };
+        let beta = if guess < U::Score::BEST - above { guess + above } else { U::Score::BEST };
+        let (index, score) = negamax_root::<T, U>(root, depth, maximize, alpha, beta, true);
+        if score <= alpha && alpha > U::Score::WORST {
+            below = widen(below);
+        } else if score >= beta && beta < U::Score::BEST {
+            above = widen(above);
+        } else {
+            return into_result(root, index, score, maximize);
+        }
+    }
+}
@@ -0,0 +1,172 @@
+use std::{collections::HashMap, marker::PhantomData};
+
+use crate::minmax::{GameState, Heuristic, MoveKey};
+
+// Decides the order in which the search visits the children of a position. Searching the best
+// move first is what lets alphabeta prune, so a good ordering shrinks the tree dramatically.
+pub trait MoveOrdering<T> {
+    // Indices of `states`, the children of `parent`, in the order they should be searched.
+    // `ply` is the distance from the root and `maximize` tells whose move it is.
+    fn order(&mut self, parent: &T, states: &[T], ply: usize, maximize: bool) -> Vec<usize>;
+
+    // Called when `state` caused a cutoff in `parent` while searching `depth` plies deep.
+    fn record_cutoff(&mut self, _parent: &T, _state: &T, _ply: usize, _depth: usize) {}
+}
+
+impl<T, O: MoveOrdering<T> + ?Sized> MoveOrdering<T> for &mut O {
+    fn order(&mut self, parent: &T, states: &[T], ply: usize, maximize: bool) -> Vec<usize> {
+        (**self).order(parent, states, ply, maximize)
+    }
+
+    fn record_cutoff(&mut self, parent: &T, state: &T, ply: usize, depth: usize) {
+        (**self).record_cutoff(parent, state, ply, depth)
+    }
+}
+
+// The order `next_states` generates moves in, which is what plain alphabeta uses.
+pub struct GenerationOrder;
+
+impl<T> MoveOrdering<T> for GenerationOrder {
+    fn order(&mut self, _parent: &T, states: &[T], _ply: usize, _maximize: bool) -> Vec<usize> {
+        (0..states.len()).collect()
+    }
+}
+
+// Sorts children by their static evaluation, best for the side to move first.
+pub struct HeuristicOrder<U> {
+    heuristic: PhantomData<U>,
+}
+
+impl<U> HeuristicOrder<U> {
+    pub fn new() -> Self {
+        HeuristicOrder { heuristic: PhantomData }
+    }
+}
+
+impl<U> Default for HeuristicOrder<U> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: GameState, U: Heuristic<T>> MoveOrdering<T> for HeuristicOrder<U> {
+    fn order(&mut self, _parent: &T, states: &[T], _ply: usize, maximize: bool) -> Vec<usize> {
+        let scores: Vec<U::Score> = states.iter().map(U::score).collect();
+        let mut order: Vec<usize> = (0..states.len()).collect();
+        order.sort_by(|&a, &b| {
+            let ordering = scores[b].partial_cmp(&scores[a]).unwrap_or(std::cmp::Ordering::Equal);
+            if maximize { ordering } else { ordering.reverse() }
+        });
+        order
+    }
+}
+
+// Moves that caused a cutoff in a sibling position (same ply) are likely to cut here too,
+// so up to two of them per ply are tried first. The remaining moves follow in `inner`'s order.
+pub struct KillerMoves<O> {
+    killers: Vec<[Option<usize>; 2]>,
+    inner: O,
+}
+
+impl<O> KillerMoves<O> {
+    pub fn new(inner: O) -> Self {
+        KillerMoves { killers: Vec::new(), inner }
+    }
+
+    pub fn killers(&self, ply: usize) -> [Option<usize>; 2] {
+        self.killers.get(ply).copied().unwrap_or([None, None])
+    }
+}
+
+impl<T: MoveKey, O: MoveOrdering<T>> MoveOrdering<T> for KillerMoves<O> {
+    fn order(&mut self, parent: &T, states: &[T], ply: usize, maximize: bool) -> Vec<usize> {
+        let mut order = self.inner.order(parent, states, ply, maximize);
+        let killers = self.killers(ply);
+        let mut front = 0;
+        for killer in killers.into_iter().flatten() {
+            if let Some(position) = order.iter().position(|&i| parent.move_key(&states[i]) == killer) {
+                if position >= front {
+                    order[front..=position].rotate_right(1);
+                    front += 1;
+                }
+            }
+        }
+        order
+    }
+
+    fn record_cutoff(&mut self, parent: &T, state: &T, ply: usize, depth: usize) {
+        if self.killers.len() <= ply {
+            self.killers.resize(ply + 1, [None, None]);
+        }
+        let key = parent.move_key(state);
+        let slot = &mut self.killers[ply];
+        if slot[0] != Some(key) {
+            slot[1] = slot[0];
+            slot[0] = Some(key);
+        }
+        self.inner.record_cutoff(parent, state, ply, depth);
+    }
+}
+
+// Moves that caused cutoffs anywhere in the tree are tried first, weighted by depth squared
+// so cutoffs near the root count the most.
+#[derive(Default)]
+pub struct HistoryHeuristic {
+    history: HashMap<usize, u64>,
+}
+
+impl HistoryHeuristic {
+    pub fn new() -> Self {
+        HistoryHeuristic { history: HashMap::new() }
+    }
+
+    pub fn history(&self, key: usize) -> u64 {
+        self.history.get(&key).copied().unwrap_or(0)
+    }
+}
+
+impl<T: MoveKey> MoveOrdering<T> for HistoryHeuristic {
+    fn order(&mut self, parent: &T, states: &[T], _ply: usize, _maximize: bool) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..states.len()).collect();
+        order.sort_by_key(|&i| std::cmp::Reverse(self.history(parent.move_key(&states[i]))));
+        order
+    }
+
+    fn record_cutoff(&mut self, parent: &T, state: &T, _ply: usize, depth: usize) {
+        let depth = depth as u64;
+        *self.history.entry(parent.move_key(state)).or_insert(0) += depth * depth;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, PartialEq, Debug)]
+    struct Number(usize);
+
+    impl MoveKey for Number {
+        fn move_key(&self, child: &Self) -> usize {
+            child.0
+        }
+    }
+
+    #[test]
+    fn test_killers_first() {
+        let states: Vec<Number> = (0..5).map(Number).collect();
+        let mut ordering = KillerMoves::new(GenerationOrder);
+        ordering.record_cutoff(&Number(0), &Number(3), 2, 1);
+        ordering.record_cutoff(&Number(0), &Number(1), 2, 1);
+        assert_eq!(ordering.order(&Number(0), &states, 2, true), vec![1, 3, 0, 2, 4]);
+        assert_eq!(ordering.order(&Number(0), &states, 1, true), vec![0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_history_order() {
+        let states: Vec<Number> = (0..4).map(Number).collect();
+        let mut ordering = HistoryHeuristic::new();
+        ordering.record_cutoff(&Number(0), &Number(2), 0, 1);
+        ordering.record_cutoff(&Number(0), &Number(3), 0, 3);
+        assert_eq!(ordering.order(&Number(0), &states, 0, true), vec![3, 2, 0, 1]);
+    }
+}
@@ -0,0 +1,55 @@
+use crate::minmax::{GameState, MoveKey};
+
+// Counts the positions exactly `depth` plies below `root`; lines that end the game earlier add nothing.
+pub fn perft<T: GameState>(root: &T, depth: usize) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    let states = root.next_states();
+    if depth == 1 {
+        return states.len() as u64;
+    }
+    states.iter().map(|state| perft(state, depth - 1)).sum()
+}
+
+// perft split by root move, keyed by `move_key` in `next_states` order. Comparing two move
+// generators move by move narrows a mismatch down to the line that causes it.
+pub fn perft_divide<T: GameState + MoveKey>(root: &T, depth: usize) -> Vec<(usize, u64)> {
+    root.next_states()
+        .iter()
+        .map(|state| (root.move_key(state), perft(state, depth.saturating_sub(1))))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Counts down to zero, branching into every smaller number.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Countdown(usize);
+
+    impl GameState for Countdown {
+        fn next_states(&self) -> Vec<Countdown> {
+            (0..self.0).map(Countdown).collect()
+        }
+    }
+
+    impl MoveKey for Countdown {
+        fn move_key(&self, child: &Self) -> usize {
+            child.0
+        }
+    }
+
+    #[test]
+    fn test_perft_counts_leaves() {
+        assert_eq!(perft(&Countdown(4), 0), 1);
+        assert_eq!(perft(&Countdown(4), 1), 4);
+        // 0 ends immediately, 1 has one line (1 -> 0), 2 has two and 3 has four.
+        assert_eq!(perft(&Countdown(4), 2), 1 + 2 + 3);
+        assert_eq!(perft(&Countdown(4), 3), 1 + 3);
+        assert_eq!(perft_divide(&Countdown(4), 3), vec![(0, 0), (1, 0), (2, 1), (3, 3)]);
+        let total: u64 = perft_divide(&Countdown(5), 3).iter().map(|(_, n)| n).sum();
+        assert_eq!(total, perft(&Countdown(5), 3));
+    }
+}
@@ -0,0 +1,257 @@
+use std::mem::size_of;
+
+use crate::minmax::ZobristHash;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bound {
+    Exact,
+    Lower, // The real score is at least `score` (search failed high).
+    Upper, // The real score is at most `score` (search failed low).
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TableEntry<S> {
+    pub key: u64,
+    pub depth: usize,
+    pub score: S,
+    pub bound: Bound,
+    pub best_move: Option<usize>, // Index into `GameState::next_states`.
+    generation: u8,
+}
+
+impl<S> TableEntry<S> {
+    pub fn new(key: u64, depth: usize, score: S, bound: Bound, best_move: Option<usize>) -> Self {
+        TableEntry { key, depth, score, bound, best_move, generation: 0 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TableStats {
+    pub probes: u64,
+    pub hits: u64,
+    pub stores: u64,
+    pub replacements: u64,
+    pub rejected_stores: u64,
+}
+
+impl TableStats {
+    pub fn hit_rate(&self) -> f64 {
+        if self.probes == 0 {
+            return 0.0;
+        }
+        self.hits as f64 / self.probes as f64
+    }
+}
+
+// The capacity of the tables the strategies and the engine search with, about 10MB for i32 scores.
+pub const DEFAULT_CAPACITY: usize = 1 << 18;
+
+// Fixed size table indexed by `key % capacity`.
+// Replacement is depth preferred, but entries left over from an earlier search are always replaced.
+pub struct TranspositionTable<S> {
+    entries: Vec<Option<TableEntry<S>>>,
+    generation: u8,
+    stats: TableStats,
+}
+
+impl<S: Copy> TranspositionTable<S> {
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "Transposition table needs at least one entry");
+        TranspositionTable {
+            entries: vec![None; capacity],
+            generation: 0,
+            stats: TableStats::default(),
+        }
+    }
+
+    pub fn with_megabytes(megabytes: usize) -> Self {
+        let capacity = megabytes * 1024 * 1024 / size_of::<Option<TableEntry<S>>>();
+        Self::new(capacity.max(1))
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.iter().filter(|e| e.is_some()).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.iter().all(|e| e.is_none())
+    }
+
+    fn index(&self, key: u64) -> usize {
+        (key % self.entries.len() as u64) as usize
+    }
+
+    pub fn probe(&mut self, key: u64) -> Option<TableEntry<S>> {
+        self.stats.probes += 1;
+        let entry = self.entries[self.index(key)].filter(|e| e.key == key);
+        if entry.is_some() {
+            self.stats.hits += 1;
+        }
+        entry
+    }
+
+    pub fn store(&mut self, mut entry: TableEntry<S>) {
+        entry.generation = self.generation;
+        let index = self.index(entry.key);
+        let replace = match &self.entries[index] {
+            None => true,
+            Some(old) => old.key == entry.key || old.generation != self.generation || old.depth <= entry.depth,
+        };
+        if !replace {
+            self.stats.rejected_stores += 1;
+            return;
+        }
+        if matches!(&self.entries[index], Some(old) if old.key != entry.key) {
+            self.stats.replacements += 1;
+        }
+        self.stats.stores += 1;
+        self.entries[index] = Some(entry);
+    }
+
+    // Marks every current entry as stale, so it can be replaced regardless of depth.
+    pub fn new_search(&mut self) {
+        self.generation = self.generation.wrapping_add(1);
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.iter_mut().for_each(|e| *e = None);
+        self.generation = 0;
+        self.stats = TableStats::default();
+    }
+
+    pub fn stats(&self) -> TableStats {
+        self.stats
+    }
+
+    pub fn reset_stats(&mut self) {
+        self.stats = TableStats::default();
+    }
+}
+
+// What a search remembers about the positions it has seen. `key` is None when nothing is kept,
+// so positions aren't even hashed.
+pub trait SearchTable<T, S> {
+    fn key(&self, position: &T) -> Option<u64>;
+    fn probe(&mut self, key: u64) -> Option<TableEntry<S>>;
+    fn store(&mut self, entry: TableEntry<S>);
+}
+
+pub struct NoTable;
+
+impl<T, S> SearchTable<T, S> for NoTable {
+    fn key(&self, _position: &T) -> Option<u64> {
+        None
+    }
+
+    fn probe(&mut self, _key: u64) -> Option<TableEntry<S>> {
+        None
+    }
+
+    fn store(&mut self, _entry: TableEntry<S>) {}
+}
+
+impl<T: ZobristHash, S: Copy> SearchTable<T, S> for TranspositionTable<S> {
+    fn key(&self, position: &T) -> Option<u64> {
+        Some(position.zobrist_hash())
+    }
+
+    fn probe(&mut self, key: u64) -> Option<TableEntry<S>> {
+        TranspositionTable::probe(self, key)
+    }
+
+    fn store(&mut self, entry: TableEntry<S>) {
+        TranspositionTable::store(self, entry)
+    }
+}
+
+impl<T, S, M: SearchTable<T, S> + ?Sized> SearchTable<T, S> for &mut M {
+    fn key(&self, position: &T) -> Option<u64> {
+        (**self).key(position)
+    }
+
+    fn probe(&mut self, key: u64) -> Option<TableEntry<S>> {
+        (**self).probe(key)
+    }
+
+    fn store(&mut self, entry: TableEntry<S>) {
+        (**self).store(entry)
+    }
+}
+
+impl<T, S, M: SearchTable<T, S>> SearchTable<T, S> for Option<M> {
+    fn key(&self, position: &T) -> Option<u64> {
+        self.as_ref().and_then(|table| table.key(position))
+    }
+
+    fn probe(&mut self, key: u64) -> Option<TableEntry<S>> {
+        self.as_mut().and_then(|table| table.probe(key))
+    }
+
+    fn store(&mut self, entry: TableEntry<S>) {
+        if let Some(table) = self {
+            table.store(entry);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_probe_after_store() {
+        let mut table = TranspositionTable::new(16);
+        assert!(table.probe(3).is_none());
+        table.store(TableEntry::new(3, 2, 10, Bound::Exact, Some(1)));
+        let entry = table.probe(3).unwrap();
+        assert_eq!(entry.score, 10);
+        assert_eq!(entry.best_move, Some(1));
+        assert_eq!(table.stats().probes, 2);
+        assert_eq!(table.stats().hits, 1);
+        assert_eq!(table.stats().hit_rate(), 0.5);
+    }
+
+    #[test]
+    fn test_collision_is_not_a_hit() {
+        let mut table = TranspositionTable::new(16);
+        table.store(TableEntry::new(3, 2, 10, Bound::Exact, None));
+        assert!(table.probe(19).is_none());
+    }
+
+    #[test]
+    fn test_depth_preferred_replacement() {
+        let mut table = TranspositionTable::new(16);
+        table.store(TableEntry::new(3, 5, 10, Bound::Exact, None));
+        table.store(TableEntry::new(19, 2, 20, Bound::Lower, None));
+        assert_eq!(table.probe(3).unwrap().score, 10);
+        assert_eq!(table.stats().rejected_stores, 1);
+
+        table.store(TableEntry::new(19, 6, 20, Bound::Lower, None));
+        assert!(table.probe(3).is_none());
+        assert_eq!(table.probe(19).unwrap().score, 20);
+        assert_eq!(table.stats().replacements, 1);
+    }
+
+    #[test]
+    fn test_stale_entries_are_replaced() {
+        let mut table = TranspositionTable::new(16);
+        table.store(TableEntry::new(3, 5, 10, Bound::Exact, None));
+        table.new_search();
+        table.store(TableEntry::new(19, 1, 20, Bound::Upper, None));
+        assert_eq!(table.probe(19).unwrap().score, 20);
+    }
+
+    #[test]
+    fn test_bounded_memory() {
+        let mut table = TranspositionTable::new(8);
+        for key in 0..100 {
+            table.store(TableEntry::new(key, 1, 0, Bound::Exact, None));
+        }
+        assert_eq!(table.capacity(), 8);
+        assert_eq!(table.len(), 8);
+    }
+}
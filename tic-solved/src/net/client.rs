@@ -0,0 +1,191 @@
+use std::{
+    io::{self, BufRead, BufReader},
+    net::{TcpStream, ToSocketAddrs},
+    sync::{Arc, Mutex},
+};
+
+use log::info;
+use crate::{
+    tic_array::{tic::*, tic_simulator::{GameResult, Strategy}},
+    game::HumanPlayerInterface,
+    net::*,
+};
+
+struct Connection {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Connection {
+    fn open<A: ToSocketAddrs>(address: A) -> io::Result<Connection> {
+        let writer = TcpStream::connect(address)?;
+        let reader = BufReader::new(writer.try_clone()?);
+        Ok(Connection { reader, writer })
+    }
+
+    fn send(&mut self, message: &ClientMessage) -> io::Result<()> {
+        send(&mut self.writer, message)
+    }
+
+    fn receive(&mut self) -> io::Result<ServerMessage> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "The server closed the connection"));
+        }
+        ServerMessage::parse(&line).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+// A seat in a game on a server, taken once an opponent has joined.
+pub struct RemoteGame {
+    pub game: usize,
+    pub seat: Player,
+    pub start: Board,
+    pub opponent: RemoteOpponent,
+}
+
+impl RemoteGame {
+    pub fn join<A: ToSocketAddrs>(address: A) -> io::Result<RemoteGame> {
+        let mut connection = Connection::open(address)?;
+        connection.send(&ClientMessage::JoinPlayer)?;
+        let (game, seat) = loop {
+            match connection.receive()? {
+                ServerMessage::Welcome(game, Role::Player(seat)) => break (game, seat),
+                ServerMessage::Error(e) => return Err(io::Error::other(e)),
+                _ => {}
+            }
+        };
+        let start = loop {
+            match connection.receive()? {
+                ServerMessage::Board(board) => break board,
+                ServerMessage::Error(e) => return Err(io::Error::other(e)),
+                _ => {}
+            }
+        };
+        let state = RemoteState { connection, board: start.clone(), result: None };
+        Ok(RemoteGame { game, seat, start, opponent: RemoteOpponent { seat, state: Arc::new(Mutex::new(state)) } })
+    }
+}
+
+struct RemoteState {
+    connection: Connection,
+    board: Board, // The position as the server last sent it.
+    result: Option<GameResult>,
+}
+
+impl RemoteState {
+    fn exchange(&mut self, game_state: &Board, seat: Player) -> io::Result<Option<Board>> {
+        if self.result.is_some() {
+            return Ok(None);
+        }
+        if *game_state != self.board {
+            let Some(m) = self.board.move_to(game_state) else {
+                info!("The position is not one move on from the server's");
+                return Ok(None);
+            };
+            self.connection.send(&ClientMessage::Move(m))?;
+        }
+        loop {
+            match self.connection.receive()? {
+                ServerMessage::Board(board) => {
+                    self.board = board;
+                    if self.board.who_turn() == seat && self.board != *game_state {
+                        return Ok(Some(self.board.clone()));
+                    }
+                }
+                ServerMessage::GameOver(result) => {
+                    self.result = Some(result);
+                    return Ok(None);
+                }
+                ServerMessage::Illegal(reason) | ServerMessage::Error(reason) => {
+                    info!("The server refused our move: {}", reason);
+                    return Ok(None);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn finish(&mut self, end: &Board) -> io::Result<GameResult> {
+        if let Some(result) = self.result {
+            return Ok(result);
+        }
+        match self.board.move_to(end) {
+            Some(m) => self.connection.send(&ClientMessage::Move(m))?,
+            None => self.connection.send(&ClientMessage::Resign)?,
+        }
+        loop {
+            match self.connection.receive()? {
+                ServerMessage::GameOver(result) => {
+                    self.result = Some(result);
+                    return Ok(result);
+                }
+                ServerMessage::Illegal(reason) | ServerMessage::Error(reason) => return Err(io::Error::other(reason)),
+                _ => {}
+            }
+        }
+    }
+}
+
+// The player at the other end of the connection, as a strategy for our side of the game.
+// get_move sends the move that led to the position it's given and waits for the answer. None
+// means the game ended without one: the opponent resigned or left, or the server refused the move.
+// A strategy isn't asked for a move once the game is over, so call finish at the end. Clones share
+// the connection.
+#[derive(Clone)]
+pub struct RemoteOpponent {
+    seat: Player,
+    state: Arc<Mutex<RemoteState>>,
+}
+
+impl RemoteOpponent {
+    // How the server says the game ended, once it has.
+    pub fn result(&self) -> Option<GameResult> {
+        self.state.lock().unwrap().result
+    }
+
+    // Tells the server how the game ended here: sends the move that ended it, or resigns if it
+    // ended without one, and returns the result the server settles on.
+    pub fn finish(&self, end: &Board) -> io::Result<GameResult> {
+        self.state.lock().unwrap().finish(end)
+    }
+
+    pub fn resign(&self) -> io::Result<()> {
+        self.state.lock().unwrap().connection.send(&ClientMessage::Resign)
+    }
+}
+
+impl Strategy for RemoteOpponent {
+    fn get_move(&self, game_state: &Board) -> Option<Board> {
+        let mut state = self.state.lock().unwrap();
+        state.exchange(game_state, self.seat).unwrap_or_else(|e| {
+            info!("Lost the connection to the server: {}", e);
+            None
+        })
+    }
+}
+
+// Shows every position of a game in progress on `interface` until it ends, the newest game by
+// default.
+// Returns None if the connection closed before the end.
+pub fn spectate<A: ToSocketAddrs, I: HumanPlayerInterface>(address: A, game: Option<usize>, interface: &mut I) -> io::Result<Option<GameResult>> {
+    let mut connection = Connection::open(address)?;
+    connection.send(&ClientMessage::JoinSpectator(game))?;
+    let mut board = Board::new();
+    loop {
+        match connection.receive() {
+            Ok(ServerMessage::Board(next)) => {
+                interface.show_position(&next);
+                board = next;
+            }
+            Ok(ServerMessage::GameOver(result)) => {
+                interface.show_result(result, &board);
+                return Ok(Some(result));
+            }
+            Ok(ServerMessage::Error(e)) => return Err(io::Error::other(e)),
+            Ok(_) => {}
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e),
+        }
+    }
+}
@@ -0,0 +1,192 @@
+pub mod server;
+pub mod client;
+
+use std::{fmt, io::{self, Write}};
+
+use crate::tic_array::{
+    tic::*,
+    tic_move::Move,
+    tic_simulator::GameResult,
+    game_record::{format_result, parse_result},
+};
+
+// Line based protocol for playing over TCP. Clients send
+//
+//   join [player]                 take a seat in the next game that needs a player
+//   join spectator [GAME]         watch a game in progress, the newest one by default
+//   move B2b2
+//   resign
+//
+// and the server answers with
+//
+//   welcome GAME player1|player2|spectator
+//   start                         both seats are taken
+//   board <board>                 the position, after `start` and after every move
+//   played player1 B2b2
+//   illegal <reason>              only to the player whose move it was
+//   gameover <result>             results as game records write them
+//   error <message>
+//
+// <board> is the 81 cells on one line, as BoardResult parses them. Leaving a game before it is
+// over forfeits it. A game is forgotten once it is over.
+
+pub const DEFAULT_ADDRESS: &str = "127.0.0.1:7881";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Player(Player),
+    Spectator,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClientMessage {
+    JoinPlayer,
+    JoinSpectator(Option<usize>),
+    Move(Move),
+    Resign,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ServerMessage {
+    Welcome(usize, Role),
+    Start,
+    Board(Board),
+    Played(Player, Move),
+    Illegal(String),
+    GameOver(GameResult),
+    Error(String),
+}
+
+fn format_player(player: Player) -> &'static str {
+    match player {
+        Player::Player1 => "player1",
+        Player::Player2 => "player2",
+    }
+}
+
+fn parse_player(s: &str) -> Result<Player, String> {
+    match s {
+        "player1" => Ok(Player::Player1),
+        "player2" => Ok(Player::Player2),
+        _ => Err(format!("Invalid player: {}", s)),
+    }
+}
+
+fn format_board(board: &Board) -> String {
+    board.to_string().split_whitespace().collect()
+}
+
+impl ClientMessage {
+    pub fn parse(line: &str) -> Result<ClientMessage, String> {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        match tokens.as_slice() {
+            ["join"] | ["join", "player"] => Ok(ClientMessage::JoinPlayer),
+            ["join", "spectator"] => Ok(ClientMessage::JoinSpectator(None)),
+            ["join", "spectator", game] => game.parse()
+                .map(|game| ClientMessage::JoinSpectator(Some(game)))
+                .map_err(|_| format!("Invalid game: {}", game)),
+            ["move", notation] => notation.parse().map(ClientMessage::Move),
+            ["resign"] => Ok(ClientMessage::Resign),
+            _ => Err(format!("Unknown command: {}", line.trim())),
+        }
+    }
+}
+
+impl fmt::Display for ClientMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientMessage::JoinPlayer => write!(f, "join player"),
+            ClientMessage::JoinSpectator(None) => write!(f, "join spectator"),
+            ClientMessage::JoinSpectator(Some(game)) => write!(f, "join spectator {}", game),
+            ClientMessage::Move(m) => write!(f, "move {}", m),
+            ClientMessage::Resign => write!(f, "resign"),
+        }
+    }
+}
+
+impl ServerMessage {
+    pub fn parse(line: &str) -> Result<ServerMessage, String> {
+        let line = line.trim();
+        let (command, rest) = line.split_once(' ').unwrap_or((line, ""));
+        match command {
+            "welcome" => {
+                let (game, role) = rest.split_once(' ').ok_or(format!("Invalid welcome: {}", line))?;
+                let game = game.parse().map_err(|_| format!("Invalid game: {}", game))?;
+                let role = match role {
+                    "spectator" => Role::Spectator,
+                    player => Role::Player(parse_player(player)?),
+                };
+                Ok(ServerMessage::Welcome(game, role))
+            }
+            "start" => Ok(ServerMessage::Start),
+            "board" => BoardResult::from(rest).into_inner().map(ServerMessage::Board),
+            "played" => {
+                let (player, m) = rest.split_once(' ').ok_or(format!("Invalid played: {}", line))?;
+                Ok(ServerMessage::Played(parse_player(player)?, m.parse()?))
+            }
+            "illegal" => Ok(ServerMessage::Illegal(rest.to_string())),
+            "gameover" => parse_result(rest).map(ServerMessage::GameOver),
+            "error" => Ok(ServerMessage::Error(rest.to_string())),
+            _ => Err(format!("Unknown message: {}", line)),
+        }
+    }
+}
+
+impl fmt::Display for ServerMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ServerMessage::Welcome(game, Role::Player(player)) => write!(f, "welcome {} {}", game, format_player(*player)),
+            ServerMessage::Welcome(game, Role::Spectator) => write!(f, "welcome {} spectator", game),
+            ServerMessage::Start => write!(f, "start"),
+            ServerMessage::Board(board) => write!(f, "board {}", format_board(board)),
+            ServerMessage::Played(player, m) => write!(f, "played {} {}", format_player(*player), m),
+            ServerMessage::Illegal(reason) => write!(f, "illegal {}", reason),
+            ServerMessage::GameOver(result) => write!(f, "gameover {}", format_result(*result)),
+            ServerMessage::Error(message) => write!(f, "error {}", message),
+        }
+    }
+}
+
+fn send<W: Write, M: fmt::Display>(out: &mut W, message: &M) -> io::Result<()> {
+    writeln!(out, "{}", message)?;
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_server_messages_round_trip() {
+        let board = Board::new().play("B2b2".parse().unwrap()).unwrap();
+        for message in [
+            ServerMessage::Welcome(3, Role::Player(Player::Player2)),
+            ServerMessage::Welcome(0, Role::Spectator),
+            ServerMessage::Start,
+            ServerMessage::Board(board),
+            ServerMessage::Played(Player::Player1, "B2b2".parse().unwrap()),
+            ServerMessage::Illegal("B2b2 is illegal: the cell is taken".to_string()),
+            ServerMessage::GameOver(GameResult::Forfeit(Player::Player1)),
+            ServerMessage::Error("not your turn".to_string()),
+        ] {
+            assert_eq!(ServerMessage::parse(&message.to_string()), Ok(message));
+        }
+        assert!(ServerMessage::parse("hello").is_err());
+    }
+
+    #[test]
+    fn test_client_messages_round_trip() {
+        for message in [
+            ClientMessage::JoinPlayer,
+            ClientMessage::JoinSpectator(None),
+            ClientMessage::JoinSpectator(Some(2)),
+            ClientMessage::Move("C3a1".parse().unwrap()),
+            ClientMessage::Resign,
+        ] {
+            assert_eq!(ClientMessage::parse(&message.to_string()), Ok(message));
+        }
+        assert_eq!(ClientMessage::parse("join"), Ok(ClientMessage::JoinPlayer));
+        assert!(ClientMessage::parse("move Z9z9").is_err());
+        assert!(ClientMessage::parse("join spectator x").is_err());
+    }
+}
@@ -0,0 +1,293 @@
+use std::{
+    collections::BTreeMap,
+    io::{self, BufRead, BufReader},
+    net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    sync::{mpsc, Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use log::info;
+use crate::{
+    tic_array::{tic::*, tic_move::Move, tic_simulator::GameResult},
+    net::*,
+};
+
+struct Game {
+    board: Board,
+    players: [Option<Outbox>; 2],
+    spectators: Vec<Outbox>,
+    started: bool,
+}
+
+fn seat(player: Player) -> usize {
+    match player {
+        Player::Player1 => 0,
+        Player::Player2 => 1,
+    }
+}
+
+// How long a write to a client may block before the client is dropped.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(10);
+
+// The messages waiting for one client. A thread of its own writes them, so sending never blocks
+// and a client that stops reading only holds up itself. Once a write fails the connection is
+// shut down, which a player's connection notices and forfeits the game. Dropping the last
+// outbox of a connection ends the writer.
+#[derive(Clone)]
+struct Outbox(mpsc::Sender<ServerMessage>);
+
+impl Outbox {
+    fn open(mut stream: TcpStream) -> io::Result<Outbox> {
+        stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+        let (sender, receiver) = mpsc::channel::<ServerMessage>();
+        thread::spawn(move || {
+            for message in receiver {
+                if send(&mut stream, &message).is_err() {
+                    let _ = stream.shutdown(Shutdown::Both);
+                    break;
+                }
+            }
+        });
+        Ok(Outbox(sender))
+    }
+
+    // False once the connection is gone.
+    fn send(&self, message: ServerMessage) -> bool {
+        self.0.send(message).is_ok()
+    }
+}
+
+impl Game {
+    fn new(start: Board) -> Game {
+        Game { board: start, players: [None, None], spectators: Vec::new(), started: false }
+    }
+
+    // Spectators that went away are dropped. A player that went away is noticed by its own
+    // connection, which forfeits the game.
+    fn broadcast(&mut self, message: &ServerMessage) {
+        for player in self.players.iter().flatten() {
+            player.send(message.clone());
+        }
+        self.spectators.retain(|spectator| spectator.send(message.clone()));
+    }
+
+    fn result(&self) -> Option<GameResult> {
+        match self.board.outcome() {
+            GameOutcome::Win(player) => Some(GameResult::Win(player)),
+            GameOutcome::Draw => Some(GameResult::Draw),
+            GameOutcome::Ongoing => None,
+        }
+    }
+}
+
+// Why a client's request was turned down, answered as `illegal` or `error`.
+enum Refusal {
+    Illegal(String),
+    Error(String),
+}
+
+impl From<Refusal> for ServerMessage {
+    fn from(refusal: Refusal) -> ServerMessage {
+        match refusal {
+            Refusal::Illegal(reason) => ServerMessage::Illegal(reason),
+            Refusal::Error(message) => ServerMessage::Error(message),
+        }
+    }
+}
+
+fn error(message: &str) -> Refusal {
+    Refusal::Error(message.to_string())
+}
+
+// The games waiting for players or in progress, by id. A game is dropped as soon as it ends, and
+// with it the outboxes of its clients.
+struct Lobby {
+    start: Board,
+    next_id: usize,
+    games: BTreeMap<usize, Game>,
+}
+
+impl Lobby {
+    fn new(start: Board) -> Lobby {
+        Lobby { start, next_id: 0, games: BTreeMap::new() }
+    }
+
+    fn finish(&mut self, id: usize, result: GameResult) {
+        if let Some(mut game) = self.games.remove(&id) {
+            info!("Game {} is over", id);
+            game.broadcast(&ServerMessage::GameOver(result));
+        }
+    }
+
+    fn finish_if_over(&mut self, id: usize) {
+        if let Some(result) = self.games.get(&id).and_then(Game::result) {
+            self.finish(id, result);
+        }
+    }
+
+    fn join_player(&mut self, outbox: Outbox) -> (usize, Player) {
+        let id = match self.games.iter().find(|(_, game)| !game.started) {
+            Some((&id, _)) => id,
+            None => {
+                let id = self.next_id;
+                self.next_id += 1;
+                self.games.insert(id, Game::new(self.start.clone()));
+                id
+            }
+        };
+        let game = self.games.get_mut(&id).unwrap();
+        let player = if game.players[0].is_none() { Player::Player1 } else { Player::Player2 };
+        outbox.send(ServerMessage::Welcome(id, Role::Player(player)));
+        game.players[seat(player)] = Some(outbox);
+        if game.players.iter().all(Option::is_some) {
+            info!("Game {} starts", id);
+            game.started = true;
+            game.broadcast(&ServerMessage::Start);
+            game.broadcast(&ServerMessage::Board(game.board.clone()));
+            self.finish_if_over(id);
+        }
+        (id, player)
+    }
+
+    fn join_spectator(&mut self, id: Option<usize>, outbox: Outbox) -> Result<usize, Refusal> {
+        let id = id.or(self.games.keys().next_back().copied()).ok_or(error("there is no game to watch"))?;
+        let game = self.games.get_mut(&id).ok_or(error(&format!("there is no game {} in progress", id)))?;
+        outbox.send(ServerMessage::Welcome(id, Role::Spectator));
+        if game.started {
+            outbox.send(ServerMessage::Board(game.board.clone()));
+        }
+        game.spectators.push(outbox);
+        Ok(id)
+    }
+
+    fn play(&mut self, id: usize, player: Player, m: Move) -> Result<(), Refusal> {
+        let game = self.games.get_mut(&id).ok_or(error("the game is over"))?;
+        if !game.started {
+            return Err(error("the game has not started"));
+        }
+        if game.board.who_turn() != player {
+            return Err(error("it is not your turn"));
+        }
+        // get_legal_boards has the final say, play explains why a move is refused.
+        let next = game.board.play(m).map_err(|e| Refusal::Illegal(e.to_string()))?;
+        if !game.board.get_legal_boards().contains(&next) {
+            return Err(Refusal::Illegal(format!("{} is not a legal move", m)));
+        }
+        game.board = next;
+        game.broadcast(&ServerMessage::Played(player, m));
+        game.broadcast(&ServerMessage::Board(game.board.clone()));
+        self.finish_if_over(id);
+        Ok(())
+    }
+
+    fn resign(&mut self, id: usize, player: Player) -> Result<(), Refusal> {
+        match self.games.get(&id) {
+            Some(game) if game.started => {
+                self.finish(id, GameResult::Forfeit(player));
+                Ok(())
+            }
+            _ => Err(error("there is no game in progress")),
+        }
+    }
+
+    // A player who leaves a game in progress forfeits it. Before it starts the seat is freed,
+    // and a game nobody is waiting in is dropped.
+    fn leave(&mut self, id: usize, player: Player) {
+        let Some(game) = self.games.get_mut(&id) else {
+            return;
+        };
+        if game.started {
+            self.finish(id, GameResult::Forfeit(player));
+        } else {
+            game.players[seat(player)] = None;
+            if game.players.iter().all(Option::is_none) {
+                self.games.remove(&id);
+            }
+        }
+    }
+}
+
+// Hosts games for clients speaking the protocol in net/mod.rs. Players are paired in the order
+// they join, each connection is served on its own thread.
+pub struct Server {
+    listener: TcpListener,
+    lobby: Arc<Mutex<Lobby>>,
+}
+
+impl Server {
+    pub fn bind<A: ToSocketAddrs>(address: A) -> io::Result<Server> {
+        let listener = TcpListener::bind(address)?;
+        Ok(Server { listener, lobby: Arc::new(Mutex::new(Lobby::new(Board::new()))) })
+    }
+
+    // The position new games start from.
+    pub fn with_start(self, start: Board) -> Server {
+        self.lobby.lock().unwrap().start = start;
+        self
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    // Accepts connections until the listener fails.
+    pub fn run(&self) -> io::Result<()> {
+        for stream in self.listener.incoming() {
+            let stream = stream?;
+            let lobby = Arc::clone(&self.lobby);
+            thread::spawn(move || serve(stream, lobby));
+        }
+        Ok(())
+    }
+}
+
+fn serve(stream: TcpStream, lobby: Arc<Mutex<Lobby>>) {
+    let peer = stream.peer_addr().map_or("unknown".to_string(), |address| address.to_string());
+    info!("{} connected", peer);
+    let mut joined = None;
+    if let Err(e) = handle_commands(stream, &lobby, &mut joined) {
+        info!("{}: {}", peer, e);
+    }
+    if let Some((id, Role::Player(player))) = joined {
+        lobby.lock().unwrap().leave(id, player);
+    }
+    info!("{} disconnected", peer);
+}
+
+fn handle_commands(stream: TcpStream, lobby: &Mutex<Lobby>, joined: &mut Option<(usize, Role)>) -> io::Result<()> {
+    // Replies go through the same outbox as the games' messages, so they arrive in order.
+    let out = Outbox::open(stream.try_clone()?)?;
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let message = match ClientMessage::parse(&line) {
+            Ok(message) => message,
+            Err(e) => {
+                out.send(ServerMessage::Error(e));
+                continue;
+            }
+        };
+        let mut lobby = lobby.lock().unwrap();
+        let reply = match (message, *joined) {
+            (ClientMessage::JoinPlayer, None) => {
+                let (id, player) = lobby.join_player(out.clone());
+                *joined = Some((id, Role::Player(player)));
+                Ok(())
+            }
+            (ClientMessage::JoinSpectator(id), None) => lobby.join_spectator(id, out.clone())
+                .map(|id| *joined = Some((id, Role::Spectator))),
+            (ClientMessage::JoinPlayer | ClientMessage::JoinSpectator(_), Some(_)) => Err(error("you have already joined a game")),
+            (ClientMessage::Move(m), Some((id, Role::Player(player)))) => lobby.play(id, player, m),
+            (ClientMessage::Resign, Some((id, Role::Player(player)))) => lobby.resign(id, player),
+            (ClientMessage::Move(_) | ClientMessage::Resign, _) => Err(error("join a game as a player first")),
+        };
+        drop(lobby);
+        if let Err(reply) = reply {
+            out.send(ServerMessage::from(reply));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,217 @@
+use std::time::Duration;
+
+use crate::tic_array::{
+    tic::*,
+    tic_move::{IllegalMove, Move},
+    tic_simulator::*,
+    game_record::{RecordedMove, SavedTree},
+};
+
+pub type NodeId = usize;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    pub board: Board,
+    pub parent: Option<NodeId>,
+    pub chosen: Option<Move>, // None at the root.
+    pub elapsed: Duration,
+    pub info: MoveInfo,
+    pub children: Vec<NodeId>, // The first continues the main line, the others are variations.
+    redo: Option<NodeId>, // The child we last came back from.
+}
+
+// An interactive game: every position played so far as a tree, with the one on screen as the
+// current node. Playing a move that was played from here before goes back into that line instead
+// of adding it twice, and playing a new one after undoing starts a variation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Session {
+    nodes: Vec<Node>,
+    current: NodeId,
+}
+
+impl Session {
+    pub fn new(start: Board) -> Session {
+        let root = Node { board: start, parent: None, chosen: None, elapsed: Duration::ZERO, info: MoveInfo::default(), children: Vec::new(), redo: None };
+        Session { nodes: vec![root], current: 0 }
+    }
+
+    pub fn root(&self) -> NodeId {
+        0
+    }
+
+    pub fn current(&self) -> NodeId {
+        self.current
+    }
+
+    pub fn node(&self, id: NodeId) -> &Node {
+        &self.nodes[id]
+    }
+
+    pub fn start(&self) -> &Board {
+        &self.nodes[0].board
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.nodes[self.current].board
+    }
+
+    pub fn play(&mut self, m: Move) -> Result<NodeId, IllegalMove> {
+        self.play_with(m, Duration::ZERO, MoveInfo::default())
+    }
+
+    // Like play, keeping how long the move took and what the player knew about it.
+    pub fn play_with(&mut self, m: Move, elapsed: Duration, info: MoveInfo) -> Result<NodeId, IllegalMove> {
+        let next = self.board().play(m)?;
+        let existing = self.nodes[self.current].children.iter().copied().find(|&child| self.nodes[child].chosen == Some(m));
+        let child = match existing {
+            Some(child) => child,
+            None => {
+                let id = self.nodes.len();
+                self.nodes.push(Node { board: next, parent: Some(self.current), chosen: Some(m), elapsed, info, children: Vec::new(), redo: None });
+                self.nodes[self.current].children.push(id);
+                id
+            }
+        };
+        self.nodes[self.current].redo = Some(child);
+        self.current = child;
+        Ok(child)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.nodes[self.current].parent.is_some()
+    }
+
+    // Returns false at the start.
+    pub fn undo(&mut self) -> bool {
+        let Some(parent) = self.nodes[self.current].parent else {
+            return false;
+        };
+        self.nodes[parent].redo = Some(self.current);
+        self.current = parent;
+        true
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.nodes[self.current].children.is_empty()
+    }
+
+    // Goes forward along the line last undone, or the main line. Returns false at the end of it.
+    pub fn redo(&mut self) -> bool {
+        let node = &self.nodes[self.current];
+        match node.redo.or(node.children.first().copied()) {
+            Some(child) => {
+                self.current = child;
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Jumps to any position in the tree, for instance to branch off from it. Redo from its
+    // ancestors leads back to it. Returns false for an unknown node.
+    pub fn go_to(&mut self, id: NodeId) -> bool {
+        if id >= self.nodes.len() {
+            return false;
+        }
+        let mut child = id;
+        while let Some(parent) = self.nodes[child].parent {
+            self.nodes[parent].redo = Some(child);
+            child = parent;
+        }
+        self.current = id;
+        true
+    }
+
+    // Makes the line through `id` the main line.
+    pub fn promote(&mut self, id: NodeId) {
+        let mut child = id;
+        while let Some(parent) = self.nodes[child].parent {
+            let children = &mut self.nodes[parent].children;
+            if let Some(index) = children.iter().position(|&c| c == child) {
+                children[..=index].rotate_right(1);
+            }
+            child = parent;
+        }
+    }
+
+    // Nodes from the first move to the current one.
+    pub fn path(&self) -> Vec<NodeId> {
+        let mut path = Vec::new();
+        let mut id = self.current;
+        while let Some(parent) = self.nodes[id].parent {
+            path.push(id);
+            id = parent;
+        }
+        path.reverse();
+        path
+    }
+
+    pub fn line(&self) -> Vec<Move> {
+        self.path().into_iter().filter_map(|id| self.nodes[id].chosen).collect()
+    }
+
+    // The moves leading to the current position, as a game record has them.
+    pub fn line_records(&self) -> Vec<MoveRecord> {
+        self.path()
+            .into_iter()
+            .map(|id| {
+                let node = &self.nodes[id];
+                let player = self.nodes[node.parent.expect("Only the root has no parent")].board.who_turn();
+                MoveRecord { player, board: node.board.clone(), elapsed: node.elapsed, info: node.info.clone() }
+            })
+            .collect()
+    }
+
+    pub fn main_line(&self) -> Vec<NodeId> {
+        let mut line = Vec::new();
+        let mut id = self.root();
+        while let Some(&child) = self.nodes[id].children.first() {
+            line.push(child);
+            id = child;
+        }
+        line
+    }
+
+    fn recorded(&self, id: NodeId) -> RecordedMove {
+        let node = &self.nodes[id];
+        RecordedMove {
+            chosen: node.chosen.expect("Only the root has no move"),
+            elapsed: node.elapsed,
+            next: node.children.iter().map(|&child| self.recorded(child)).collect(),
+        }
+    }
+
+    // The whole tree in the game record format, with the result of the main line once it's over.
+    pub fn export(&self) -> SavedTree {
+        let end = self.main_line().last().map_or(self.start(), |&id| &self.nodes[id].board);
+        let result = match end.outcome() {
+            GameOutcome::Win(player) => Some(GameResult::Win(player)),
+            GameOutcome::Draw => Some(GameResult::Draw),
+            GameOutcome::Ongoing => None,
+        };
+        SavedTree {
+            headers: Vec::new(),
+            start: self.start().clone(),
+            result,
+            moves: self.nodes[0].children.iter().map(|&child| self.recorded(child)).collect(),
+        }
+    }
+
+    // Rebuilds the tree of a record, positioned at the end of its main line.
+    pub fn import(tree: &SavedTree) -> Result<Session, IllegalMove> {
+        let mut session = Session::new(tree.start.clone());
+        session.import_alternatives(&tree.moves)?;
+        let end = session.main_line().last().copied().unwrap_or(0);
+        session.go_to(end);
+        Ok(session)
+    }
+
+    fn import_alternatives(&mut self, alternatives: &[RecordedMove]) -> Result<(), IllegalMove> {
+        for recorded in alternatives {
+            self.play_with(recorded.chosen, recorded.elapsed, MoveInfo::default())?;
+            self.import_alternatives(&recorded.next)?;
+            self.undo();
+        }
+        Ok(())
+    }
+}
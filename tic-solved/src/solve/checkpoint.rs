@@ -0,0 +1,55 @@
+use std::{
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+use crate::solve::table::SolutionTable;
+
+// File layout (little endian):
+//   magic "TICSOLV2", entry count as u64, then the ranks of the solved positions in increasing
+//   order as u128, then one byte per solution in the same order.
+const MAGIC: &[u8; 8] = b"TICSOLV2";
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+// Written to a temporary file first so an interrupted save never corrupts the previous checkpoint.
+pub fn save(path: &Path, table: &mut SolutionTable) -> io::Result<()> {
+    let tmp_path = path.with_extension("tmp");
+    {
+        let (ranks, solutions) = table.compact();
+        let mut writer = BufWriter::new(File::create(&tmp_path)?);
+        writer.write_all(MAGIC)?;
+        writer.write_all(&(ranks.len() as u64).to_le_bytes())?;
+        for rank in ranks {
+            writer.write_all(&rank.to_le_bytes())?;
+        }
+        writer.write_all(solutions)?;
+        writer.flush()?;
+    }
+    fs::rename(tmp_path, path)
+}
+
+pub fn load(path: &Path) -> io::Result<SolutionTable> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut magic = [0; 8];
+    reader.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(invalid_data("Not a solver checkpoint"));
+    }
+    let mut count = [0; 8];
+    reader.read_exact(&mut count)?;
+    let count = u64::from_le_bytes(count) as usize;
+
+    let mut ranks = Vec::with_capacity(count);
+    let mut rank = [0; 16];
+    for _ in 0..count {
+        reader.read_exact(&mut rank)?;
+        ranks.push(u128::from_le_bytes(rank));
+    }
+    let mut solutions = vec![0; count];
+    reader.read_exact(&mut solutions)?;
+    SolutionTable::from_sorted(ranks, solutions).ok_or_else(|| invalid_data("Corrupt solver checkpoint"))
+}
@@ -0,0 +1,229 @@
+use std::{io, path::PathBuf};
+
+use log::info;
+
+use crate::{
+    tic_array::{tic::{GameOutcome, Player}, bitmap_tic::BitBoard, symmetry::Symmetric, ranking},
+    minmax::GameState,
+    solve::table::SolutionTable,
+};
+
+pub mod checkpoint;
+pub mod table;
+pub mod tablebase;
+
+// Solutions as bytes: 1 + outcome * 82 + distance, leaving 0 free to mark a missing entry.
+const DISTANCES: u8 = 82;
+
+// Game theoretic value of a position: the outcome under perfect play, never Ongoing, and the number
+// of plies until the game ends. The winner takes the shortest win, the loser the longest loss.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Solution {
+    pub outcome: GameOutcome,
+    pub distance: u8,
+}
+
+impl Solution {
+    // Larger is better for `player`.
+    pub(crate) fn rank(&self, player: Player) -> (u8, i16) {
+        let distance = self.distance as i16;
+        match self.outcome {
+            GameOutcome::Win(p) if p == player => (2, -distance),
+            GameOutcome::Win(_) => (0, distance),
+            GameOutcome::Draw | GameOutcome::Ongoing => (1, -distance),
+        }
+    }
+
+    pub(crate) fn one_ply_earlier(&self) -> Solution {
+        Solution { outcome: self.outcome, distance: self.distance + 1 }
+    }
+
+    pub(crate) fn to_byte(self) -> u8 {
+        let outcome = match self.outcome {
+            GameOutcome::Draw | GameOutcome::Ongoing => 0,
+            GameOutcome::Win(Player::Player1) => 1,
+            GameOutcome::Win(Player::Player2) => 2,
+        };
+        1 + outcome * DISTANCES + self.distance
+    }
+
+    pub(crate) fn from_byte(byte: u8) -> Option<Solution> {
+        let outcome = match byte.checked_sub(1)? / DISTANCES {
+            0 => GameOutcome::Draw,
+            1 => GameOutcome::Win(Player::Player1),
+            2 => GameOutcome::Win(Player::Player2),
+            _ => return None,
+        };
+        Some(Solution { outcome, distance: (byte - 1) % DISTANCES })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SolverStats {
+    pub nodes: u64,
+    pub cache_hits: u64,
+    pub checkpoints_written: u64,
+}
+
+struct CheckpointConfig {
+    path: PathBuf,
+    interval: usize,
+    solved_since_last: usize,
+}
+
+// Exhaustive solver for BitBoard positions. Results are cached under the rank of the canonical
+// orientation of each position, and can be checkpointed to disk so a long run can be resumed.
+pub struct Solver {
+    cache: SolutionTable,
+    checkpoint: Option<CheckpointConfig>,
+    stats: SolverStats,
+}
+
+impl Default for Solver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Solver {
+    pub fn new() -> Self {
+        Solver { cache: SolutionTable::new(), checkpoint: None, stats: SolverStats::default() }
+    }
+
+    // Resumes from `path` if it exists, and writes the cache back every `interval` newly solved positions.
+    pub fn with_checkpoint(path: impl Into<PathBuf>, interval: usize) -> io::Result<Self> {
+        let path = path.into();
+        let cache = if path.exists() {
+            let cache = checkpoint::load(&path)?;
+            info!("Resumed {} solved positions from {}", cache.len(), path.display());
+            cache
+        } else {
+            SolutionTable::new()
+        };
+        Ok(Solver {
+            cache,
+            checkpoint: Some(CheckpointConfig { path, interval: interval.max(1), solved_since_last: 0 }),
+            stats: SolverStats::default(),
+        })
+    }
+
+    pub fn stats(&self) -> SolverStats {
+        self.stats
+    }
+
+    pub fn solved_positions(&self) -> usize {
+        self.cache.len()
+    }
+
+    // Fails with InvalidInput for a position that isn't legal (see `ranking`).
+    pub fn solve(&mut self, board: &BitBoard) -> io::Result<Solution> {
+        if let Some(player) = board.winner() {
+            return Ok(Solution { outcome: GameOutcome::Win(player), distance: 0 });
+        }
+        let (canonical, _) = board.canonical();
+        let key = ranking::rank(&canonical)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Position is not legal"))?;
+        if let Some(solution) = self.cache.get(key) {
+            self.stats.cache_hits += 1;
+            return Ok(solution);
+        }
+        self.stats.nodes += 1;
+
+        let player = board.who_turn();
+        let states = board.next_states();
+        let mut best: Option<Solution> = None;
+        if states.iter().any(|next| next.winner() == Some(player)) {
+            best = Some(Solution { outcome: GameOutcome::Win(player), distance: 1 });
+        } else {
+            for next in &states {
+                let candidate = self.solve(next)?.one_ply_earlier();
+                if best.is_none_or(|b| candidate.rank(player) > b.rank(player)) {
+                    best = Some(candidate);
+                }
+            }
+        }
+        let solution = best.unwrap_or(Solution { outcome: GameOutcome::Draw, distance: 0 });
+        self.cache.insert(key, solution);
+        self.after_solved()?;
+        Ok(solution)
+    }
+
+    pub fn best_move(&mut self, board: &BitBoard) -> io::Result<Option<(BitBoard, Solution)>> {
+        if board.winner().is_some() {
+            return Ok(None);
+        }
+        let player = board.who_turn();
+        let mut best: Option<(BitBoard, Solution)> = None;
+        for next in board.next_states() {
+            let candidate = self.solve(&next)?.one_ply_earlier();
+            if best.as_ref().is_none_or(|(_, b)| candidate.rank(player) > b.rank(player)) {
+                best = Some((next, candidate));
+            }
+        }
+        Ok(best)
+    }
+
+    // The positions reached when both sides play perfectly, which proves the solution's distance.
+    pub fn principal_line(&mut self, board: &BitBoard) -> io::Result<Vec<BitBoard>> {
+        let mut line = Vec::new();
+        let mut current = board.clone();
+        while let Some((next, _)) = self.best_move(&current)? {
+            line.push(next.clone());
+            current = next;
+        }
+        Ok(line)
+    }
+
+    pub fn save_checkpoint(&mut self) -> io::Result<()> {
+        if let Some(config) = self.checkpoint.as_mut() {
+            checkpoint::save(&config.path, &mut self.cache)?;
+            config.solved_since_last = 0;
+            self.stats.checkpoints_written += 1;
+            info!("Checkpointed {} solved positions to {}", self.cache.len(), config.path.display());
+        }
+        Ok(())
+    }
+
+    fn after_solved(&mut self) -> io::Result<()> {
+        let due = match self.checkpoint.as_mut() {
+            Some(config) => {
+                config.solved_since_last += 1;
+                config.solved_since_last >= config.interval
+            },
+            None => false,
+        };
+        if due {
+            self.save_checkpoint()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rank_prefers_fast_wins_and_slow_losses() {
+        let win = |d| Solution { outcome: GameOutcome::Win(Player::Player1), distance: d };
+        let loss = |d| Solution { outcome: GameOutcome::Win(Player::Player2), distance: d };
+        let draw = Solution { outcome: GameOutcome::Draw, distance: 4 };
+        let p = Player::Player1;
+        assert!(win(1).rank(p) > win(3).rank(p));
+        assert!(win(9).rank(p) > draw.rank(p));
+        assert!(draw.rank(p) > loss(9).rank(p));
+        assert!(loss(9).rank(p) > loss(1).rank(p));
+    }
+
+    #[test]
+    fn test_byte_round_trip() {
+        for outcome in [GameOutcome::Draw, GameOutcome::Win(Player::Player1), GameOutcome::Win(Player::Player2)] {
+            for distance in 0..=81 {
+                let solution = Solution { outcome, distance };
+                assert_eq!(Solution::from_byte(solution.to_byte()), Some(solution));
+            }
+        }
+        assert_eq!(Solution::from_byte(0), None);
+        assert_eq!(Solution::from_byte(u8::MAX), None);
+    }
+}
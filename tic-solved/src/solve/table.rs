@@ -0,0 +1,131 @@
+use crate::solve::Solution;
+
+// Solutions keyed by position rank (see `ranking`), kept in flat arrays: sorted runs of ranks with
+// one byte per solution beside them. Every insert starts a run of its own and runs of about the
+// same length are merged, so a lookup only has to binary search a logarithmic number of runs.
+#[derive(Default)]
+pub struct SolutionTable {
+    runs: Vec<Run>,
+}
+
+#[derive(Default)]
+struct Run {
+    ranks: Vec<u128>,
+    solutions: Vec<u8>,
+}
+
+impl Run {
+    fn get(&self, rank: u128) -> Option<u8> {
+        self.ranks.binary_search(&rank).ok().map(|i| self.solutions[i])
+    }
+
+    fn len(&self) -> usize {
+        self.ranks.len()
+    }
+
+    fn merge(self, other: Run) -> Run {
+        let mut merged = Run { ranks: Vec::with_capacity(self.len() + other.len()), solutions: Vec::with_capacity(self.len() + other.len()) };
+        let mut left = self.ranks.into_iter().zip(self.solutions).peekable();
+        let mut right = other.ranks.into_iter().zip(other.solutions).peekable();
+        loop {
+            let next = match (left.peek(), right.peek()) {
+                (Some(l), Some(r)) if l.0 < r.0 => left.next(),
+                (Some(_), Some(_)) => right.next(),
+                (Some(_), None) => left.next(),
+                (None, _) => right.next(),
+            };
+            let Some((rank, solution)) = next else {
+                break;
+            };
+            merged.ranks.push(rank);
+            merged.solutions.push(solution);
+        }
+        merged
+    }
+}
+
+impl SolutionTable {
+    pub fn new() -> Self {
+        SolutionTable::default()
+    }
+
+    // None unless `ranks` is strictly increasing and every byte is a solution.
+    pub(crate) fn from_sorted(ranks: Vec<u128>, solutions: Vec<u8>) -> Option<Self> {
+        if ranks.len() != solutions.len()
+            || ranks.windows(2).any(|w| w[0] >= w[1])
+            || solutions.iter().any(|&s| Solution::from_byte(s).is_none()) {
+            return None;
+        }
+        let runs = if ranks.is_empty() { Vec::new() } else { vec![Run { ranks, solutions }] };
+        Some(SolutionTable { runs })
+    }
+
+    pub fn len(&self) -> usize {
+        self.runs.iter().map(Run::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.runs.is_empty()
+    }
+
+    pub fn get(&self, rank: u128) -> Option<Solution> {
+        self.runs.iter().find_map(|run| run.get(rank)).and_then(Solution::from_byte)
+    }
+
+    // A rank keeps the first solution stored for it; a position's solution never changes.
+    pub fn insert(&mut self, rank: u128, solution: Solution) {
+        if self.runs.iter().any(|run| run.get(rank).is_some()) {
+            return;
+        }
+        self.runs.push(Run { ranks: vec![rank], solutions: vec![solution.to_byte()] });
+        while let [.., older, newer] = &self.runs[..] {
+            if older.len() > newer.len() {
+                break;
+            }
+            let newer = self.runs.pop().unwrap();
+            let older = self.runs.pop().unwrap();
+            self.runs.push(older.merge(newer));
+        }
+    }
+
+    // Merges everything into one run and returns its ranks, in increasing order, and solutions.
+    pub(crate) fn compact(&mut self) -> (&[u128], &[u8]) {
+        while self.runs.len() > 1 {
+            let newer = self.runs.pop().unwrap();
+            let older = self.runs.pop().unwrap();
+            self.runs.push(older.merge(newer));
+        }
+        match self.runs.first() {
+            Some(run) => (&run.ranks, &run.solutions),
+            None => (&[], &[]),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tic_array::tic::{GameOutcome, Player};
+
+    #[test]
+    fn test_insert_and_get() {
+        let mut table = SolutionTable::new();
+        let solution = |distance| Solution { outcome: GameOutcome::Win(Player::Player1), distance };
+        for rank in (0..1000u128).rev() {
+            table.insert(rank * 7919 % 1000, solution((rank % 80) as u8));
+        }
+        assert_eq!(table.len(), 1000);
+        assert!(table.runs.len() <= 10);
+        table.insert(5, Solution { outcome: GameOutcome::Draw, distance: 0 });
+        assert_eq!(table.len(), 1000);
+        for rank in 0..1000u128 {
+            assert!(table.get(rank).is_some());
+        }
+        assert_eq!(table.get(1000), None);
+
+        let expected: Vec<_> = (0..1000).map(|rank| table.get(rank)).collect();
+        let (ranks, _) = table.compact();
+        assert!(ranks.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!((0..1000).map(|rank| table.get(rank)).collect::<Vec<_>>(), expected);
+    }
+}
@@ -0,0 +1,132 @@
+use std::{
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+use crate::{
+    tic_array::{bitmap_tic::BitBoard, endgame_index::EndgameIndex, tic::GameOutcome},
+    minmax::GameState,
+    solve::Solution,
+};
+
+// Largest tablebase we are willing to build, one byte per entry. Two playable cells take about
+// 24MB, three would take almost 5GB.
+pub const MAX_TABLEBASE_ENTRIES: u64 = 1 << 28;
+
+// One byte per indexed position (see `Solution::to_byte`), 0 for numbers no position has.
+const UNKNOWN: u8 = 0;
+
+// File layout (little endian):
+//   magic "TICTB002", max empty cells u8, entry count u64, then one byte per entry.
+const MAGIC: &[u8; 8] = b"TICTB002";
+
+// The solution of every unfinished position with at most `max_empty` playable cells, whatever game
+// it comes from, stored in a flat array indexed by `EndgameIndex`.
+pub struct Tablebase {
+    index: EndgameIndex,
+    entries: Vec<u8>,
+}
+
+fn solution_of(outcome: GameOutcome) -> Solution {
+    Solution { outcome, distance: 0 }
+}
+
+impl Tablebase {
+    // Solves positions in index order: every move leaves fewer playable cells, so the positions
+    // after it are either over or already solved.
+    pub fn generate(max_empty: usize) -> Result<Tablebase, String> {
+        let index = EndgameIndex::new(max_empty, MAX_TABLEBASE_ENTRIES)?;
+        let mut entries = vec![UNKNOWN; index.size() as usize];
+        for entry in 0..index.size() {
+            let Some(position) = index.position(entry) else {
+                continue;
+            };
+            let player = position.who_turn();
+            let mut best: Option<Solution> = None;
+            for next in position.next_states() {
+                let solution = match next.outcome() {
+                    GameOutcome::Ongoing => index.index_of(&next)
+                        .and_then(|next_entry| Solution::from_byte(entries[next_entry as usize]))
+                        .ok_or_else(|| format!("Position after entry {} is not in the tablebase", entry))?,
+                    outcome => solution_of(outcome),
+                };
+                let candidate = solution.one_ply_earlier();
+                if best.is_none_or(|b| candidate.rank(player) > b.rank(player)) {
+                    best = Some(candidate);
+                }
+            }
+            entries[entry as usize] = best.unwrap_or(solution_of(GameOutcome::Draw)).to_byte();
+        }
+        Ok(Tablebase { index, entries })
+    }
+
+    pub fn max_empty(&self) -> usize {
+        self.index.max_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.iter().filter(|&&e| e != UNKNOWN).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // None for finished games and positions with more than `max_empty` playable cells.
+    pub fn probe(&self, board: &BitBoard) -> Option<Solution> {
+        let entry = self.index.index_of(board)?;
+        Solution::from_byte(self.entries[entry as usize])
+    }
+
+    pub fn best_move(&self, board: &BitBoard) -> Option<(BitBoard, Solution)> {
+        if board.outcome() != GameOutcome::Ongoing {
+            return None;
+        }
+        let player = board.who_turn();
+        board.next_states()
+            .into_iter()
+            .map(|next| {
+                let solution = match next.outcome() {
+                    GameOutcome::Ongoing => self.probe(&next)?,
+                    outcome => solution_of(outcome),
+                };
+                Some((next, solution.one_ply_earlier()))
+            })
+            .collect::<Option<Vec<_>>>()?
+            .into_iter()
+            .max_by_key(|(_, solution)| solution.rank(player))
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[self.max_empty() as u8])?;
+        writer.write_all(&(self.entries.len() as u64).to_le_bytes())?;
+        writer.write_all(&self.entries)?;
+        writer.flush()
+    }
+
+    pub fn load(path: &Path) -> io::Result<Tablebase> {
+        let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+        let mut reader = BufReader::new(File::open(path)?);
+        let mut header = [0; 8 + 1 + 8];
+        reader.read_exact(&mut header)?;
+        if &header[0..8] != MAGIC {
+            return Err(invalid("Not a tablebase file"));
+        }
+        let max_empty = header[8] as usize;
+        let count = u64::from_le_bytes(header[9..17].try_into().unwrap());
+
+        let index = EndgameIndex::new(max_empty, MAX_TABLEBASE_ENTRIES).map_err(|e| invalid(&e))?;
+        if index.size() != count {
+            return Err(invalid("Tablebase size does not match its number of empty cells"));
+        }
+        let mut entries = vec![UNKNOWN; count as usize];
+        reader.read_exact(&mut entries)?;
+        if entries.iter().any(|&e| e != UNKNOWN && Solution::from_byte(e).is_none()) {
+            return Err(invalid("Corrupt tablebase entry"));
+        }
+        Ok(Tablebase { index, entries })
+    }
+}
@@ -1,39 +1,87 @@
+use std::{
+    sync::{Arc, Mutex, atomic::AtomicBool},
+    time::Duration,
+};
+
 use crate::{
-    tic_array::{tic_simulator::Strategy, tic::*},
-    minmax::{*, alphabeta::*}
+    tic_array::{tic_simulator::{Strategy, MoveInfo}, tic::*},
+    minmax::{
+        *,
+        alphabeta::*,
+        iterative::{Deadline, iterative_deepening_until},
+        transposition::{TranspositionTable, DEFAULT_CAPACITY},
+    }
 };
 
+// The transposition table is kept from move to move, so moves are searched one at a time.
 pub struct AlphaBetaStrategy {
     steps_to_search: usize,
+    time_budget: Option<Duration>,
+    table: Mutex<TranspositionTable<i32>>,
 }
 
 impl AlphaBetaStrategy {
     pub fn new(steps_to_search: usize) -> AlphaBetaStrategy {
-        AlphaBetaStrategy { steps_to_search }
+        AlphaBetaStrategy { steps_to_search, time_budget: None, table: Mutex::new(TranspositionTable::new(DEFAULT_CAPACITY)) }
+    }
+
+    // Deepens one ply at a time up to `steps_to_search` until `budget` has been spent on the move.
+    pub fn with_time_budget(mut self, budget: Duration) -> AlphaBetaStrategy {
+        self.time_budget = Some(budget);
+        self
     }
 }
 
 impl Strategy for AlphaBetaStrategy {
     fn get_move(&self, game_state: &Board) -> Option<Board> {
-        match game_state.who_turn() {
-            Player::Player1 => get_move_player1(self.steps_to_search, game_state),
-            Player::Player2 => get_move_player2(self.steps_to_search, game_state),
+        self.get_move_with_info(game_state).map(|(board, _)| board)
+    }
+
+    fn get_move_with_info(&self, game_state: &Board) -> Option<(Board, MoveInfo)> {
+        self.get_move_until(game_state, &Arc::new(AtomicBool::new(false)))
+    }
+
+    // A search stopped before its depth or budget is done has no move, except that with a time
+    // budget the first iteration always completes.
+    fn get_move_until(&self, game_state: &Board, stop: &Arc<AtomicBool>) -> Option<(Board, MoveInfo)> {
+        let mut table = self.table.lock().unwrap();
+        table.new_search();
+        if let Some(budget) = self.time_budget {
+            let maximize = game_state.who_turn() == Player::Player2;
+            let deadline = Deadline::after(budget).with_stop(Arc::clone(stop));
+            let search = Search::<Board, AlmostWinHeuristic, _, _, _>::new().with_table(&mut *table);
+            return iterative_deepening_until(game_state, maximize, self.steps_to_search, &deadline, search, |_| {})
+                .map(|result| (result.best, evaluated(result.score, result.depth)));
         }
+        let deadline = Deadline::never().with_stop(Arc::clone(stop));
+        let (s, score) = match game_state.who_turn() {
+            Player::Player1 => get_move_player1(self.steps_to_search, game_state, &mut table, &deadline),
+            Player::Player2 => get_move_player2(self.steps_to_search, game_state, &mut table, &deadline),
+        }?;
+        Some((s, evaluated(score, self.steps_to_search)))
     }
 }
 
-fn get_move_player1(depth: usize, game_state: &Board) -> Option<Board> {
+pub(crate) fn evaluated(score: i32, depth: usize) -> MoveInfo {
+    MoveInfo { evaluation: Some(score), depth: Some(depth), ..MoveInfo::default() }
+}
+
+fn get_move_player1(depth: usize, game_state: &Board, table: &mut TranspositionTable<i32>, deadline: &Deadline) -> Option<(Board, i32)> {
     if game_state.next_states().is_empty() {
         return None
     }
-    let (s, _) = min_score::<Board, AlmostWinHeuristic>(game_state, depth, None, None);
-    Some(s)
+    Search::<Board, AlmostWinHeuristic, _, _, _>::new()
+        .with_table(table)
+        .with_deadline(deadline)
+        .min_score(game_state, depth, None, None)
 }
 
-fn get_move_player2(depth: usize, game_state: &Board) -> Option<Board> {
+fn get_move_player2(depth: usize, game_state: &Board, table: &mut TranspositionTable<i32>, deadline: &Deadline) -> Option<(Board, i32)> {
     if game_state.next_states().is_empty() {
         return None
     }
-    let (s, _) = max_score::<Board, AlmostWinHeuristic>(game_state, depth, None, None);
-    Some(s)
+    Search::<Board, AlmostWinHeuristic, _, _, _>::new()
+        .with_table(table)
+        .with_deadline(deadline)
+        .max_score(game_state, depth, None, None)
 }
\ No newline at end of file
@@ -1,40 +1,119 @@
+use std::{
+    sync::{Arc, Mutex, atomic::AtomicBool},
+    time::Duration,
+};
+
 use crate::{
-    tic_array::{tic_simulator::Strategy, tic::*, bitmap_tic::*},
-    minmax::{*, alphabeta::*}
+    tic_array::{tic_simulator::{Strategy, MoveInfo}, tic::*, bitmap_tic::*, alphabeta_strategy::evaluated},
+    minmax::{
+        *,
+        alphabeta::*,
+        iterative::{Deadline, iterative_deepening_until},
+        transposition::{TranspositionTable, DEFAULT_CAPACITY},
+    },
+    solve::{Solution, tablebase::Tablebase},
 };
 
+// Tablebase results are scored beyond anything WinHeuristic can return, faster wins first.
+const TABLEBASE_WIN: i32 = 1000;
+
+// Like AlphaBetaStrategy, the transposition table is kept from move to move.
 pub struct AlphaBetaBitBoardStrategy {
     steps_to_search: usize,
+    time_budget: Option<Duration>,
+    tablebases: Vec<Tablebase>,
+    table: Mutex<TranspositionTable<i32>>,
 }
 
 impl AlphaBetaBitBoardStrategy {
     pub fn new(steps_to_search: usize) -> AlphaBetaBitBoardStrategy {
-        AlphaBetaBitBoardStrategy { steps_to_search }
+        AlphaBetaBitBoardStrategy {
+            steps_to_search,
+            time_budget: None,
+            tablebases: Vec::new(),
+            table: Mutex::new(TranspositionTable::new(DEFAULT_CAPACITY)),
+        }
+    }
+
+    // Deepens one ply at a time up to `steps_to_search` until `budget` has been spent on the move.
+    pub fn with_time_budget(mut self, budget: Duration) -> AlphaBetaBitBoardStrategy {
+        self.time_budget = Some(budget);
+        self
+    }
+
+    pub fn with_tablebase(mut self, tablebase: Tablebase) -> AlphaBetaBitBoardStrategy {
+        self.tablebases.push(tablebase);
+        self
+    }
+
+    fn probe(&self, game_state: &BitBoard) -> Option<i32> {
+        self.tablebases.iter()
+            .find_map(|tablebase| tablebase.probe(game_state))
+            .map(solution_score)
+    }
+}
+
+fn solution_score(solution: Solution) -> i32 {
+    let distance: i32 = solution.distance.into();
+    match solution.outcome {
+        GameOutcome::Win(Player::Player1) => distance - TABLEBASE_WIN,
+        GameOutcome::Win(Player::Player2) => TABLEBASE_WIN - distance,
+        GameOutcome::Draw | GameOutcome::Ongoing => 0,
     }
 }
 
 impl Strategy for AlphaBetaBitBoardStrategy {
     fn get_move(&self, game_state: &Board) -> Option<Board> {
+        self.get_move_with_info(game_state).map(|(board, _)| board)
+    }
+
+    fn get_move_with_info(&self, game_state: &Board) -> Option<(Board, MoveInfo)> {
+        self.get_move_until(game_state, &Arc::new(AtomicBool::new(false)))
+    }
+
+    // Stops like AlphaBetaStrategy does.
+    fn get_move_until(&self, game_state: &Board, stop: &Arc<AtomicBool>) -> Option<(Board, MoveInfo)> {
         let bitboard: BitBoard = game_state.to_owned().into();
-        match game_state.who_turn() {
-            Player::Player1 => get_move_player1(self.steps_to_search, &bitboard),
-            Player::Player2 => get_move_player2(self.steps_to_search, &bitboard),
+        if let Some((next, solution)) = self.tablebases.iter().find_map(|tablebase| tablebase.best_move(&bitboard)) {
+            return Some((next.into(), evaluated(solution_score(solution), solution.distance.into())));
         }
+        let probe = |state: &BitBoard| self.probe(state);
+        let mut table = self.table.lock().unwrap();
+        table.new_search();
+        if let Some(budget) = self.time_budget {
+            let maximize = game_state.who_turn() == Player::Player2;
+            let deadline = Deadline::after(budget).with_stop(Arc::clone(stop));
+            let search = Search::<BitBoard, WinHeuristic, _, _, _>::new().with_table(&mut *table).with_probe(probe);
+            return iterative_deepening_until(&bitboard, maximize, self.steps_to_search, &deadline, search, |_| {})
+                .map(|result| (result.best.into(), evaluated(result.score, result.depth)));
+        }
+        let deadline = Deadline::never().with_stop(Arc::clone(stop));
+        let (s, score) = match game_state.who_turn() {
+            Player::Player1 => get_move_player1(self.steps_to_search, &bitboard, &mut table, &deadline, &probe),
+            Player::Player2 => get_move_player2(self.steps_to_search, &bitboard, &mut table, &deadline, &probe),
+        }?;
+        Some((s.into(), evaluated(score, self.steps_to_search)))
     }
 }
 
-fn get_move_player1(depth: usize, game_state: &BitBoard) -> Option<Board> {
+fn get_move_player1<P: Fn(&BitBoard) -> Option<i32>>(depth: usize, game_state: &BitBoard, table: &mut TranspositionTable<i32>, deadline: &Deadline, probe: &P) -> Option<(BitBoard, i32)> {
     if game_state.next_states().is_empty() {
         return None
     }
-    let (s, _) = min_score::<BitBoard, WinHeuristic>(game_state, depth, None, None);
-    Some(s.into())
+    Search::<BitBoard, WinHeuristic, _, _, _>::new()
+        .with_table(table)
+        .with_probe(probe)
+        .with_deadline(deadline)
+        .min_score(game_state, depth, None, None)
 }
 
-fn get_move_player2(depth: usize, game_state: &BitBoard) -> Option<Board> {
+fn get_move_player2<P: Fn(&BitBoard) -> Option<i32>>(depth: usize, game_state: &BitBoard, table: &mut TranspositionTable<i32>, deadline: &Deadline, probe: &P) -> Option<(BitBoard, i32)> {
     if game_state.next_states().is_empty() {
         return None
     }
-    let (s, _) = max_score::<BitBoard, WinHeuristic>(game_state, depth, None, None);
-    Some(s.into())
-}
\ No newline at end of file
+    Search::<BitBoard, WinHeuristic, _, _, _>::new()
+        .with_table(table)
+        .with_probe(probe)
+        .with_deadline(deadline)
+        .max_score(game_state, depth, None, None)
+}
@@ -0,0 +1,327 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    future::{self, Future},
+    pin::Pin,
+    sync::{Arc, Mutex, atomic::{AtomicBool, Ordering}},
+    task::{Context, Poll, Waker},
+    thread,
+    time::Instant,
+};
+
+use log::info;
+use crate::tic_array::{tic::*, tic_move::Move, tic_simulator::*};
+
+pub type MoveFuture<'a> = Pin<Box<dyn Future<Output = Option<(Board, MoveInfo)>> + Send + 'a>>;
+
+// Strategy for callers that can't afford to block, like a UI that keeps drawing while a player
+// thinks. Resolving to None means the player has no move, which forfeits like in run_game.
+pub trait AsyncStrategy {
+    fn get_move(&self, game_state: &Board) -> MoveFuture<'_>;
+}
+
+impl<S: AsyncStrategy + ?Sized> AsyncStrategy for &S {
+    fn get_move(&self, game_state: &Board) -> MoveFuture<'_> {
+        (**self).get_move(game_state)
+    }
+}
+
+impl<S: AsyncStrategy + ?Sized> AsyncStrategy for Box<S> {
+    fn get_move(&self, game_state: &Board) -> MoveFuture<'_> {
+        (**self).get_move(game_state)
+    }
+}
+
+struct Queue<T> {
+    items: VecDeque<T>,
+    closed: bool,
+    waker: Option<Waker>,
+}
+
+// Hands values from any thread to a single async receiver. The receiver sees the end once the
+// sender is dropped.
+struct Sender<T> {
+    queue: Arc<Mutex<Queue<T>>>,
+}
+
+struct Receiver<T> {
+    queue: Arc<Mutex<Queue<T>>>,
+}
+
+fn queue<T>() -> (Sender<T>, Receiver<T>) {
+    let queue = Arc::new(Mutex::new(Queue { items: VecDeque::new(), closed: false, waker: None }));
+    (Sender { queue: Arc::clone(&queue) }, Receiver { queue })
+}
+
+impl<T> Sender<T> {
+    fn send(&self, item: T) {
+        let mut queue = self.queue.lock().unwrap();
+        queue.items.push_back(item);
+        if let Some(waker) = queue.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut queue = self.queue.lock().unwrap();
+        queue.closed = true;
+        if let Some(waker) = queue.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+impl<T> Receiver<T> {
+    fn poll_recv(&self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let mut queue = self.queue.lock().unwrap();
+        match queue.items.pop_front() {
+            Some(item) => Poll::Ready(Some(item)),
+            None if queue.closed => Poll::Ready(None),
+            None => {
+                queue.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+
+    async fn recv(&self) -> Option<T> {
+        future::poll_fn(|cx| self.poll_recv(cx)).await
+    }
+}
+
+// Raises the flag when dropped.
+struct StopOnDrop(Arc<AtomicBool>);
+
+impl Drop for StopOnDrop {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+// Runs a blocking strategy on a thread of its own for every move, so the future stays pending
+// without holding up the caller. Dropping the future before it resolves tells the search to stop
+// through get_move_until; a strategy that can't stop keeps its thread until it finishes, and its
+// answer is thrown away.
+pub struct ThreadedStrategy<S> {
+    strategy: Arc<S>,
+}
+
+impl<S: Strategy + Send + Sync + 'static> ThreadedStrategy<S> {
+    pub fn new(strategy: S) -> ThreadedStrategy<S> {
+        ThreadedStrategy { strategy: Arc::new(strategy) }
+    }
+
+    // Like get_move, but the search doesn't borrow the strategy, so the future can be kept
+    // around, for instance by a UI that polls it every frame.
+    pub fn spawn_move(&self, game_state: &Board) -> MoveFuture<'static> {
+        let (sender, receiver) = queue();
+        let strategy = Arc::clone(&self.strategy);
+        let board = game_state.clone();
+        let stop = Arc::new(AtomicBool::new(false));
+        let abandoned = StopOnDrop(Arc::clone(&stop));
+        thread::spawn(move || sender.send(strategy.get_move_until(&board, &stop)));
+        // A search that panicked drops the sender without an answer, which counts as no move.
+        Box::pin(async move {
+            let _abandoned = abandoned;
+            receiver.recv().await.flatten()
+        })
+    }
+}
+
+impl<S: Strategy + Send + Sync + 'static> AsyncStrategy for ThreadedStrategy<S> {
+    fn get_move(&self, game_state: &Board) -> MoveFuture<'_> {
+        self.spawn_move(game_state)
+    }
+}
+
+// A player whose moves come from somewhere else, like a UI thread reading keys or a socket.
+// Moves are played in the position the game is in when they arrive; illegal ones are skipped.
+// Dropping the MoveSender gives up.
+pub struct ChannelPlayer {
+    moves: Receiver<Move>,
+}
+
+pub struct MoveSender {
+    moves: Sender<Move>,
+}
+
+impl MoveSender {
+    pub fn send(&self, m: Move) {
+        self.moves.send(m)
+    }
+}
+
+pub fn channel_player() -> (MoveSender, ChannelPlayer) {
+    let (moves, receiver) = queue();
+    (MoveSender { moves }, ChannelPlayer { moves: receiver })
+}
+
+impl AsyncStrategy for ChannelPlayer {
+    fn get_move(&self, game_state: &Board) -> MoveFuture<'_> {
+        let board = game_state.clone();
+        Box::pin(async move {
+            loop {
+                let m = self.moves.recv().await?;
+                match board.play(m) {
+                    Ok(next) => return Some((next, MoveInfo::default())),
+                    Err(e) => info!("Skipping {}", e),
+                }
+            }
+        })
+    }
+}
+
+// One waker per run call that is still waiting, so polling again replaces it instead of piling up.
+#[derive(Default)]
+struct Waiters {
+    next_id: usize,
+    wakers: HashMap<usize, Waker>,
+}
+
+struct CancelState {
+    cancelled: AtomicBool,
+    waiters: Mutex<Waiters>,
+}
+
+// Unregisters a run call once it is done or dropped.
+struct Waiting<'a> {
+    state: &'a CancelState,
+    id: usize,
+}
+
+impl Drop for Waiting<'_> {
+    fn drop(&mut self) {
+        self.state.waiters.lock().unwrap().wakers.remove(&self.id);
+    }
+}
+
+// Shared flag to abandon a game from outside it. Clones cancel together.
+#[derive(Clone)]
+pub struct CancelToken {
+    state: Arc<CancelState>,
+}
+
+impl Default for CancelToken {
+    fn default() -> Self {
+        CancelToken::new()
+    }
+}
+
+impl CancelToken {
+    pub fn new() -> CancelToken {
+        CancelToken { state: Arc::new(CancelState { cancelled: AtomicBool::new(false), waiters: Mutex::new(Waiters::default()) }) }
+    }
+
+    pub fn cancel(&self) {
+        self.state.cancelled.store(true, Ordering::SeqCst);
+        let wakers: Vec<Waker> = self.state.waiters.lock().unwrap().wakers.drain().map(|(_, waker)| waker).collect();
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.state.cancelled.load(Ordering::SeqCst)
+    }
+
+    // Polls `future` until it is done, or gives None as soon as the token is cancelled.
+    pub async fn run<F: Future>(&self, future: F) -> Option<F::Output> {
+        let mut future = Box::pin(future);
+        let waiting = {
+            let mut waiters = self.state.waiters.lock().unwrap();
+            waiters.next_id += 1;
+            Waiting { state: &self.state, id: waiters.next_id }
+        };
+        future::poll_fn(|cx| {
+            if self.is_cancelled() {
+                return Poll::Ready(None);
+            }
+            self.state.waiters.lock().unwrap().wakers.insert(waiting.id, cx.waker().clone());
+            // Cancelled between the check and registering the waker.
+            if self.is_cancelled() {
+                return Poll::Ready(None);
+            }
+            future.as_mut().poll(cx).map(Some)
+        }).await
+    }
+}
+
+// The moves of a game that was cancelled before it ended.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AbandonedGame {
+    pub start: Board,
+    pub moves: Vec<MoveRecord>,
+}
+
+// Async version of run_game. Cancelling the token abandons the game, even while a player is
+// still thinking.
+pub async fn run_game_async<T: AsyncStrategy, U: AsyncStrategy>(board: Board, strategy1: T, strategy2: U, cancel: &CancelToken) -> Result<GameRecord, AbandonedGame> {
+    let mut record = GameRecord { start: board.clone(), moves: Vec::new(), result: GameResult::Draw };
+    let mut board = board;
+    loop {
+        match board.outcome() {
+            GameOutcome::Win(player) => {
+                record.result = GameResult::Win(player);
+                break;
+            }
+            GameOutcome::Draw => {
+                record.result = GameResult::Draw;
+                break;
+            }
+            GameOutcome::Ongoing => {}
+        }
+        let player = board.who_turn();
+        let strategy: &dyn AsyncStrategy = match player {
+            Player::Player1 => &strategy1,
+            Player::Player2 => &strategy2,
+        };
+        let start = Instant::now();
+        let Some(next) = cancel.run(strategy.get_move(&board)).await else {
+            info!("Game abandoned after {} moves", record.moves.len());
+            return Err(AbandonedGame { start: record.start, moves: record.moves });
+        };
+        let elapsed = start.elapsed();
+        match next.filter(|(next, _)| board.move_to(next).is_some()) {
+            Some((next, info)) => {
+                record.moves.push(MoveRecord { player, board: next.clone(), elapsed, info });
+                board = next;
+            }
+            None => {
+                info!("{:?} forfeits", player);
+                record.result = GameResult::Forfeit(player);
+                break;
+            }
+        }
+    }
+    info!("Game ended: {:?}\n{}", record.result, board);
+    Ok(record)
+}
+
+// Runs a future to completion on the calling thread, for callers without an executor of their own.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    pollster::block_on(future)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Regression: every poll used to store another waker that was only let go of on cancel.
+    #[test]
+    fn test_waker_list_stays_bounded() {
+        let cancel = CancelToken::new();
+        let mut polls = 0;
+        let pending_for_a_while = future::poll_fn(|cx| {
+            assert!(cancel.state.waiters.lock().unwrap().wakers.len() <= 1);
+            polls += 1;
+            if polls == 100 {
+                return Poll::Ready(());
+            }
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        });
+        assert_eq!(block_on(cancel.run(pending_for_a_while)), Some(()));
+        assert!(cancel.state.waiters.lock().unwrap().wakers.is_empty());
+    }
+}
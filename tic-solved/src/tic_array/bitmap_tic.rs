@@ -1,7 +1,7 @@
 
 use itertools::Itertools;
 
-use crate::{tic_array::tic::*, minmax::{GameState, Heuristic}};
+use crate::{tic_array::{tic::*, zobrist::*, symmetry::*, tic_move::*}, minmax::{GameState, Heuristic, MoveKey, ZobristHash}};
 use std::fmt;
 
 struct D(u128);
@@ -27,7 +27,7 @@ impl fmt::Debug for BoardCounts {
     }
 }
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub struct BitBoard {
     player1: u128,
     player2: u128,
@@ -63,33 +63,55 @@ fn col_wins_single(counts: u128) -> u128 {
     counts & onestep & twostep
 }
 
+// Only the bottom right cell can end a diagonal; the centre bits in the mask would otherwise pick
+// up cells from the sub board above.
 fn left_right_wins_single(counts: u128) -> u128 {
     let onestep = (counts << 10) & LEFT_RIGHT_MASK;
     let twostep = (counts << 20) & LEFT_RIGHT_MASK;
 
-    counts & onestep & twostep
+    counts & onestep & twostep & CORNER_MASK
 }
 
+// Marks the bottom left cell.
 fn right_left_wins_single(counts: u128) -> u128 {
     let onestep = (counts << 8) & RIGHT_LEFT_MASK;
     let twostep = (counts << 16) & RIGHT_LEFT_MASK;
-    counts & onestep & twostep
+    counts & onestep & twostep & (CORNER_MASK >> 2)
+}
+
+// The cells at (k, l) in every sub board.
+const fn cell_mask(k: usize, l: usize) -> u128 {
+    let mut mask = 0;
+    let mut n = 0;
+    while n < 9 {
+        mask |= 1 << ((n / 3) * 27 + k * 9 + (n % 3) * 3 + l);
+        n += 1;
+    }
+    mask
+}
+
+const ROW_STARTS: u128 = cell_mask(0, 0) | cell_mask(1, 0) | cell_mask(2, 0);
+const COL_STARTS: u128 = cell_mask(0, 0) | cell_mask(0, 1) | cell_mask(0, 2);
+const LEFT_RIGHT_START: u128 = cell_mask(0, 0);
+const RIGHT_LEFT_START: u128 = cell_mask(0, 2);
+
+// Marks the first cell of every line starting at `starts` and going `step` bits at a time that
+// holds two of `counts` and an `empty` cell.
+fn almost_lines(counts: u128, empty: u128, starts: u128, step: u32) -> u128 {
+    let (c0, c1, c2) = (counts, counts >> step, counts >> (2 * step));
+    let (e0, e1, e2) = (empty, empty >> step, empty >> (2 * step));
+    ((c0 & c1 & e2) | (c0 & e1 & c2) | (e0 & c1 & c2)) & starts
 }
 
-fn almost_wins_single(counts: u128) -> u128 {
-    let row = ((counts << 1) & COL_MASK) & counts;
-    let row_gap = ((((counts << 1) & COL_MASK) << 1) & COL_MASK) & counts;
-    let col = ((counts << 9) & ROW_MASK) & counts;
-    let col_gap = ((((counts << 9) & ROW_MASK) << 9) & ROW_MASK) & counts;
-    let left_right = ((counts << 10) & LEFT_RIGHT_MASK) & counts;
-    let left_right_gap = ((((counts << 10) & LEFT_RIGHT_MASK) << 10) & LEFT_RIGHT_MASK) & counts;
-    let right_left = ((counts << 8) & RIGHT_LEFT_MASK) & counts;
-    let right_left_gap = ((((counts << 8) & RIGHT_LEFT_MASK) << 8) & RIGHT_LEFT_MASK) & counts;
-    let combine = row | col | left_right | right_left
-                        | row_gap | col_gap | left_right_gap | right_left_gap;
+// Like Board's number_almost_wins: the corner of every sub board where `counts` has two in a line
+// and the third cell is still empty.
+fn almost_wins_single(counts: u128, empty: u128) -> u128 {
+    let combine = almost_lines(counts, empty, ROW_STARTS, 1)
+        | almost_lines(counts, empty, COL_STARTS, 9)
+        | almost_lines(counts, empty, LEFT_RIGHT_START, 10)
+        | almost_lines(counts, empty, RIGHT_LEFT_START, 8);
     let accumulate_into_col = combine | combine << 1 | combine << 2;
-    let accumulate = (accumulate_into_col | accumulate_into_col << 9 | accumulate_into_col << 18) & CORNER_MASK;
-    accumulate
+    (accumulate_into_col | accumulate_into_col << 9 | accumulate_into_col << 18) & CORNER_MASK
 }
 
 fn all_wins_single(counts: u128) -> u128 {
@@ -127,28 +149,8 @@ impl Iterator for LegalBoards {
         if ones == 81 {
             return None;
         }
-        let next_move: u128 = 1 << ones;
-        let next_move_board: u128 = {
-            let k: u128 = (3*((ones%(9*3))/9) + ones % 3).into();
-            let board_idx = 3*9*(k/3) + (k%3)*3 + 20;
-            1 << board_idx
-        };
-        self.not_allowed_moves = self.not_allowed_moves | next_move;
-        assert!((self.original_state.player1 | self.original_state.player2) & next_move == 0);
-        let next_board = match self.who_turn {
-            Player::Player1 => BitBoard {
-                player1: self.original_state.player1 | next_move,
-                player2: self.original_state.player2,
-                last_move: Some(next_move_board),
-            },
-            Player::Player2 => BitBoard {
-                player1: self.original_state.player1,
-                player2: self.original_state.player2 | next_move,
-                last_move: Some(next_move_board),
-            }
-        };
-        
-        Some(next_board)
+        self.not_allowed_moves |= 1 << ones;
+        Some(self.original_state.with_move(ones, self.who_turn))
     }
 }
 
@@ -157,6 +159,14 @@ impl BitBoard {
         BitBoard { player1: 0, player2: 0, last_move: None }
     }
 
+    pub(crate) fn from_parts(player1: u128, player2: u128, last_move: Option<u128>) -> Self {
+        BitBoard { player1, player2, last_move }
+    }
+
+    pub(crate) fn to_parts(&self) -> (u128, u128, Option<u128>) {
+        (self.player1, self.player2, self.last_move)
+    }
+
     fn row_wins(&self) -> BoardCounts {
         let player1 = row_wins_single(self.player1);
         let player2 = row_wins_single(self.player2);
@@ -278,6 +288,9 @@ impl BitBoard {
 
     fn not_allowed_moves(&self) -> u128 {
         let BoardCounts { player1: wins1, player2: wins2} = self.all_wins();
+        if self.who_win(BoardCounts { player1: wins1, player2: wins2 }).is_some() {
+            return WHOLE_BOARD_MASK;
+        }
         let filled_tiles = self.player1 | self.player2;
         let combined_wins = wins1 | wins2;
         let last_move_mask = if let Some(m) = self.last_move {
@@ -293,7 +306,45 @@ impl BitBoard {
         wins_mask | filled_tiles | last_move_mask
     }
 
-    fn who_turn(&self) -> Player {
+    // The sub board the next move is forced into, or None if the next move may go anywhere.
+    pub(crate) fn forced_board(&self) -> Option<u128> {
+        let BoardCounts { player1: wins1, player2: wins2 } = self.all_wins();
+        self.last_move.filter(|m| m & (wins1 | wins2 | self.full_boards()) == 0)
+    }
+
+    // Empty cells outside of won sub boards.
+    pub(crate) fn playable_cells(&self) -> u128 {
+        let BoardCounts { player1: wins1, player2: wins2 } = self.all_wins();
+        !(count_to_mask(wins1 | wins2) | self.player1 | self.player2) & WHOLE_BOARD_MASK
+    }
+
+    pub fn winner(&self) -> Option<Player> {
+        self.who_win(self.all_wins())
+    }
+
+    pub fn outcome(&self) -> GameOutcome {
+        let wins = self.all_wins();
+        if let Some(player) = self.who_win(wins.clone()) {
+            return GameOutcome::Win(player);
+        }
+        let undecided = !count_to_mask(wins.player1 | wins.player2 | self.full_boards()) & WHOLE_BOARD_MASK;
+        if undecided == 0 { GameOutcome::Draw } else { GameOutcome::Ongoing }
+    }
+
+    // Same coordinates as Board's single_board_winner.
+    pub fn single_board_winner(&self, x0: usize, x1: usize) -> Option<Player> {
+        let BoardCounts { player1, player2 } = self.all_wins();
+        let marker = 1 << cell_index(x0, x1, 2, 2);
+        if player1 & marker != 0 {
+            Some(Player::Player1)
+        } else if player2 & marker != 0 {
+            Some(Player::Player2)
+        } else {
+            None
+        }
+    }
+
+    pub fn who_turn(&self) -> Player {
         match (self.player1 | self.player2).count_ones() % 2 {
             0 => Player::Player1,
             1 => Player::Player2,
@@ -301,6 +352,56 @@ impl BitBoard {
         }   
     }
 
+    // Places `who`'s piece on bit `n` without checking that the move is legal.
+    fn with_move(&self, n: u32, who: Player) -> BitBoard {
+        let next_move: u128 = 1 << n;
+        assert!((self.player1 | self.player2) & next_move == 0);
+        let next_move_board: u128 = {
+            let k: u128 = (3*((n%(9*3))/9) + n % 3).into();
+            let board_idx = 3*9*(k/3) + (k%3)*3 + 20;
+            1 << board_idx
+        };
+        match who {
+            Player::Player1 => BitBoard {
+                player1: self.player1 | next_move,
+                player2: self.player2,
+                last_move: Some(next_move_board),
+            },
+            Player::Player2 => BitBoard {
+                player1: self.player1,
+                player2: self.player2 | next_move,
+                last_move: Some(next_move_board),
+            }
+        }
+    }
+
+    // In the same order as next_states.
+    pub fn legal_moves(&self) -> Vec<Move> {
+        let not_allowed = self.not_allowed_moves();
+        (0..81)
+            .filter(|&n| not_allowed & (1 << n) == 0)
+            .filter_map(Move::from_index)
+            .collect()
+    }
+
+    pub fn play(&self, m: Move) -> Result<BitBoard, IllegalMove> {
+        let n = m.index();
+        let bit = 1 << n;
+        if self.not_allowed_moves() & bit == 0 {
+            return Ok(self.with_move(n as u32, self.who_turn()));
+        }
+        let reason = if self.winner().is_some() {
+            IllegalMoveReason::GameOver
+        } else if (self.player1 | self.player2) & bit != 0 {
+            IllegalMoveReason::Occupied
+        } else if self.playable_cells() & bit == 0 {
+            IllegalMoveReason::BoardDecided
+        } else {
+            IllegalMoveReason::WrongBoard
+        };
+        Err(IllegalMove { attempted: m, reason })
+    }
+
     fn get_legal_boards(&self) -> impl Iterator<Item = Self> {
         let moves_mask = self.not_allowed_moves();
         let who_turn = self.who_turn();
@@ -381,25 +482,85 @@ impl GameState for BitBoard {
     }
 }
 
+fn hash_bits(mut bits: u128, keys: &[u64; 81]) -> u64 {
+    let mut hash = 0;
+    while bits != 0 {
+        hash ^= keys[bits.trailing_zeros() as usize];
+        bits &= bits - 1;
+    }
+    hash
+}
+
+// Bit indices line up with `cell_index`, so keys agree with Board's.
+impl MoveKey for BitBoard {
+    fn move_key(&self, child: &Self) -> usize {
+        let added = (child.player1 | child.player2) & !(self.player1 | self.player2);
+        added.trailing_zeros() as usize
+    }
+}
+
+impl ZobristHash for BitBoard {
+    fn zobrist_hash(&self) -> u64 {
+        let mut hash = hash_bits(self.player1, &PLAYER1_KEYS) ^ hash_bits(self.player2, &PLAYER2_KEYS);
+        if let Some(m) = self.last_move {
+            let n = m.trailing_zeros() as usize;
+            hash ^= LAST_MOVE_KEYS[3*(n/(3*9)) + (n % 9)/3];
+        }
+        hash
+    }
+}
+
+fn transform_bits(mut bits: u128, symmetry: Symmetry) -> u128 {
+    let mut transformed = 0;
+    while bits != 0 {
+        let n = bits.trailing_zeros() as usize;
+        let (i, j, k, l) = symmetry.apply_cell((n/27, (n % 9)/3, (n % 27)/9, n % 3));
+        transformed |= 1 << cell_index(i, j, k, l);
+        bits &= bits - 1;
+    }
+    transformed
+}
+
+impl Symmetric for BitBoard {
+    type Key = (u128, u128, Option<u128>);
+
+    fn transform(&self, symmetry: Symmetry) -> BitBoard {
+        let last_move = self.last_move.map(|m| {
+            let n = m.trailing_zeros() as usize;
+            let (x, y) = symmetry.apply((n/(3*9), (n % 9)/3));
+            1 << (3*y + x*3*9 + 20)
+        });
+        BitBoard {
+            player1: transform_bits(self.player1, symmetry),
+            player2: transform_bits(self.player2, symmetry),
+            last_move,
+        }
+    }
+
+    fn canonical_key(&self) -> Self::Key {
+        (self.player1, self.player2, self.last_move)
+    }
+}
+
 impl Heuristic<BitBoard> for AlmostWinHeuristic {
     type Score = i32;
 
     fn score(game_state: &BitBoard) -> Self::Score {
-        let wins = game_state.all_wins();
-        let is_won = game_state.who_win(wins.clone());
-        match is_won {
-            Some(Player::Player1) => return -1000,
-            Some(Player::Player2) => return 1000,
-            None => {}
+        match game_state.outcome() {
+            GameOutcome::Win(Player::Player1) => return -1000,
+            GameOutcome::Win(Player::Player2) => return 1000,
+            GameOutcome::Draw => return 0,
+            GameOutcome::Ongoing => {}
         }
-        let wins1 = wins.player1.count_ones();
-        let almost_wins1 = almost_wins_single(game_state.player1);
-        let almost_wins1_count = (almost_wins1 & !wins.player1).count_ones(); 
-        let wins2 = wins.player2.count_ones();
-        let almost_wins2 = almost_wins_single(game_state.player2);
-        let almost_wins2_count = (almost_wins2 & !wins.player2).count_ones(); 
-
-        (wins2 * 3 + almost_wins2_count - wins1 * 3 - almost_wins1_count).try_into().unwrap()
+        let wins = game_state.all_wins();
+        let decided = wins.player1 | wins.player2;
+        let empty = !(game_state.player1 | game_state.player2) & WHOLE_BOARD_MASK;
+        let wins1 = wins.player1.count_ones() as i32;
+        let almost_wins1 = (almost_wins_single(game_state.player1, empty) & !decided).count_ones() as i32;
+        let wins2 = wins.player2.count_ones() as i32;
+        let almost_wins2 = (almost_wins_single(game_state.player2, empty) & !decided).count_ones() as i32;
+
+        wins2 * 3 + almost_wins2 - wins1 * 3 - almost_wins1
     }
 }
 
@@ -420,6 +581,7 @@ impl Heuristic<BitBoard> for WinHeuristic {
 mod tests {
     use rstest::rstest;
     use std::collections::HashSet;
+    use itertools::iproduct;
     use std::fs;
     use std::path::PathBuf;
 
@@ -465,6 +627,59 @@ mod tests {
         assert_eq!(next_board_board1, next_board_board2);
     }
 
+    fn board_of(player1: &[(usize, usize, usize, usize)], player2: &[(usize, usize, usize, usize)]) -> Board {
+        let mut cells = [[[[0; 3]; 3]; 3]; 3];
+        for &(i, j, k, l) in player1 {
+            cells[i][j][k][l] = 1;
+        }
+        for &(i, j, k, l) in player2 {
+            cells[i][j][k][l] = 2;
+        }
+        Board::create_board(cells, None).unwrap()
+    }
+
+    fn assert_same_moves(board: &Board) {
+        let bitmap: BitBoard = board.clone().into();
+        let mut next_board_board1: Vec<Board> = bitmap.get_legal_boards().map(|x| x.into()).collect();
+        let mut next_board_board2 = board.next_states();
+        next_board_board1.sort();
+        next_board_board2.sort();
+        assert_eq!(next_board_board1, next_board_board2);
+    }
+
+    // Regression: moves were still generated once the whole game was won.
+    #[test]
+    fn test_no_moves_after_game_won() {
+        let top_row: Vec<_> = iproduct!(0..3, 0..3).map(|(j, l)| (0, j, 0, l)).collect();
+        let board = board_of(
+            &top_row,
+            &[(1, 0, 0, 0), (1, 0, 0, 1), (1, 0, 1, 2), (1, 0, 2, 0), (1, 0, 2, 1), (1, 1, 0, 0), (1, 1, 0, 1), (1, 1, 1, 2)],
+        );
+        let bitmap: BitBoard = board.clone().into();
+        assert_eq!(bitmap.who_win(bitmap.all_wins()), Some(Player::Player1));
+        assert_eq!(bitmap.get_legal_boards().count(), 0);
+        assert_same_moves(&board);
+    }
+
+    // Regression: a sub board's top left and centre cells together with a cell of the board above
+    // counted as a left-right diagonal.
+    #[test]
+    fn test_no_spurious_left_right_diagonal_win() {
+        let board = board_of(&[(1, 0, 0, 0), (1, 0, 1, 1), (0, 2, 1, 2)], &[(2, 2, 0, 0), (2, 2, 0, 1), (2, 1, 0, 0)]);
+        let bitmap: BitBoard = board.clone().into();
+        assert_eq!(all_wins_single(bitmap.player1), 0);
+        assert_same_moves(&board);
+    }
+
+    // Regression: the same for the right-left diagonal, with a cell two boards to the left.
+    #[test]
+    fn test_no_spurious_right_left_diagonal_win() {
+        let board = board_of(&[(1, 2, 0, 2), (1, 2, 1, 1), (1, 0, 0, 0)], &[(2, 2, 0, 0), (2, 2, 0, 1), (2, 1, 0, 0)]);
+        let bitmap: BitBoard = board.clone().into();
+        assert_eq!(all_wins_single(bitmap.player1), 0);
+        assert_same_moves(&board);
+    }
+
     #[rstest]
     fn test_almost_wins(#[files("positions/*.txt")] file_path: PathBuf) {
         let board_str = fs::read_to_string(file_path)
@@ -473,8 +688,17 @@ mod tests {
         let board_result: BoardResult = board_str.as_str().into();
         let board = board_result.into_inner().unwrap();
         let bitmap: BitBoard = board.clone().into();
+        let empty = !(bitmap.player1 | bitmap.player2) & WHOLE_BOARD_MASK;
         println!("{:?}", D(bitmap.player1));
-        println!("{:?}", D(almost_wins_single(bitmap.player1)));
-        // assert!(false);
+        println!("{:?}", D(almost_wins_single(bitmap.player1, empty)));
+        // Every sub board with an almost win has its marker in the corner.
+        assert_eq!(almost_wins_single(bitmap.player1, empty) & !CORNER_MASK, 0);
+        for x0 in 0..3 {
+            for x1 in 0..3 {
+                let marker = 1 << cell_index(x0, x1, 2, 2);
+                let almost = almost_wins_single(bitmap.player1, empty) & marker != 0;
+                assert_eq!(almost, board.number_almost_wins(x0, x1, Player::Player1) > 0);
+            }
+        }
     }
 }
\ No newline at end of file
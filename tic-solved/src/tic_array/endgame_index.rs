@@ -0,0 +1,368 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::tic_array::{
+    tic::{GameOutcome, Player},
+    bitmap_tic::BitBoard,
+    ranking::{self, LINES, LOCAL_STATES, NO_LINE},
+};
+
+// Numbers the unfinished positions with at most `max_empty` playable cells (empty cells outside won
+// sub boards), whatever game they come from, so their solutions can be stored in a flat array.
+//
+// Positions that have to play out the same way share a number. What is left of the game is decided
+// by
+// - which sub boards are open (no line and an empty cell), and how many empty cells each has;
+// - for each meta line through an open sub board, whether it is dead (it has a drawn sub board or
+//   sub boards won by both players), has only sub boards won by one player, or has none decided;
+// - the contents of each open sub board, up to which player wins it or fills it after which moves
+//   (see `Classes`);
+// - the sub board the next move is forced into and the side to move.
+// Numbers are grouped by the open sub boards and their empty cells, fewest empty cells in total
+// first, so the children of a position always come before it. Within a group the digits are the
+// side to move, the forced sub board (free first), the meta lines and the contents of the open
+// sub boards in order.
+//
+// `position` builds a board for a number with stand-ins for the decided sub boards. When no stand-in
+// gets the side to move right (every decided sub board drawn, so the number of pieces is fixed) no
+// position has that number and `position` returns None.
+
+const BOARDS: usize = 9;
+const FULL_BOARDS: u16 = 0o777;
+
+// Status of a meta line through an open sub board; WON_BY_1 and WON_BY_2 when all of its decided sub
+// boards are won by that player.
+const DEAD: u32 = 0;
+const UNDECIDED: u32 = 3;
+
+// Outcomes of a move in a sub board; other moves lead to a class, numbered from CLASSES.
+const FILLED: u32 = 0;
+const CLASSES: u32 = 3;
+
+const fn local(cells: [usize; 9]) -> usize {
+    let mut code = 0;
+    let mut cell = 9;
+    while cell > 0 {
+        cell -= 1;
+        code = 3*code + cells[cell];
+    }
+    code
+}
+
+// Stand-ins for a drawn sub board and for one won by each player, by kind, and the same with one
+// more piece for the side to move to come out right.
+const DECIDED: [usize; 3] = [
+    local([1, 2, 1, 1, 2, 2, 2, 1, 1]),
+    local([1, 1, 1, 0, 0, 0, 0, 0, 0]),
+    local([2, 2, 2, 0, 0, 0, 0, 0, 0]),
+];
+const DECIDED_FLIPPED: [usize; 3] = [
+    DECIDED[NO_LINE],
+    local([1, 1, 1, 0, 0, 0, 0, 0, 2]),
+    local([2, 2, 2, 0, 0, 0, 0, 0, 1]),
+];
+
+fn power_of_3(n: usize) -> usize {
+    3usize.pow(n as u32)
+}
+
+fn boards(set: u16) -> impl Iterator<Item = usize> {
+    (0..BOARDS).filter(move |&board| set & (1 << board) != 0)
+}
+
+// Open sub board contents, numbered by class. Two contents are in the same class when they have
+// the same empty cells and every move into them wins the sub board for the same player, fills it,
+// or leads to the same class, so the rest of the game can't tell them apart.
+struct Classes {
+    of: Vec<u32>,
+    codes: Vec<usize>,
+    // Classes with e empty cells are numbered from starts[e] to starts[e + 1].
+    starts: Vec<usize>,
+}
+
+impl Classes {
+    fn new(max_empty: usize) -> Classes {
+        let local = ranking::local_states();
+        let mut classes = Classes { of: vec![u32::MAX; LOCAL_STATES], codes: Vec::new(), starts: vec![0, 0] };
+        let mut interned: HashMap<(u16, Vec<u32>), u32> = HashMap::new();
+        for e in 1..=max_empty.min(BOARDS) {
+            for (code, state) in local.iter().enumerate() {
+                if !state.is_open() || state.empty.count_ones() as usize != e {
+                    continue;
+                }
+                let mut signature = Vec::with_capacity(2*e);
+                for cell in (0..BOARDS).filter(|&cell| state.empty & (1 << cell) != 0) {
+                    for piece in 1..=2 {
+                        let child_code = code + piece*power_of_3(cell);
+                        let child = &local[child_code];
+                        signature.push(if child.class != NO_LINE {
+                            child.class as u32
+                        } else if child.empty == 0 {
+                            FILLED
+                        } else {
+                            CLASSES + classes.of[child_code]
+                        });
+                    }
+                }
+                let next = classes.codes.len() as u32;
+                let class = *interned.entry((state.empty, signature)).or_insert(next);
+                if class == next {
+                    classes.codes.push(code);
+                }
+                classes.of[code] = class;
+            }
+            classes.starts.push(classes.codes.len());
+        }
+        classes
+    }
+
+    fn count(&self, empty: usize) -> usize {
+        self.starts[empty + 1] - self.starts[empty]
+    }
+}
+
+// The statuses of the meta lines through an open sub board, as base 4 digits by line, or None if a
+// line of decided sub boards is already won.
+fn meta_code(kinds: &[usize; 9], open: u16) -> Option<u32> {
+    let mut code = 0;
+    for (n, &line) in LINES.iter().enumerate() {
+        let mut decided = boards(line & !open).map(|board| kinds[board]);
+        let status = match decided.next() {
+            None => UNDECIDED,
+            Some(NO_LINE) => DEAD,
+            Some(kind) if decided.all(|other| other == kind) => kind as u32,
+            Some(_) => DEAD,
+        };
+        if line & open != 0 {
+            code |= status << (2*n);
+        } else if status != DEAD {
+            return None;
+        }
+    }
+    Some(code)
+}
+
+// The meta line statuses that can go with a set of open sub boards, sorted, with the kinds of
+// decided sub boards that give each. Those with a won sub board are preferred, as its stand-in can
+// take an extra piece.
+struct MetaLines {
+    codes: Vec<u32>,
+    kinds: Vec<[usize; 9]>,
+}
+
+impl MetaLines {
+    fn new(open: u16) -> MetaLines {
+        let decided: Vec<usize> = boards(FULL_BOARDS & !open).collect();
+        let mut found: BTreeMap<u32, [usize; 9]> = BTreeMap::new();
+        for assignment in 0..power_of_3(decided.len()) {
+            let mut kinds = [NO_LINE; 9];
+            let mut rest = assignment;
+            for &board in &decided {
+                kinds[board] = rest % 3;
+                rest /= 3;
+            }
+            let Some(code) = meta_code(&kinds, open) else {
+                continue;
+            };
+            let has_win = kinds.iter().any(|&kind| kind != NO_LINE);
+            let best = found.entry(code).or_insert(kinds);
+            if has_win && best.iter().all(|&kind| kind == NO_LINE) {
+                *best = kinds;
+            }
+        }
+        let (codes, kinds) = found.into_iter().unzip();
+        MetaLines { codes, kinds }
+    }
+}
+
+// Open sub boards with a given number of empty cells each, and how many numbers they take.
+struct Pattern {
+    open: u16,
+    empties: [usize; 9],
+    offset: u64,
+    forced: u64,
+    metas: u64,
+}
+
+pub struct EndgameIndex {
+    max_empty: usize,
+    classes: Classes,
+    meta_lines: Vec<MetaLines>,
+    patterns: Vec<Pattern>,
+    pattern_ids: HashMap<(u16, [usize; 9]), usize>,
+    size: u64,
+}
+
+// Every way to spread `total` empty cells over the open sub boards `open`, between one and nine each.
+fn spreads(open: u16, total: usize) -> Vec<[usize; 9]> {
+    let mut result = Vec::new();
+    let open: Vec<usize> = boards(open).collect();
+    let mut empties = [0; 9];
+    fn fill(open: &[usize], left: usize, empties: &mut [usize; 9], result: &mut Vec<[usize; 9]>) {
+        match open {
+            [] => {},
+            [board] => if left <= BOARDS {
+                empties[*board] = left;
+                result.push(*empties);
+            },
+            [board, rest @ ..] => {
+                for e in 1..=(left - rest.len()).min(BOARDS) {
+                    empties[*board] = e;
+                    fill(rest, left - e, empties, result);
+                }
+            },
+        }
+    }
+    if total >= open.len() {
+        fill(&open, total, &mut empties, &mut result);
+    }
+    result
+}
+
+impl EndgameIndex {
+    // Fails if the positions would take more than `max_size` numbers.
+    pub fn new(max_empty: usize, max_size: u64) -> Result<EndgameIndex, String> {
+        let classes = Classes::new(max_empty);
+        let meta_lines: Vec<MetaLines> = (0..=FULL_BOARDS).map(MetaLines::new).collect();
+        let too_many = || format!("More than {} positions with up to {} playable cells", max_size, max_empty);
+        let mut patterns = Vec::new();
+        let mut pattern_ids = HashMap::new();
+        let mut size: u64 = 0;
+        for total in 1..=max_empty {
+            for open in 1..=FULL_BOARDS {
+                for empties in spreads(open, total) {
+                    let forced = open.count_ones() as u64 + 1;
+                    let metas = meta_lines[open as usize].codes.len() as u64;
+                    let end = boards(open)
+                        .try_fold(2*forced*metas, |count, board| count.checked_mul(classes.count(empties[board]) as u64))
+                        .and_then(|count| size.checked_add(count))
+                        .filter(|&end| end <= max_size)
+                        .ok_or_else(too_many)?;
+                    pattern_ids.insert((open, empties), patterns.len());
+                    patterns.push(Pattern { open, empties, offset: size, forced, metas });
+                    size = end;
+                }
+            }
+        }
+        Ok(EndgameIndex { max_empty, classes, meta_lines, patterns, pattern_ids, size })
+    }
+
+    pub fn max_empty(&self) -> usize {
+        self.max_empty
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    // None if the game is over or has more than `max_empty` playable cells.
+    pub fn index_of(&self, board: &BitBoard) -> Option<u64> {
+        if board.playable_cells().count_ones() as usize > self.max_empty || board.outcome() != GameOutcome::Ongoing {
+            return None;
+        }
+        let (player1, player2, _) = board.to_parts();
+        let local = ranking::local_states();
+        let mut open = 0;
+        let mut empties = [0; 9];
+        let mut kinds = [NO_LINE; 9];
+        let mut codes = [0; 9];
+        for board in 0..BOARDS {
+            let code = ranking::local_code(player1, player2, board);
+            let state = &local[code];
+            if !state.legal {
+                return None;
+            }
+            if state.is_open() {
+                open |= 1 << board;
+                empties[board] = state.empty.count_ones() as usize;
+                codes[board] = code;
+            } else {
+                kinds[board] = state.class;
+            }
+        }
+        let pattern = &self.patterns[*self.pattern_ids.get(&(open, empties))?];
+        let meta = self.meta_lines[open as usize].codes.binary_search(&meta_code(&kinds, open)?).ok()? as u64;
+        let forced = board.forced_board().map_or(0, |bit| {
+            let t = ranking::forced_board_slot(bit) - 1;
+            (open & ((1 << t) - 1)).count_ones() as u64 + 1
+        });
+        let side = match board.who_turn() {
+            Player::Player1 => 0,
+            Player::Player2 => 1,
+        };
+        let digits = boards(open).fold((side*pattern.forced + forced)*pattern.metas + meta, |digits, board| {
+            let e = empties[board];
+            let class = self.classes.of[codes[board]] as usize - self.classes.starts[e];
+            digits*self.classes.count(e) as u64 + class as u64
+        });
+        Some(pattern.offset + digits)
+    }
+
+    // None if `index` is out of range or no position has it.
+    pub fn position(&self, index: u64) -> Option<BitBoard> {
+        if index >= self.size {
+            return None;
+        }
+        let pattern = &self.patterns[self.patterns.partition_point(|pattern| pattern.offset <= index) - 1];
+        let open: Vec<usize> = boards(pattern.open).collect();
+        let mut rest = index - pattern.offset;
+        let mut codes = [0; 9];
+        for &board in open.iter().rev() {
+            let e = pattern.empties[board];
+            let count = self.classes.count(e) as u64;
+            codes[board] = self.classes.codes[self.classes.starts[e] + (rest % count) as usize];
+            rest /= count;
+        }
+        let kinds = &self.meta_lines[pattern.open as usize].kinds[(rest % pattern.metas) as usize];
+        rest /= pattern.metas;
+        let forced = (rest % pattern.forced) as usize;
+        let side = (rest / pattern.forced) as u32;
+
+        let decided: Vec<usize> = boards(FULL_BOARDS & !pattern.open).collect();
+        for &board in &decided {
+            codes[board] = DECIDED[kinds[board]];
+        }
+        let pieces: u32 = codes.iter().map(|&code| 9 - ranking::local_states()[code].empty.count_ones()).sum();
+        if pieces % 2 != side {
+            let &won = decided.iter().find(|&&board| kinds[board] != NO_LINE)?;
+            codes[won] = DECIDED_FLIPPED[kinds[won]];
+        }
+        let (mut player1, mut player2) = (0, 0);
+        for (board, &code) in codes.iter().enumerate() {
+            ranking::place_local(code, board, &mut player1, &mut player2);
+        }
+        let last_move = if forced == 0 { None } else { Some(ranking::forced_board_bit(open[forced - 1] + 1)) };
+        Some(BitBoard::from_parts(player1, player2, last_move))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tic_array::ranking::{WON_BY_1, WON_BY_2};
+
+    #[test]
+    fn test_stand_ins() {
+        let local = ranking::local_states();
+        for kind in [NO_LINE, WON_BY_1, WON_BY_2] {
+            for code in [DECIDED[kind], DECIDED_FLIPPED[kind]] {
+                assert!(local[code].legal && !local[code].is_open());
+                assert_eq!(local[code].class, kind);
+            }
+        }
+    }
+
+    #[test]
+    fn test_one_empty_cell_classes() {
+        // Either player's move into the last cell wins the sub board for them or fills it, and all
+        // four combinations happen for every cell.
+        let classes = Classes::new(1);
+        assert_eq!(classes.count(1), 9*4);
+    }
+
+    #[test]
+    fn test_spreads() {
+        assert_eq!(spreads(0b101, 3), vec![[1, 0, 2, 0, 0, 0, 0, 0, 0], [2, 0, 1, 0, 0, 0, 0, 0, 0]]);
+        assert!(spreads(0b111, 2).is_empty());
+        assert_eq!(spreads(0b1, 10), Vec::<[usize; 9]>::new());
+    }
+}
@@ -0,0 +1,301 @@
+use std::{
+    io::{self, BufRead, Write},
+    sync::{Arc, Mutex, atomic::{AtomicBool, Ordering}},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    tic_array::{tic::*, bitmap_tic::*, tic_move::Move},
+    minmax::{
+        MoveKey, alphabeta,
+        iterative::{Deadline, IterationResult, iterative_deepening_until},
+        transposition::{TranspositionTable, DEFAULT_CAPACITY},
+    },
+};
+
+// Line based protocol in the spirit of UCI, so GUIs and bots can drive the engine over
+// stdin/stdout. Commands:
+//
+//   uti                                 -> id name tic-solved, utiok
+//   isready                             -> readyok
+//   newgame                             back to the empty board
+//   position startpos [moves B2b2 ...]
+//   position board <board> [moves ...]  <board> as BoardResult parses it
+//   go [depth N] [movetime MS] [infinite]
+//   stop                                finish the search now
+//   quit
+//
+// `go` without a depth or movetime thinks for DEFAULT_MOVETIME. `go infinite` searches until
+// `stop`, or until the input ends.
+//
+// While searching the engine prints one line per completed depth,
+//
+//   info depth 4 score 1 nodes 5120 time 3 pv B2b2 B2a1 A1e2 B2c3
+//
+// with the score from the side to move's point of view, and ends with `bestmove B2b2`, or
+// `bestmove none` when the game is over. Problems are reported as `info string <message>`.
+
+// Deepest search `go` asks for when no depth is given, enough to reach the end of any game.
+pub const MAX_DEPTH: usize = 81;
+
+// How long `go` thinks without any limits.
+pub const DEFAULT_MOVETIME: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GoLimits {
+    pub depth: usize,
+    pub movetime: Option<Duration>,
+    pub infinite: bool,
+}
+
+impl Default for GoLimits {
+    fn default() -> Self {
+        GoLimits { depth: MAX_DEPTH, movetime: Some(DEFAULT_MOVETIME), infinite: false }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Uti,
+    IsReady,
+    NewGame,
+    Position(Board),
+    Go(GoLimits),
+    Stop,
+    Quit,
+}
+
+impl Command {
+    pub fn parse(line: &str) -> Result<Command, String> {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        match tokens.as_slice() {
+            ["uti"] => Ok(Command::Uti),
+            ["isready"] => Ok(Command::IsReady),
+            ["newgame"] => Ok(Command::NewGame),
+            ["position", rest @ ..] => parse_position(rest).map(Command::Position),
+            ["go", rest @ ..] => parse_go(rest).map(Command::Go),
+            ["stop"] => Ok(Command::Stop),
+            ["quit"] => Ok(Command::Quit),
+            _ => Err(format!("Unknown command: {}", line.trim())),
+        }
+    }
+}
+
+fn parse_position(tokens: &[&str]) -> Result<Board, String> {
+    let split = tokens.iter().position(|&t| t == "moves").unwrap_or(tokens.len());
+    let (start, moves) = tokens.split_at(split);
+    let mut board = match start {
+        ["startpos"] => Board::new(),
+        ["board", cells @ ..] => BoardResult::from(cells.concat().as_str()).into_inner()?,
+        _ => return Err("Expected startpos or board".to_string()),
+    };
+    for notation in moves.iter().skip(1) {
+        board = board.play(notation.parse()?).map_err(|e| e.to_string())?;
+    }
+    Ok(board)
+}
+
+fn parse_go(tokens: &[&str]) -> Result<GoLimits, String> {
+    if tokens.is_empty() {
+        return Ok(GoLimits::default());
+    }
+    let mut limits = GoLimits { depth: MAX_DEPTH, movetime: None, infinite: false };
+    let mut tokens = tokens.iter();
+    while let Some(&token) = tokens.next() {
+        let mut value = || -> Result<u64, String> {
+            tokens.next()
+                .and_then(|v| v.parse().ok())
+                .ok_or(format!("Expected a number after {}", token))
+        };
+        match token {
+            "depth" => limits.depth = value()?.max(1) as usize,
+            "movetime" => limits.movetime = Some(Duration::from_millis(value()?)),
+            "infinite" => limits.infinite = true,
+            _ => return Err(format!("Unknown go option: {}", token)),
+        }
+    }
+    Ok(limits)
+}
+
+// The move that leads from `before` to `after`.
+fn played(before: &BitBoard, after: &BitBoard) -> Move {
+    Move::from_index(before.move_key(after)).expect("Boards differ by one move")
+}
+
+pub fn format_info(root: &BitBoard, iteration: &IterationResult<BitBoard, i32>, elapsed: Duration) -> String {
+    let score = if root.who_turn() == Player::Player2 { iteration.score } else { -iteration.score };
+    let mut line = format!("info depth {} score {} nodes {} time {} pv", iteration.depth, score, iteration.nodes, elapsed.as_millis());
+    let mut previous = root;
+    for state in &iteration.principal_variation {
+        line.push_str(&format!(" {}", played(previous, state)));
+        previous = state;
+    }
+    line
+}
+
+struct Search {
+    stop: Arc<AtomicBool>,
+    handle: JoinHandle<()>,
+    infinite: bool,
+}
+
+// Reads commands and answers on `out`. Searches run on their own thread so `stop` and
+// `isready` are answered while the engine thinks.
+pub struct Engine<W: Write + Send + 'static> {
+    out: Arc<Mutex<W>>,
+    position: Board,
+    search: Option<Search>,
+}
+
+fn send<W: Write>(out: &Mutex<W>, line: &str) {
+    let mut out = out.lock().unwrap();
+    // Nothing sensible to do when the other side has gone away.
+    let _ = writeln!(out, "{}", line).and_then(|_| out.flush());
+}
+
+impl<W: Write + Send + 'static> Engine<W> {
+    pub fn new(out: W) -> Engine<W> {
+        Engine { out: Arc::new(Mutex::new(out)), position: Board::new(), search: None }
+    }
+
+    pub fn position(&self) -> &Board {
+        &self.position
+    }
+
+    // Handles one line of input. Returns false once the engine should quit.
+    pub fn handle(&mut self, line: &str) -> bool {
+        if line.trim().is_empty() {
+            return true;
+        }
+        let command = match Command::parse(line) {
+            Ok(command) => command,
+            Err(e) => {
+                send(&self.out, &format!("info string {}", e));
+                return true;
+            }
+        };
+        match command {
+            Command::Uti => {
+                send(&self.out, "id name tic-solved");
+                send(&self.out, "utiok");
+            }
+            Command::IsReady => send(&self.out, "readyok"),
+            Command::NewGame => {
+                self.stop();
+                self.position = Board::new();
+            }
+            Command::Position(board) => {
+                self.stop();
+                self.position = board;
+            }
+            Command::Go(limits) => {
+                self.stop();
+                self.go(limits);
+            }
+            Command::Stop => self.stop(),
+            Command::Quit => {
+                self.stop();
+                return false;
+            }
+        }
+        true
+    }
+
+    // Handles commands until `quit` or the end of the input, letting a running search finish
+    // unless nothing could stop it any more.
+    pub fn run<R: BufRead>(&mut self, input: R) -> io::Result<()> {
+        for line in input.lines() {
+            if !self.handle(&line?) {
+                return Ok(());
+            }
+        }
+        self.finish();
+        Ok(())
+    }
+
+    fn go(&mut self, limits: GoLimits) {
+        let root: BitBoard = self.position.clone().into();
+        let maximize = self.position.who_turn() == Player::Player2;
+        let stop = Arc::new(AtomicBool::new(false));
+        let out = Arc::clone(&self.out);
+        let deadline = match limits.movetime {
+            Some(movetime) if !limits.infinite => Deadline::after(movetime),
+            _ => Deadline::never(),
+        }.with_stop(Arc::clone(&stop));
+        let handle = thread::spawn(move || {
+            let start = Instant::now();
+            let mut table = TranspositionTable::new(DEFAULT_CAPACITY);
+            let search = alphabeta::Search::<BitBoard, WinHeuristic, _, _, _>::new().with_table(&mut table);
+            let result = iterative_deepening_until(
+                &root, maximize, limits.depth, &deadline, search,
+                |iteration| send(&out, &format_info(&root, iteration, start.elapsed()))
+            );
+            match result {
+                Some(result) => send(&out, &format!("bestmove {}", played(&root, &result.best))),
+                None => send(&out, "bestmove none"),
+            }
+        });
+        self.search = Some(Search { stop, handle, infinite: limits.infinite });
+    }
+
+    // Ends the running search early. It still reports its best move.
+    pub fn stop(&mut self) {
+        if let Some(search) = &self.search {
+            search.stop.store(true, Ordering::Relaxed);
+        }
+        self.wait();
+    }
+
+    // Blocks until the running search has printed its best move.
+    pub fn wait(&mut self) {
+        if let Some(search) = self.search.take() {
+            search.handle.join().expect("Search thread panicked");
+        }
+    }
+
+    // Stops an infinite search, waits for any other.
+    fn finish(&mut self) {
+        if self.search.as_ref().is_some_and(|search| search.infinite) {
+            self.stop();
+        }
+        self.wait();
+    }
+
+    // Finishes the running search and hands back the output.
+    pub fn into_output(mut self) -> W {
+        self.finish();
+        match Arc::try_unwrap(self.out) {
+            Ok(out) => out.into_inner().unwrap(),
+            Err(_) => unreachable!("The search thread has finished"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_go() {
+        assert_eq!(Command::parse("go"), Ok(Command::Go(GoLimits::default())));
+        assert_eq!(
+            Command::parse("go depth 3 movetime 250"),
+            Ok(Command::Go(GoLimits { depth: 3, movetime: Some(Duration::from_millis(250)), infinite: false }))
+        );
+        assert_eq!(Command::parse("go depth 3"), Ok(Command::Go(GoLimits { depth: 3, movetime: None, infinite: false })));
+        assert_eq!(Command::parse("go infinite"), Ok(Command::Go(GoLimits { depth: MAX_DEPTH, movetime: None, infinite: true })));
+        assert!(Command::parse("go depth").is_err());
+        assert!(Command::parse("go nodes 5").is_err());
+    }
+
+    #[test]
+    fn test_parse_position() {
+        let board = Board::new().play("B2b2".parse().unwrap()).unwrap();
+        assert_eq!(Command::parse("position startpos moves B2b2"), Ok(Command::Position(board.clone())));
+        let cells: String = board.to_string().split_whitespace().collect();
+        assert_eq!(Command::parse(&format!("position board {}", cells)), Ok(Command::Position(board)));
+        assert!(Command::parse("position startpos moves B2b2 B2b2").is_err());
+        assert!(Command::parse("position moves B2b2").is_err());
+    }
+}
@@ -0,0 +1,306 @@
+use std::{
+    fmt, fs, io,
+    iter::Peekable,
+    path::Path,
+    time::Duration,
+};
+
+use crate::{
+    tic_array::{tic::*, tic_move::Move, tic_simulator::*},
+    minmax::MoveKey,
+};
+
+// Text format for finished games:
+//
+//   # tic-solved game record 1
+//   Player1: depth 3
+//   Player2: mcts
+//   Start: <the start board as BoardResult parses it, on one line>
+//   Result: player1 | player2 | draw | player1 forfeits | player2 forfeits
+//
+//   1 B2b2 1520us
+//   2 B2a1 87us
+//
+// Any other "Key: value" header is kept as is. Moves are numbered from 1 and give the move in
+// `Move` notation followed by the time the strategy took.
+//
+// A record can also hold variations, moves that could have been played instead. They follow the
+// move they replace between lines holding only ( and ), and can be nested:
+//
+//   1 B2b2 1520us
+//   (
+//   1 A1a1 0us
+//   2 A1b2 0us
+//   )
+//   2 B2a1 87us
+//
+// The Result header is left out while the main line is unfinished. SavedGame only reads records
+// with a result and skips the variations.
+
+const FIRST_LINE: &str = "# tic-solved game record 1";
+
+pub(crate) fn format_result(result: GameResult) -> &'static str {
+    match result {
+        GameResult::Win(Player::Player1) => "player1",
+        GameResult::Win(Player::Player2) => "player2",
+        GameResult::Draw => "draw",
+        GameResult::Forfeit(Player::Player1) => "player1 forfeits",
+        GameResult::Forfeit(Player::Player2) => "player2 forfeits",
+    }
+}
+
+pub(crate) fn parse_result(s: &str) -> Result<GameResult, String> {
+    match s {
+        "player1" => Ok(GameResult::Win(Player::Player1)),
+        "player2" => Ok(GameResult::Win(Player::Player2)),
+        "draw" => Ok(GameResult::Draw),
+        "player1 forfeits" => Ok(GameResult::Forfeit(Player::Player1)),
+        "player2 forfeits" => Ok(GameResult::Forfeit(Player::Player2)),
+        _ => Err(format!("Invalid result: {}", s)),
+    }
+}
+
+fn parse_elapsed(s: &str) -> Result<Duration, String> {
+    s.strip_suffix("us")
+        .and_then(|micros| micros.parse().ok())
+        .map(Duration::from_micros)
+        .ok_or(format!("Invalid move time: {}", s))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SavedGame {
+    pub headers: Vec<(String, String)>,
+    pub record: GameRecord,
+}
+
+impl SavedGame {
+    pub fn new(record: GameRecord) -> SavedGame {
+        SavedGame { headers: Vec::new(), record }
+    }
+
+    pub fn with_header(mut self, key: impl Into<String>, value: impl Into<String>) -> SavedGame {
+        self.headers.push((key.into(), value.into()));
+        self
+    }
+
+    pub fn header(&self, key: &str) -> Option<&str> {
+        self.headers.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+    }
+
+    // The start position followed by the position after every move.
+    pub fn positions(&self) -> Vec<Board> {
+        std::iter::once(self.record.start.clone())
+            .chain(self.record.moves.iter().map(|m| m.board.clone()))
+            .collect()
+    }
+
+    pub fn moves(&self) -> Vec<Move> {
+        self.positions()
+            .windows(2)
+            .filter_map(|pair| Move::from_index(pair[0].move_key(&pair[1])))
+            .collect()
+    }
+
+    pub fn parse(s: &str) -> Result<SavedGame, String> {
+        let tree = SavedTree::parse(s)?;
+        let result = tree.result.ok_or("Missing result")?;
+        let mut board = tree.start.clone();
+        let mut moves = Vec::new();
+        for recorded in tree.main_line() {
+            let player = board.who_turn();
+            let next = board.play(recorded.chosen).map_err(|e| e.to_string())?;
+            moves.push(MoveRecord { player, board: next.clone(), elapsed: recorded.elapsed, info: MoveInfo::default() });
+            board = next;
+        }
+        Ok(SavedGame { headers: tree.headers, record: GameRecord { start: tree.start, moves, result } })
+    }
+
+    // The game as a tree without variations.
+    pub fn to_tree(&self) -> SavedTree {
+        let mut moves = Vec::new();
+        for (record, chosen) in self.record.moves.iter().zip(self.moves()).rev() {
+            moves = vec![RecordedMove { chosen, elapsed: record.elapsed, next: moves }];
+        }
+        SavedTree { headers: self.headers.clone(), start: self.record.start.clone(), result: Some(self.record.result), moves }
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.to_string())
+    }
+
+    pub fn load(path: &Path) -> io::Result<SavedGame> {
+        let text = fs::read_to_string(path)?;
+        SavedGame::parse(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+impl fmt::Display for SavedGame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_tree())
+    }
+}
+
+// A move and the moves that can follow it. The first of `next` continues the line, the others
+// are variations.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedMove {
+    pub chosen: Move,
+    pub elapsed: Duration,
+    pub next: Vec<RecordedMove>,
+}
+
+// A record with all its variations. `moves` are the alternatives for the first move.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SavedTree {
+    pub headers: Vec<(String, String)>,
+    pub start: Board,
+    pub result: Option<GameResult>, // None while the main line is unfinished.
+    pub moves: Vec<RecordedMove>,
+}
+
+impl SavedTree {
+    pub fn new(start: Board) -> SavedTree {
+        SavedTree { headers: Vec::new(), start, result: None, moves: Vec::new() }
+    }
+
+    pub fn with_header(mut self, key: impl Into<String>, value: impl Into<String>) -> SavedTree {
+        self.headers.push((key.into(), value.into()));
+        self
+    }
+
+    pub fn header(&self, key: &str) -> Option<&str> {
+        self.headers.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+    }
+
+    pub fn main_line(&self) -> Vec<&RecordedMove> {
+        let mut line = Vec::new();
+        let mut alternatives = &self.moves;
+        while let Some(main) = alternatives.first() {
+            line.push(main);
+            alternatives = &main.next;
+        }
+        line
+    }
+
+    pub fn parse(s: &str) -> Result<SavedTree, String> {
+        let mut lines = s.lines().map(str::trim);
+        if lines.next() != Some(FIRST_LINE) {
+            return Err("Not a game record".to_string());
+        }
+        let mut headers = Vec::new();
+        let mut start = None;
+        let mut result = None;
+        for line in lines.by_ref() {
+            if line.is_empty() {
+                break;
+            }
+            let (key, value) = line.split_once(':').ok_or(format!("Invalid header: {}", line))?;
+            let value = value.trim();
+            match key {
+                "Start" => start = Some(BoardResult::from(value).into_inner()?),
+                "Result" => result = Some(parse_result(value)?),
+                _ => headers.push((key.to_string(), value.to_string())),
+            }
+        }
+        let start = start.ok_or("Missing start position")?;
+
+        let mut lines = lines.filter(|line| !line.is_empty()).peekable();
+        let moves = parse_alternatives(&mut lines, &start, 1)?;
+        if let Some(line) = lines.next() {
+            return Err(format!("Unexpected line: {}", line));
+        }
+        Ok(SavedTree { headers, start, result, moves })
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.to_string())
+    }
+
+    pub fn load(path: &Path) -> io::Result<SavedTree> {
+        let text = fs::read_to_string(path)?;
+        SavedTree::parse(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+// Reads the move numbered `number`, the variations to it and everything after it.
+fn parse_alternatives<'a, I>(lines: &mut Peekable<I>, board: &Board, number: usize) -> Result<Vec<RecordedMove>, String>
+where I: Iterator<Item = &'a str> {
+    let Some(&line) = lines.peek() else {
+        return Ok(Vec::new());
+    };
+    if line == "(" || line == ")" {
+        return Ok(Vec::new());
+    }
+    lines.next();
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    let [n, notation, elapsed] = fields[..] else {
+        return Err(format!("Invalid move: {}", line));
+    };
+    if n.parse() != Ok(number) {
+        return Err(format!("Move out of order: {}", line));
+    }
+    let chosen: Move = notation.parse()?;
+    let next = board.play(chosen).map_err(|e| e.to_string())?;
+    let elapsed = parse_elapsed(elapsed)?;
+
+    let mut variations = Vec::new();
+    while lines.next_if_eq(&"(").is_some() {
+        let variation = parse_alternatives(lines, board, number)?;
+        if variation.is_empty() {
+            return Err("Empty variation".to_string());
+        }
+        if lines.next() != Some(")") {
+            return Err("Unclosed variation".to_string());
+        }
+        variations.extend(variation);
+    }
+    let main = RecordedMove { chosen, elapsed, next: parse_alternatives(lines, &next, number + 1)? };
+    Ok(std::iter::once(main).chain(variations).collect())
+}
+
+fn write_alternatives(f: &mut fmt::Formatter<'_>, alternatives: &[RecordedMove], number: usize) -> fmt::Result {
+    let Some((main, variations)) = alternatives.split_first() else {
+        return Ok(());
+    };
+    writeln!(f, "{} {} {}us", number, main.chosen, main.elapsed.as_micros())?;
+    for variation in variations {
+        writeln!(f, "(")?;
+        write_alternatives(f, std::slice::from_ref(variation), number)?;
+        writeln!(f, ")")?;
+    }
+    write_alternatives(f, &main.next, number + 1)
+}
+
+impl fmt::Display for SavedTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", FIRST_LINE)?;
+        for (key, value) in &self.headers {
+            writeln!(f, "{}: {}", key, value)?;
+        }
+        let start: String = self.start.to_string().split_whitespace().collect();
+        writeln!(f, "Start: {}", start)?;
+        if let Some(result) = self.result {
+            writeln!(f, "Result: {}", format_result(result))?;
+        }
+        writeln!(f)?;
+        write_alternatives(f, &self.moves, 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_result_round_trip() {
+        for result in [
+            GameResult::Win(Player::Player1),
+            GameResult::Win(Player::Player2),
+            GameResult::Draw,
+            GameResult::Forfeit(Player::Player1),
+            GameResult::Forfeit(Player::Player2),
+        ] {
+            assert_eq!(parse_result(format_result(result)), Ok(result));
+        }
+    }
+}
@@ -0,0 +1,224 @@
+use std::{
+    sync::{Arc, atomic::{AtomicBool, Ordering}},
+    time::{Duration, Instant},
+};
+
+use log::info;
+
+use crate::{
+    tic_array::{tic_simulator::{Strategy, MoveInfo}, tic::*, bitmap_tic::*, tic_move::Move, random::Rng},
+    minmax::{GameState, Heuristic, MoveKey, ZobristHash},
+};
+
+// sqrt(2), the textbook UCT exploration constant for rewards in [0, 1].
+const DEFAULT_EXPLORATION: f64 = std::f64::consts::SQRT_2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rollout {
+    Random,
+    // Plays a move that wins a sub board whenever there is one, otherwise a random move.
+    Heuristic,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Budget {
+    Iterations(usize),
+    Time(Duration),
+}
+
+pub struct MctsStrategy {
+    budget: Budget,
+    exploration: f64,
+    rollout: Rollout,
+    seed: u64,
+}
+
+impl MctsStrategy {
+    pub fn new(iterations: usize) -> MctsStrategy {
+        MctsStrategy {
+            budget: Budget::Iterations(iterations),
+            exploration: DEFAULT_EXPLORATION,
+            rollout: Rollout::Random,
+            seed: 0,
+        }
+    }
+
+    // Replaces the iteration count; at least one iteration always runs.
+    pub fn with_time_budget(mut self, budget: Duration) -> MctsStrategy {
+        self.budget = Budget::Time(budget);
+        self
+    }
+
+    pub fn with_exploration(mut self, exploration: f64) -> MctsStrategy {
+        self.exploration = exploration;
+        self
+    }
+
+    pub fn with_rollout(mut self, rollout: Rollout) -> MctsStrategy {
+        self.rollout = rollout;
+        self
+    }
+
+    // The random stream for a move depends on the seed and the position only, so with an
+    // iteration budget the same seed always picks the same move.
+    pub fn with_seed(mut self, seed: u64) -> MctsStrategy {
+        self.seed = seed;
+        self
+    }
+
+    pub fn best_move(&self, root: &BitBoard) -> Option<BitBoard> {
+        let tree = self.search(root, &AtomicBool::new(false))?;
+        tree.most_visited_child(0).map(|child| tree.nodes[child].state.clone())
+    }
+
+    // Raising `stop` ends the search early, after at least one iteration.
+    fn search(&self, root: &BitBoard, stop: &AtomicBool) -> Option<Tree> {
+        let mut tree = Tree::new(root.clone());
+        tree.nodes[0].untried = root.next_states();
+        if tree.nodes[0].untried.is_empty() {
+            return None;
+        }
+        let mut rng = Rng::new(self.seed ^ root.zobrist_hash());
+        let start = Instant::now();
+        let mut iterations = 0;
+        loop {
+            let done = match self.budget {
+                Budget::Iterations(n) => iterations >= n.max(1),
+                Budget::Time(budget) => iterations > 0 && start.elapsed() >= budget,
+            } || (iterations > 0 && stop.load(Ordering::Relaxed));
+            if done {
+                break;
+            }
+            let leaf = tree.select_and_expand(self.exploration, &mut rng);
+            let winner = rollout(&tree.nodes[leaf].state, self.rollout, &mut rng);
+            tree.backpropagate(leaf, winner);
+            iterations += 1;
+        }
+        info!("MCTS ran {} iterations, root visited {} times", iterations, tree.nodes[0].visits);
+        Some(tree)
+    }
+}
+
+impl Strategy for MctsStrategy {
+    fn get_move(&self, game_state: &Board) -> Option<Board> {
+        let bitboard: BitBoard = game_state.to_owned().into();
+        self.best_move(&bitboard).map(|next| next.into())
+    }
+
+    fn get_move_with_info(&self, game_state: &Board) -> Option<(Board, MoveInfo)> {
+        self.get_move_until(game_state, &Arc::new(AtomicBool::new(false)))
+    }
+
+    // The principal variation follows the most visited child down the tree.
+    fn get_move_until(&self, game_state: &Board, stop: &Arc<AtomicBool>) -> Option<(Board, MoveInfo)> {
+        let bitboard: BitBoard = game_state.to_owned().into();
+        let tree = self.search(&bitboard, stop)?;
+        let mut principal_variation = Vec::new();
+        let mut current = 0;
+        while let Some(child) = tree.most_visited_child(current) {
+            principal_variation.extend(Move::from_index(tree.nodes[current].state.move_key(&tree.nodes[child].state)));
+            current = child;
+        }
+        let best = tree.most_visited_child(0)?;
+        let info = MoveInfo { depth: Some(principal_variation.len()), principal_variation, ..MoveInfo::default() };
+        Some((tree.nodes[best].state.clone().into(), info))
+    }
+}
+
+struct Node {
+    state: BitBoard,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    untried: Vec<BitBoard>,
+    visits: u32,
+    // Reward for the player who moved into this node: 1 per win, 0.5 per draw.
+    reward: f64,
+}
+
+struct Tree {
+    nodes: Vec<Node>,
+}
+
+fn is_terminal(state: &BitBoard) -> bool {
+    state.outcome().is_over()
+}
+
+impl Tree {
+    fn new(root: BitBoard) -> Tree {
+        Tree { nodes: vec![Node { state: root, parent: None, children: Vec::new(), untried: Vec::new(), visits: 0, reward: 0.0 }] }
+    }
+
+    fn most_visited_child(&self, node: usize) -> Option<usize> {
+        self.nodes[node].children.iter().copied().max_by_key(|&child| self.nodes[child].visits)
+    }
+
+    fn uct(&self, child: usize, parent_visits: f64, exploration: f64) -> f64 {
+        let node = &self.nodes[child];
+        let visits = node.visits as f64;
+        node.reward / visits + exploration * (parent_visits.ln() / visits).sqrt()
+    }
+
+    // Walks down fully expanded nodes by UCT and expands one untried move below them.
+    fn select_and_expand(&mut self, exploration: f64, rng: &mut Rng) -> usize {
+        let mut current = 0;
+        loop {
+            let node = &self.nodes[current];
+            if !node.untried.is_empty() {
+                let index = rng.below(node.untried.len());
+                let state = self.nodes[current].untried.swap_remove(index);
+                let untried = if is_terminal(&state) { Vec::new() } else { state.next_states() };
+                self.nodes.push(Node { state, parent: Some(current), children: Vec::new(), untried, visits: 0, reward: 0.0 });
+                let child = self.nodes.len() - 1;
+                self.nodes[current].children.push(child);
+                return child;
+            }
+            if node.children.is_empty() {
+                return current;
+            }
+            let parent_visits = node.visits as f64;
+            current = *node.children.iter()
+                .max_by(|&&a, &&b| self.uct(a, parent_visits, exploration).total_cmp(&self.uct(b, parent_visits, exploration)))
+                .unwrap();
+        }
+    }
+
+    fn backpropagate(&mut self, leaf: usize, winner: Option<Player>) {
+        let mut current = Some(leaf);
+        while let Some(index) = current {
+            let node = &mut self.nodes[index];
+            node.visits += 1;
+            let mover = node.state.who_turn().get_other_player();
+            node.reward += match winner {
+                Some(player) if player == mover => 1.0,
+                Some(_) => 0.0,
+                None => 0.5,
+            };
+            current = node.parent;
+        }
+    }
+}
+
+// Plays the game out and returns the winner, None for a draw.
+fn rollout(state: &BitBoard, policy: Rollout, rng: &mut Rng) -> Option<Player> {
+    let mut state = state.clone();
+    loop {
+        match state.outcome() {
+            GameOutcome::Win(player) => return Some(player),
+            GameOutcome::Draw => return None,
+            GameOutcome::Ongoing => {}
+        }
+        let mut states = state.next_states();
+        let index = match policy {
+            Rollout::Random => rng.below(states.len()),
+            Rollout::Heuristic => {
+                let sign = match state.who_turn() { Player::Player1 => -1, Player::Player2 => 1 };
+                let before = sign * WinHeuristic::score(&state);
+                let winning: Vec<usize> = (0..states.len())
+                    .filter(|&i| sign * WinHeuristic::score(&states[i]) > before)
+                    .collect();
+                if winning.is_empty() { rng.below(states.len()) } else { winning[rng.below(winning.len())] }
+            },
+        };
+        state = states.swap_remove(index);
+    }
+}
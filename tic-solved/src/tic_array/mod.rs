@@ -1,7 +1,18 @@
 pub mod alphabeta_strategy;
 pub mod tic;
+pub mod tic_move;
 pub mod tic_simulator;
 pub mod naive_strategy;
 pub mod tic_terminal_interface;
 pub mod bitmap_tic;
-pub mod alphabeta_strategy_bitboard;
\ No newline at end of file
+pub mod alphabeta_strategy_bitboard;
+pub mod symmetry;
+pub mod endgame_index;
+pub mod ranking;
+pub mod mcts_strategy;
+pub mod tournament;
+pub mod game_record;
+pub mod engine_protocol;
+pub mod async_simulator;
+mod zobrist;
+mod random;
\ No newline at end of file
@@ -1,5 +1,5 @@
 use crate::{
-    tic_array::{tic_simulator::Strategy, tic::*},
+    tic_array::{tic_simulator::{Strategy, MoveInfo}, tic::*, alphabeta_strategy::evaluated},
     minmax::{*, naive::*}
 };
 
@@ -15,14 +15,19 @@ impl MinimaxStrategy {
 
 impl Strategy for MinimaxStrategy {
     fn get_move(&self, game_state: &Board) -> Option<Board> {
-        match game_state.who_turn() {
+        self.get_move_with_info(game_state).map(|(board, _)| board)
+    }
+
+    fn get_move_with_info(&self, game_state: &Board) -> Option<(Board, MoveInfo)> {
+        let (board, score) = match game_state.who_turn() {
             Player::Player1 => get_move_player1(self.steps_to_search, game_state),
             Player::Player2 => get_move_player2(self.steps_to_search, game_state)
-        }
+        }?;
+        Some((board, evaluated(score, self.steps_to_search)))
     }
 }
 
-fn get_move_player1(steps_to_search: usize, game_state: &Board) -> Option<Board> {
+fn get_move_player1(steps_to_search: usize, game_state: &Board) -> Option<(Board, i32)> {
     let states = game_state.next_states();
     let mut best_score = None;
     let mut best_board = None;
@@ -40,10 +45,10 @@ fn get_move_player1(steps_to_search: usize, game_state: &Board) -> Option<Board>
             best_board = Some(state);
         }
     }
-    best_board
+    Some((best_board?, best_score?))
 }
 
-fn get_move_player2(steps_to_search: usize, game_state: &Board) -> Option<Board> {
+fn get_move_player2(steps_to_search: usize, game_state: &Board) -> Option<(Board, i32)> {
     let states = game_state.next_states();
     let mut best_score = None;
     let mut best_board = None;
@@ -61,5 +66,5 @@ fn get_move_player2(steps_to_search: usize, game_state: &Board) -> Option<Board>
             best_board = Some(state);
         }
     }
-    best_board
+    Some((best_board?, best_score?))
 }
\ No newline at end of file
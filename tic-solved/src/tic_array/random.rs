@@ -0,0 +1,21 @@
+use crate::tic_array::zobrist::splitmix64;
+
+// Small seeded generator for reproducible playouts and openings; not suitable for anything
+// that needs real randomness.
+pub(crate) struct Rng(u64);
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Rng {
+        Rng(seed)
+    }
+
+    pub(crate) fn next(&mut self) -> u64 {
+        let (state, value) = splitmix64(self.0);
+        self.0 = state;
+        value
+    }
+
+    pub(crate) fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
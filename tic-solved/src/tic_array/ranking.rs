@@ -0,0 +1,521 @@
+use std::sync::OnceLock;
+
+use crate::tic_array::bitmap_tic::BitBoard;
+
+// Numbers the legal positions 0, 1, 2, ... without gaps, so anything keyed by position can live in
+// flat arrays: `rank` gives the number of a position and `unrank` the position of a number.
+//
+// A position is legal when
+// - no sub board has a line of both players, and a sub board with lines has a piece of the player
+//   that owns them lying on all of them (the move that won it, after which the sub board is closed);
+// - player 1 has as many pieces as player 2 or one more;
+// - not both players have a line of won sub boards, and one that does made the last move;
+// - the sub board the next move is forced into is neither won nor full, and the player that moved
+//   last has a piece on the matching cell of a sub board that move could have been made in: one
+//   without a line, or one they won with that piece.
+// Every move keeps these, so all positions reachable from the start are legal (not every legal
+// position is reachable). A last move pointing at a won or full sub board is the same position as
+// a free move: it ranks like one, and `unrank` gives the free move.
+//
+// Numbers are ordered by the side to move, then the forced sub board (free first), then the won
+// sub boards, then the contents of the forced sub board and of the others in order. Only the mix
+// of won and undecided sub boards matters for how many positions share the earlier digits, and
+// those counts are tabulated once on first use.
+
+pub(crate) const LOCAL_STATES: usize = 19683;
+const SLOTS: usize = 10;
+pub(crate) const LINES: [u16; 8] = [0o007, 0o070, 0o700, 0o111, 0o222, 0o444, 0o421, 0o124];
+
+// Kinds of sub board. OPEN is the part of NO_LINE that still has an empty cell, which is what the
+// forced sub board has to be.
+pub(crate) const NO_LINE: usize = 0;
+pub(crate) const WON_BY_1: usize = 1;
+pub(crate) const WON_BY_2: usize = 2;
+const OPEN: usize = 3;
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct LocalState {
+    pub(crate) legal: bool,
+    pub(crate) class: usize,
+    pub(crate) delta: i32,
+    pub(crate) empty: u16,
+    // Per player, the cells whose piece could have been the last one played in this sub board.
+    last_move_cells: [u16; 2],
+}
+
+impl LocalState {
+    fn new(code: usize) -> LocalState {
+        let mut state = LocalState { legal: true, class: NO_LINE, delta: 0, empty: 0, last_move_cells: [0; 2] };
+        let mut owned = [0u16; 2];
+        let mut rest = code;
+        for cell in 0..9 {
+            match rest % 3 {
+                0 => state.empty |= 1 << cell,
+                1 => {
+                    owned[0] |= 1 << cell;
+                    state.delta += 1;
+                },
+                _ => {
+                    owned[1] |= 1 << cell;
+                    state.delta -= 1;
+                },
+            }
+            rest /= 3;
+        }
+        for (player, &pieces) in owned.iter().enumerate() {
+            let lines: Vec<u16> = LINES.iter().copied().filter(|&line| pieces & line == line).collect();
+            let winning_pieces = lines.iter().fold(pieces, |cells, &line| cells & line);
+            state.last_move_cells[player] = winning_pieces;
+            if !lines.is_empty() {
+                state.legal &= state.class == NO_LINE && winning_pieces != 0;
+                state.class = WON_BY_1 + player;
+            }
+        }
+        if state.class != NO_LINE {
+            state.last_move_cells[WON_BY_2 - state.class] = 0;
+        }
+        state
+    }
+
+    pub(crate) fn is_open(&self) -> bool {
+        self.class == NO_LINE && self.empty != 0
+    }
+
+    fn is_kind(&self, kind: usize) -> bool {
+        self.legal && if kind == OPEN { self.is_open() } else { self.class == kind }
+    }
+}
+
+// Cell (k, l) of sub board (i, j) is bit 27i + 9k + 3j + l.
+fn cell_bit(board: usize, cell: usize) -> u32 {
+    (27*(board/3) + 9*(cell/3) + 3*(board % 3) + cell % 3) as u32
+}
+
+pub(crate) fn local_code(player1: u128, player2: u128, board: usize) -> usize {
+    (0..9).rev().fold(0, |code, cell| {
+        let bit = 1 << cell_bit(board, cell);
+        3*code + if player1 & bit != 0 { 1 } else if player2 & bit != 0 { 2 } else { 0 }
+    })
+}
+
+pub(crate) fn place_local(code: usize, board: usize, player1: &mut u128, player2: &mut u128) {
+    let mut rest = code;
+    for cell in 0..9 {
+        match rest % 3 {
+            1 => *player1 |= 1 << cell_bit(board, cell),
+            2 => *player2 |= 1 << cell_bit(board, cell),
+            _ => {},
+        }
+        rest /= 3;
+    }
+}
+
+pub(crate) fn forced_board_bit(slot: usize) -> u128 {
+    let t = slot - 1;
+    1 << (27*(t/3) + 3*(t % 3) + 20)
+}
+
+pub(crate) fn forced_board_slot(bit: u128) -> usize {
+    let n = bit.trailing_zeros() as usize;
+    3*(n/27) + (n % 9)/3 + 1
+}
+
+// Forced sub boards whose cell is a corner, an edge or the centre share their counts.
+fn slot_type(slot: usize) -> usize {
+    match slot {
+        0 => 0,
+        5 => 3,
+        2 | 4 | 6 | 8 => 2,
+        _ => 1,
+    }
+}
+
+const TYPE_SLOTS: [usize; 4] = [0, 1, 2, 5];
+
+// Sub board contents of one kind, sorted so that those adding the same to the piece difference and
+// agreeing on whether the last move can have been on the forced cell are next to each other.
+struct Group {
+    delta: i32,
+    last_move: bool,
+    start: usize,
+    len: usize,
+}
+
+struct LocalOrder {
+    codes: [Vec<u16>; 4],
+    groups: [Vec<Group>; 4],
+    position: [Vec<u16>; 4],
+}
+
+impl LocalOrder {
+    fn new(local: &[LocalState], side: usize, slot: usize) -> LocalOrder {
+        let last_mover = 1 - side;
+        let last_move = |code: usize| slot != 0 && local[code].last_move_cells[last_mover] & (1 << (slot - 1)) != 0;
+        let mut order = LocalOrder { codes: Default::default(), groups: Default::default(), position: Default::default() };
+        for kind in 0..4 {
+            let mut codes: Vec<u16> = (0..LOCAL_STATES as u16).filter(|&code| local[code as usize].is_kind(kind)).collect();
+            codes.sort_by_key(|&code| (local[code as usize].delta, last_move(code as usize), code));
+            let mut position = vec![u16::MAX; LOCAL_STATES];
+            let mut groups: Vec<Group> = Vec::new();
+            for (i, &code) in codes.iter().enumerate() {
+                position[code as usize] = i as u16;
+                let (delta, last_move) = (local[code as usize].delta, last_move(code as usize));
+                match groups.last_mut() {
+                    Some(group) if group.delta == delta && group.last_move == last_move => group.len += 1,
+                    _ => groups.push(Group { delta, last_move, start: i, len: 1 }),
+                }
+            }
+            order.codes[kind] = codes;
+            order.groups[kind] = groups;
+            order.position[kind] = position;
+        }
+        order
+    }
+}
+
+// How many sub boards are undecided, won by player 1 and won by player 2.
+type Mix = [usize; 3];
+
+// The number of ways to fill a mix of sub boards that add up to a piece difference, with or without
+// requiring one of them to hold the last move.
+struct Completions {
+    spread: i32,
+    counts: Vec<u128>,
+}
+
+impl Completions {
+    fn new(order: &LocalOrder, mixes: &Mixes, spread: i32) -> Completions {
+        let width = 2*spread as usize + 1;
+        let mut completions = Completions { spread, counts: vec![0; mixes.all.len()*width*2] };
+        let start = completions.offset(0, 0, false).unwrap();
+        completions.counts[start] = 1;
+        for (id, mix) in mixes.all.iter().enumerate().skip(1) {
+            let kind = mix.iter().position(|&n| n > 0).unwrap();
+            let mut smaller = *mix;
+            smaller[kind] -= 1;
+            let smaller = mixes.id(smaller);
+            for difference in -spread..=spread {
+                for need_last_move in [false, true] {
+                    let count = order.groups[kind].iter()
+                        .map(|group| group.len as u128 * completions.count(smaller, difference - group.delta, need_last_move && !group.last_move))
+                        .sum();
+                    let offset = completions.offset(id, difference, need_last_move).unwrap();
+                    completions.counts[offset] = count;
+                }
+            }
+        }
+        completions
+    }
+
+    fn offset(&self, mix: usize, difference: i32, need_last_move: bool) -> Option<usize> {
+        if difference.abs() > self.spread {
+            return None;
+        }
+        let width = 2*self.spread as usize + 1;
+        Some((mix*width + (difference + self.spread) as usize)*2 + need_last_move as usize)
+    }
+
+    fn count(&self, mix: usize, difference: i32, need_last_move: bool) -> u128 {
+        self.offset(mix, difference, need_last_move).map_or(0, |offset| self.counts[offset])
+    }
+}
+
+struct Mixes {
+    all: Vec<Mix>,
+    ids: Vec<usize>,
+}
+
+impl Mixes {
+    fn new() -> Mixes {
+        let mut all = Vec::new();
+        for total in 0..=9 {
+            for undecided in (0..=total).rev() {
+                for won_by_1 in (0..=total - undecided).rev() {
+                    all.push([undecided, won_by_1, total - undecided - won_by_1]);
+                }
+            }
+        }
+        let mut ids = vec![usize::MAX; 1000];
+        for (id, mix) in all.iter().enumerate() {
+            ids[100*mix[0] + 10*mix[1] + mix[2]] = id;
+        }
+        Mixes { all, ids }
+    }
+
+    fn id(&self, mix: Mix) -> usize {
+        self.ids[100*mix[0] + 10*mix[1] + mix[2]]
+    }
+}
+
+fn has_line(meta: &[usize; 9], class: usize) -> bool {
+    LINES.iter().any(|&line| (0..9).all(|b| line & (1 << b) == 0 || meta[b] == class))
+}
+
+fn meta_classes(mut code: usize) -> [usize; 9] {
+    let mut meta = [0; 9];
+    for class in meta.iter_mut() {
+        *class = code % 3;
+        code /= 3;
+    }
+    meta
+}
+
+// The won sub boards allowed with one side to move and forced sub board, grouped by their mix.
+struct MetaOrder {
+    metas: Vec<Vec<u16>>,
+    position: Vec<u16>,
+    per_meta: Vec<u128>,
+    offsets: Vec<u128>,
+}
+
+impl MetaOrder {
+    fn new(side: usize, slot: usize, mixes: &Mixes, order: &LocalOrder, completions: &Completions) -> MetaOrder {
+        let last_mover = WON_BY_2 - side;
+        let mut metas = vec![Vec::new(); mixes.all.len()];
+        let mut position = vec![u16::MAX; LOCAL_STATES];
+        for (code, meta_position) in position.iter_mut().enumerate() {
+            let meta = meta_classes(code);
+            let winners: Vec<usize> = [WON_BY_1, WON_BY_2].into_iter().filter(|&class| has_line(&meta, class)).collect();
+            if winners.iter().any(|&winner| winner != last_mover) || (slot != 0 && meta[slot - 1] != NO_LINE) {
+                continue;
+            }
+            let id = mixes.id(sequence_mix(&meta, slot));
+            *meta_position = metas[id].len() as u16;
+            metas[id].push(code as u16);
+        }
+        let per_meta: Vec<u128> = (0..mixes.all.len())
+            .map(|id| if metas[id].is_empty() {
+                0
+            } else if slot == 0 {
+                completions.count(id, side as i32, false)
+            } else {
+                order.groups[OPEN].iter()
+                    .map(|group| group.len as u128 * completions.count(id, side as i32 - group.delta, !group.last_move))
+                    .sum()
+            })
+            .collect();
+        let mut offsets = vec![0];
+        for (id, metas) in metas.iter().enumerate() {
+            offsets.push(offsets[id] + metas.len() as u128 * per_meta[id]);
+        }
+        MetaOrder { metas, position, per_meta, offsets }
+    }
+}
+
+// The mix of the sub boards other than the forced one.
+fn sequence_mix(meta: &[usize; 9], slot: usize) -> Mix {
+    let mut mix = [0; 3];
+    for (board, &class) in meta.iter().enumerate() {
+        if slot == 0 || board != slot - 1 {
+            mix[class] += 1;
+        }
+    }
+    mix
+}
+
+// The forced sub board comes first, then the others in order.
+fn sequence(slot: usize) -> impl Iterator<Item = usize> {
+    let forced = slot.checked_sub(1);
+    forced.into_iter().chain((0..9).filter(move |&board| Some(board) != forced))
+}
+
+struct Tables {
+    local: Vec<LocalState>,
+    mixes: Mixes,
+    orders: Vec<LocalOrder>,
+    completions: Vec<Completions>,
+    metas: Vec<MetaOrder>,
+    offsets: Vec<u128>,
+}
+
+impl Tables {
+    fn new() -> Tables {
+        let local: Vec<LocalState> = (0..LOCAL_STATES).map(LocalState::new).collect();
+        let max_delta = local.iter().filter(|state| state.legal).map(|state| state.delta.abs()).max().unwrap();
+        let mixes = Mixes::new();
+        let orders: Vec<LocalOrder> = (0..2*SLOTS).map(|key| LocalOrder::new(&local, key / SLOTS, key % SLOTS)).collect();
+        let completions: Vec<Completions> = (0..2*TYPE_SLOTS.len())
+            .map(|key| Completions::new(&orders[(key / TYPE_SLOTS.len())*SLOTS + TYPE_SLOTS[key % TYPE_SLOTS.len()]], &mixes, 9*max_delta))
+            .collect();
+        let metas: Vec<MetaOrder> = (0..2*SLOTS)
+            .map(|key| {
+                let (side, slot) = (key / SLOTS, key % SLOTS);
+                MetaOrder::new(side, slot, &mixes, &orders[key], &completions[side*TYPE_SLOTS.len() + slot_type(slot)])
+            })
+            .collect();
+        let mut offsets = vec![0];
+        for (key, meta) in metas.iter().enumerate() {
+            offsets.push(offsets[key] + meta.offsets.last().unwrap());
+        }
+        Tables { local, mixes, orders, completions, metas, offsets }
+    }
+
+    fn completions(&self, side: usize, slot: usize) -> &Completions {
+        &self.completions[side*TYPE_SLOTS.len() + slot_type(slot)]
+    }
+}
+
+fn tables() -> &'static Tables {
+    static TABLES: OnceLock<Tables> = OnceLock::new();
+    TABLES.get_or_init(Tables::new)
+}
+
+pub(crate) fn local_states() -> &'static [LocalState] {
+    &tables().local
+}
+
+pub fn position_count() -> u128 {
+    *tables().offsets.last().unwrap()
+}
+
+// None if the position is not legal.
+pub fn rank(board: &BitBoard) -> Option<u128> {
+    let tables = tables();
+    let (player1, player2, _) = board.to_parts();
+    if player1 & player2 != 0 || (player1 | player2) >> 81 != 0 {
+        return None;
+    }
+    let codes: [usize; 9] = std::array::from_fn(|b| local_code(player1, player2, b));
+    if codes.iter().any(|&code| !tables.local[code].legal) {
+        return None;
+    }
+    let side = match codes.iter().map(|&code| tables.local[code].delta).sum() {
+        0 => 0,
+        1 => 1,
+        _ => return None,
+    };
+    let slot = match board.forced_board() {
+        Some(bit) => match forced_board_slot(bit) {
+            slot if slot < SLOTS && forced_board_bit(slot) == bit => slot,
+            _ => return None,
+        },
+        None => 0,
+    };
+
+    let meta = codes.iter().rev().fold(0, |meta, &code| 3*meta + tables.local[code].class);
+    let key = side*SLOTS + slot;
+    let meta_order = &tables.metas[key];
+    let meta_position = meta_order.position[meta];
+    if meta_position == u16::MAX {
+        return None;
+    }
+    let mut mix = sequence_mix(&meta_classes(meta), slot);
+    let id = tables.mixes.id(mix);
+    let mut index = tables.offsets[key] + meta_order.offsets[id] + meta_position as u128 * meta_order.per_meta[id];
+
+    let order = &tables.orders[key];
+    let completions = tables.completions(side, slot);
+    let mut difference = side as i32;
+    let mut need_last_move = slot != 0;
+    for board in sequence(slot) {
+        let code = codes[board];
+        let kind = if board + 1 == slot { OPEN } else { tables.local[code].class };
+        if kind != OPEN {
+            mix[kind] -= 1;
+        }
+        let rest = tables.mixes.id(mix);
+        let position = order.position[kind][code] as usize;
+        for group in &order.groups[kind] {
+            let completing = completions.count(rest, difference - group.delta, need_last_move && !group.last_move);
+            if position < group.start + group.len {
+                index += (position - group.start) as u128 * completing;
+                difference -= group.delta;
+                need_last_move &= !group.last_move;
+                break;
+            }
+            index += group.len as u128 * completing;
+        }
+    }
+    if need_last_move {
+        return None;
+    }
+    Some(index)
+}
+
+// None if `index` is not below `position_count()`.
+pub fn unrank(index: u128) -> Option<BitBoard> {
+    let tables = tables();
+    if index >= position_count() {
+        return None;
+    }
+    let key = tables.offsets.partition_point(|&offset| offset <= index) - 1;
+    let (side, slot) = (key / SLOTS, key % SLOTS);
+    let meta_order = &tables.metas[key];
+    let mut rest = index - tables.offsets[key];
+    let id = meta_order.offsets.partition_point(|&offset| offset <= rest) - 1;
+    rest -= meta_order.offsets[id];
+    let meta = meta_classes(meta_order.metas[id][(rest / meta_order.per_meta[id]) as usize] as usize);
+    rest %= meta_order.per_meta[id];
+
+    let order = &tables.orders[key];
+    let completions = tables.completions(side, slot);
+    let mut mix = tables.mixes.all[id];
+    let mut difference = side as i32;
+    let mut need_last_move = slot != 0;
+    let (mut player1, mut player2) = (0, 0);
+    for board in sequence(slot) {
+        let kind = if board + 1 == slot { OPEN } else { meta[board] };
+        if kind != OPEN {
+            mix[kind] -= 1;
+        }
+        let remaining = tables.mixes.id(mix);
+        for group in &order.groups[kind] {
+            let completing = completions.count(remaining, difference - group.delta, need_last_move && !group.last_move);
+            let block = group.len as u128 * completing;
+            if rest < block {
+                let code = order.codes[kind][group.start + (rest / completing) as usize];
+                place_local(code as usize, board, &mut player1, &mut player2);
+                rest %= completing;
+                difference -= group.delta;
+                need_last_move &= !group.last_move;
+                break;
+            }
+            rest -= block;
+        }
+    }
+    let last_move = if slot == 0 { None } else { Some(forced_board_bit(slot)) };
+    Some(BitBoard::from_parts(player1, player2, last_move))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slots_round_trip() {
+        for slot in 1..SLOTS {
+            assert_eq!(forced_board_slot(forced_board_bit(slot)), slot);
+        }
+    }
+
+    fn code_of(cells: [usize; 9]) -> usize {
+        cells.iter().rev().fold(0, |code, &cell| 3*code + cell)
+    }
+
+    #[test]
+    fn test_local_states() {
+        let local = &tables().local;
+        assert_eq!(local.iter().filter(|state| state.legal).count(), 18753);
+        // Player 1 on the top row and the left column, won with the corner they share.
+        let corner_win = code_of([1, 1, 1, 1, 2, 2, 1, 2, 2]);
+        assert!(local[corner_win].legal);
+        assert_eq!(local[corner_win].last_move_cells, [1, 0]);
+        // Two separate rows can't have been completed by one move.
+        let two_rows = code_of([1, 1, 1, 2, 2, 0, 1, 1, 1]);
+        assert!(!local[two_rows].legal);
+    }
+
+    #[test]
+    fn test_local_code_round_trip() {
+        for board in 0..9 {
+            let (mut player1, mut player2) = (0, 0);
+            place_local(12345, board, &mut player1, &mut player2);
+            assert_eq!(local_code(player1, player2, board), 12345);
+        }
+    }
+
+    #[test]
+    fn test_position_count() {
+        assert_eq!(position_count(), 194349540353986585249873108731337407233);
+    }
+}
@@ -0,0 +1,127 @@
+// The dihedral symmetries of the square. Every symmetry is applied to the macro grid and to each
+// micro grid at the same time, which maps legal positions onto legal positions.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Symmetry {
+    Identity,
+    Rotate90, // Clockwise
+    Rotate180,
+    Rotate270,
+    FlipRows, // Mirror top to bottom
+    FlipColumns, // Mirror left to right
+    Transpose, // Mirror in the main diagonal
+    AntiTranspose, // Mirror in the anti diagonal
+}
+
+impl Symmetry {
+    pub const ALL: [Symmetry; 8] = [
+        Symmetry::Identity,
+        Symmetry::Rotate90,
+        Symmetry::Rotate180,
+        Symmetry::Rotate270,
+        Symmetry::FlipRows,
+        Symmetry::FlipColumns,
+        Symmetry::Transpose,
+        Symmetry::AntiTranspose,
+    ];
+
+    pub fn apply(&self, (row, col): (usize, usize)) -> (usize, usize) {
+        match self {
+            Symmetry::Identity => (row, col),
+            Symmetry::Rotate90 => (col, 2 - row),
+            Symmetry::Rotate180 => (2 - row, 2 - col),
+            Symmetry::Rotate270 => (2 - col, row),
+            Symmetry::FlipRows => (2 - row, col),
+            Symmetry::FlipColumns => (row, 2 - col),
+            Symmetry::Transpose => (col, row),
+            Symmetry::AntiTranspose => (2 - col, 2 - row),
+        }
+    }
+
+    pub fn inverse(&self) -> Symmetry {
+        match self {
+            Symmetry::Rotate90 => Symmetry::Rotate270,
+            Symmetry::Rotate270 => Symmetry::Rotate90,
+            other => *other,
+        }
+    }
+
+    // Maps a (macro row, macro col, micro row, micro col) coordinate.
+    pub fn apply_cell(&self, (i, j, k, l): (usize, usize, usize, usize)) -> (usize, usize, usize, usize) {
+        let (i, j) = self.apply((i, j));
+        let (k, l) = self.apply((k, l));
+        (i, j, k, l)
+    }
+
+    // Maps a coordinate in the transformed position back to the original position.
+    pub fn unapply_cell(&self, cell: (usize, usize, usize, usize)) -> (usize, usize, usize, usize) {
+        self.inverse().apply_cell(cell)
+    }
+}
+
+pub trait Symmetric: Sized {
+    type Key: Ord;
+
+    fn transform(&self, symmetry: Symmetry) -> Self;
+
+    // Total order used to pick the canonical representative; equal positions must have equal keys.
+    fn canonical_key(&self) -> Self::Key;
+
+    fn symmetries(&self) -> Vec<(Symmetry, Self)> {
+        Symmetry::ALL.iter()
+            .map(|&s| (s, self.transform(s)))
+            .collect()
+    }
+
+    // Returns the representative of the position's equivalence class and the symmetry that
+    // transforms `self` into it. Moves found in the representative can be mapped back with
+    // `Symmetry::unapply_cell`, successor positions with `transform(symmetry.inverse())`.
+    fn canonical(&self) -> (Self, Symmetry) {
+        let (symmetry, representative) = self.symmetries()
+            .into_iter()
+            .min_by_key(|(_, position)| position.canonical_key())
+            .unwrap();
+        (representative, symmetry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use itertools::iproduct;
+
+    use super::*;
+
+    #[test]
+    fn test_inverse() {
+        for s in Symmetry::ALL {
+            for pos in iproduct!(0..3, 0..3) {
+                assert_eq!(s.inverse().apply(s.apply(pos)), pos);
+            }
+        }
+    }
+
+    #[test]
+    fn test_bijective() {
+        for s in Symmetry::ALL {
+            let image: HashSet<_> = iproduct!(0..3, 0..3).map(|pos| s.apply(pos)).collect();
+            assert_eq!(image.len(), 9);
+        }
+    }
+
+    #[test]
+    fn test_distinct() {
+        let images: HashSet<Vec<_>> = Symmetry::ALL.iter()
+            .map(|s| iproduct!(0..3, 0..3).map(|pos| s.apply(pos)).collect())
+            .collect();
+        assert_eq!(images.len(), 8);
+    }
+
+    #[test]
+    fn test_centre_fixed() {
+        for s in Symmetry::ALL {
+            assert_eq!(s.apply_cell((1, 1, 1, 1)), (1, 1, 1, 1));
+        }
+    }
+}
@@ -1,7 +1,8 @@
-use std::fmt;
+use std::{fmt, io::{self, Write}};
 use itertools::iproduct;
 
-use crate::minmax::{GameState, Heuristic};
+use crate::minmax::{GameState, Heuristic, MoveKey, ZobristHash, alphabeta::SearchResult};
+use crate::tic_array::{zobrist::*, symmetry::*, tic_move::*};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum Player {
@@ -10,7 +11,7 @@ pub enum Player {
 }
 
 impl Player {
-    fn get_other_player(&self) -> Player {
+    pub fn get_other_player(&self) -> Player {
         match self {
             Player::Player1 => Player::Player2,
             Player::Player2 => Player::Player1,
@@ -18,6 +19,39 @@ impl Player {
     }
 }
 
+// Where a game stands. A draw is a game nobody won where every sub board is decided, so there are
+// no moves left.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GameOutcome {
+    Win(Player),
+    Draw,
+    Ongoing,
+}
+
+impl GameOutcome {
+    pub fn is_over(&self) -> bool {
+        *self != GameOutcome::Ongoing
+    }
+
+    pub fn winner(&self) -> Option<Player> {
+        match self {
+            GameOutcome::Win(player) => Some(*player),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for GameOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameOutcome::Win(Player::Player1) => write!(f, "player 1 (X) wins"),
+            GameOutcome::Win(Player::Player2) => write!(f, "player 2 (O) wins"),
+            GameOutcome::Draw => write!(f, "draw"),
+            GameOutcome::Ongoing => write!(f, "ongoing"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 enum Tile {
     Player1,
@@ -131,7 +165,7 @@ impl Board {
         (cells, self.last_move)
     }
 
-    fn number_almost_wins(&self, x0: usize, x1: usize, player: Player) -> usize {
+    pub(crate) fn number_almost_wins(&self, x0: usize, x1: usize, player: Player) -> usize {
         let mut count = 0;
         // Check horizontal lines
         for x2 in 0..3 {
@@ -152,7 +186,7 @@ impl Board {
             }
         }
         // Check vertical lines
-        for x3 in 0..2 {
+        for x3 in 0..3 {
             if self.cells[x0][x1][0][x3] == self.cells[x0][x1][1][x3] {
                 if self.cells[x0][x1][0][x3] == player.into() && self.cells[x0][x1][2][x3] == Tile::Empty{
                     count += 1;
@@ -205,7 +239,7 @@ impl Board {
         count
     }
 
-    fn single_board_winner(&self, x0: usize, x1: usize) -> Option<Player> {
+    pub fn single_board_winner(&self, x0: usize, x1: usize) -> Option<Player> {
         // Check horizontal lines
         for x2 in 0..3 {
             if self.cells[x0][x1][x2][0] == self.cells[x0][x1][x2][1] && self.cells[x0][x1][x2][1] == self.cells[x0][x1][x2][2] {
@@ -273,22 +307,25 @@ impl Board {
         }
     
         // Check diagonals
-        let diagonal_win = {
-            let first = self.single_board_winner(0, 0)?;
-            if (1..3).all(|x| self.single_board_winner(x, x) == Some(first)) {
-                Some(first)
-            } else {
-                let first = self.single_board_winner(0, 2)?;
-                if (1..3).all(|x| self.single_board_winner(x, 2 - x) == Some(first)) {
-                    Some(first)
-                } else {
-                    None
-                }
-            }
-        };
-    
-        diagonal_win
+        let diagonal_win = self.single_board_winner(0, 0)
+            .filter(|&first| (1..3).all(|x| self.single_board_winner(x, x) == Some(first)));
+        if diagonal_win.is_some() {
+            return diagonal_win;
+        }
+
+        self.single_board_winner(0, 2)
+            .filter(|&first| (1..3).all(|x| self.single_board_winner(x, 2 - x) == Some(first)))
     }
+    pub fn outcome(&self) -> GameOutcome {
+        if let Some(player) = self.board_winner() {
+            return GameOutcome::Win(player);
+        }
+        // Play may go anywhere once the target board is decided, so only undecided boards matter.
+        let undecided = iproduct!(0..3, 0..3)
+            .any(|(x0, x1)| self.single_board_winner(x0, x1).is_none() && !self.single_board_tie(x0, x1));
+        if undecided { GameOutcome::Ongoing } else { GameOutcome::Draw }
+    }
+
     pub fn who_turn(&self) -> Player {
         let mut player1_count = 0;
         let mut player2_count = 0;
@@ -321,6 +358,38 @@ impl Board {
         }
         states
     }
+
+    // In the same order as get_legal_boards.
+    pub fn legal_moves(&self) -> Vec<Move> {
+        TicMove::iter_moves(self)
+            .filter_map(|m| Move::from_coordinates(m.move_to_make))
+            .collect()
+    }
+
+    pub fn play(&self, m: Move) -> Result<Board, IllegalMove> {
+        match TicMove::new(m.coordinates(), self) {
+            Some(tic_move) => Ok(tic_move.do_move()),
+            None => Err(IllegalMove { attempted: m, reason: self.illegal_reason(m) }),
+        }
+    }
+
+    // The legal move that leads to `next`, if there is one.
+    pub fn move_to(&self, next: &Board) -> Option<Move> {
+        self.legal_moves().into_iter().find(|&m| self.play(m).as_ref() == Ok(next))
+    }
+
+    fn illegal_reason(&self, m: Move) -> IllegalMoveReason {
+        let (i, j, k, l) = m.coordinates();
+        if self.board_winner().is_some() {
+            IllegalMoveReason::GameOver
+        } else if self.cells[i][j][k][l] != Tile::Empty {
+            IllegalMoveReason::Occupied
+        } else if self.single_board_winner(i, j).is_some() || self.single_board_tie(i, j) {
+            IllegalMoveReason::BoardDecided
+        } else {
+            IllegalMoveReason::WrongBoard
+        }
+    }
 }
 
 impl fmt::Display for Board {
@@ -433,10 +502,16 @@ impl From<&str> for BoardResult {
 
 pub fn print_on_board<T>(board: &Board, data: Vec<T>)
 where T: Copy + fmt::Display, {
+    write_on_board(&mut io::stdout().lock(), board, data).expect("Failed to write to stdout");
+}
+
+// Prints `data` in the cells of the legal moves, in legal_moves order, and the tiles elsewhere.
+pub fn write_on_board<W, T>(out: &mut W, board: &Board, data: Vec<T>) -> io::Result<()>
+where W: Write, T: Copy + fmt::Display, {
     let max_length = data.iter()
         .map(|item| item.to_string().len())
         .max()
-        .unwrap();
+        .unwrap_or(1);
     for i in 0..3 {
         for k in 0..3 {
             for j in 0..3 {
@@ -450,22 +525,47 @@ where T: Copy + fmt::Display, {
                     }
                     let length = {
                         if let Some(num) = move_num {
-                            print!(" {}", num);
+                            write!(out, " {}", num)?;
                             num.to_string().len()
                         } else {
-                            print!(" {}", tile);
+                            write!(out, " {}", tile)?;
                             1
                         }
                     };
-                    for _ in 0..max_length-length {
-                        print!(" ");
+                    for _ in length..max_length {
+                        write!(out, " ")?;
                     }
                 }
-                print!(" ");
+                write!(out, " ")?;
             }
-            print!("\n");
+            writeln!(out)?;
         }
-        print!("\n");
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+// Prints every root move's score on the board, followed by the principal variation.
+pub fn print_search_result<T, S>(board: &Board, result: &SearchResult<T, S>)
+where
+    T: Clone + Into<Board>,
+    S: Copy + fmt::Display,
+{
+    let root_scores: Vec<(Board, S)> = result.root_scores.iter()
+        .map(|(state, score)| (state.clone().into(), *score))
+        .collect();
+    let scores: Option<Vec<S>> = board.get_legal_boards()
+        .iter()
+        .map(|legal| root_scores.iter().find(|(state, _)| state == legal).map(|(_, score)| *score))
+        .collect();
+    match scores {
+        Some(scores) if !scores.is_empty() => print_on_board(board, scores),
+        _ => println!("{}", board),
+    }
+    println!("score: {} depth: {} nodes: {} cutoffs: {}", result.score, result.depth, result.nodes, result.cutoffs);
+    for (ply, state) in result.principal_variation.iter().enumerate() {
+        let state: Board = state.clone().into();
+        println!("pv {}:\n{}", ply + 1, state);
     }
 }
 
@@ -530,19 +630,64 @@ impl GameState for Board {
     }
 }
 
+// The cell index of the piece `child` added.
+impl MoveKey for Board {
+    fn move_key(&self, child: &Self) -> usize {
+        iproduct!(0..3, 0..3, 0..3, 0..3)
+            .find(|&(i, j, k, l)| self.cells[i][j][k][l] != child.cells[i][j][k][l])
+            .map_or(0, |(i, j, k, l)| cell_index(i, j, k, l))
+    }
+}
+
+impl ZobristHash for Board {
+    fn zobrist_hash(&self) -> u64 {
+        let mut hash = 0;
+        for (i, j, k, l) in iproduct!(0..3, 0..3, 0..3, 0..3) {
+            match self.cells[i][j][k][l] {
+                Tile::Player1 => hash ^= PLAYER1_KEYS[cell_index(i, j, k, l)],
+                Tile::Player2 => hash ^= PLAYER2_KEYS[cell_index(i, j, k, l)],
+                Tile::Empty => {}
+            }
+        }
+        if let Some((_, _, x, y)) = self.last_move {
+            hash ^= LAST_MOVE_KEYS[3*x + y];
+        }
+        hash
+    }
+}
+
+impl Symmetric for Board {
+    type Key = ([[[[u8; 3]; 3]; 3]; 3], Option<(usize, usize)>);
+
+    fn transform(&self, symmetry: Symmetry) -> Board {
+        let mut cells = [[[[Tile::Empty; 3]; 3]; 3]; 3];
+        for (i, j, k, l) in iproduct!(0..3, 0..3, 0..3, 0..3) {
+            let (i2, j2, k2, l2) = symmetry.apply_cell((i, j, k, l));
+            cells[i2][j2][k2][l2] = self.cells[i][j][k][l];
+        }
+        Board {
+            cells,
+            player_to_move: self.player_to_move,
+            last_move: self.last_move.map(|m| symmetry.apply_cell(m)),
+        }
+    }
+
+    fn canonical_key(&self) -> Self::Key {
+        let (cells, last_move) = self.to_arr();
+        (cells, last_move.map(|(_, _, x, y)| (x, y)))
+    }
+}
+
 pub struct AlmostWinHeuristic {}
 
 impl Heuristic<Board> for AlmostWinHeuristic {
     type Score = i32;
     fn score(board: &Board) -> Self::Score {
-        if board.board_winner() == Some(Player::Player1) {
-            return -1000;
-        }
-        if board.board_winner() == Some(Player::Player2) {
-            return 1000;
-        }
-        if board.get_legal_boards().len() == 0 {
-            return 0;
+        match board.outcome() {
+            GameOutcome::Win(Player::Player1) => return -1000,
+            GameOutcome::Win(Player::Player2) => return 1000,
+            GameOutcome::Draw => return 0,
+            GameOutcome::Ongoing => {}
         }
 
         let mut count = 0;
@@ -698,6 +843,20 @@ mod tests {
     }
 
 
+    // Regression: the diagonal check returned early when the top left board wasn't won, so the
+    // other diagonal was never looked at.
+    #[test]
+    fn test_is_board_winning_right_left_diagonal() {
+        let mut board = Board::new();
+        for (x0, x1) in [(0, 2), (1, 1), (2, 0)] {
+            for k in 0..3 {
+                board.cells[x0][x1][k][0] = Tile::Player1;
+            }
+        }
+        assert_eq!(board.single_board_winner(0, 0), None);
+        assert_eq!(board.board_winner(), Some(Player::Player1));
+    }
+
     #[test]
     fn test_is_board_winning() {
 
@@ -0,0 +1,126 @@
+use std::{fmt, str::FromStr};
+
+use crate::tic_array::zobrist::cell_index;
+
+// A single placement. Notation gives the sub board as column A-C and row 1-3, then the cell
+// within it as column a-c and row 1-3, so B2b2 is the centre of the centre board and C1a3 the
+// bottom left cell of the top right board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Move {
+    board: (usize, usize), // (row, column)
+    cell: (usize, usize),
+}
+
+impl Move {
+    // None if any coordinate is outside 0..3.
+    pub fn new(board: (usize, usize), cell: (usize, usize)) -> Option<Move> {
+        [board.0, board.1, cell.0, cell.1].iter().all(|&c| c < 3).then_some(Move { board, cell })
+    }
+
+    // Same indexing as Board's cells: cells[i][j][k][l].
+    pub fn from_coordinates((i, j, k, l): (usize, usize, usize, usize)) -> Option<Move> {
+        Move::new((i, j), (k, l))
+    }
+
+    pub fn coordinates(&self) -> (usize, usize, usize, usize) {
+        (self.board.0, self.board.1, self.cell.0, self.cell.1)
+    }
+
+    // Index of the move's bit in a BitBoard.
+    pub fn from_index(n: usize) -> Option<Move> {
+        (n < 81).then_some(Move { board: (n / 27, (n % 9) / 3), cell: ((n % 27) / 9, n % 3) })
+    }
+
+    pub fn index(&self) -> usize {
+        let (i, j, k, l) = self.coordinates();
+        cell_index(i, j, k, l)
+    }
+
+    pub fn board(&self) -> (usize, usize) {
+        self.board
+    }
+
+    pub fn cell(&self) -> (usize, usize) {
+        self.cell
+    }
+
+    // The sub board this move sends the opponent to.
+    pub fn target_board(&self) -> (usize, usize) {
+        self.cell
+    }
+}
+
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (i, j, k, l) = self.coordinates();
+        write!(f, "{}{}{}{}", (b'A' + j as u8) as char, i + 1, (b'a' + l as u8) as char, k + 1)
+    }
+}
+
+impl FromStr for Move {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let digit = |c: u8, first: u8| (first..first + 3).contains(&c).then(|| (c - first) as usize);
+        let coordinates = match s.trim().as_bytes() {
+            [j, i, l, k] => (digit(*i, b'1'), digit(*j, b'A'), digit(*k, b'1'), digit(*l, b'a')),
+            _ => return Err(format!("Invalid move: {}", s)),
+        };
+        match coordinates {
+            (Some(i), Some(j), Some(k), Some(l)) => Ok(Move { board: (i, j), cell: (k, l) }),
+            _ => Err(format!("Invalid move: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IllegalMoveReason {
+    GameOver,
+    Occupied,
+    // The sub board is already won or full.
+    BoardDecided,
+    // The previous move sends play to another sub board.
+    WrongBoard,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IllegalMove {
+    pub attempted: Move,
+    pub reason: IllegalMoveReason,
+}
+
+impl fmt::Display for IllegalMove {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self.reason {
+            IllegalMoveReason::GameOver => "the game is over",
+            IllegalMoveReason::Occupied => "the cell is taken",
+            IllegalMoveReason::BoardDecided => "the sub board is already decided",
+            IllegalMoveReason::WrongBoard => "the move has to be played in another sub board",
+        };
+        write!(f, "{} is illegal: {}", self.attempted, reason)
+    }
+}
+
+impl std::error::Error for IllegalMove {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notation_round_trip() {
+        for n in 0..81 {
+            let m = Move::from_index(n).unwrap();
+            assert_eq!(m.index(), n);
+            assert_eq!(m.to_string().parse(), Ok(m));
+            assert_eq!(Move::from_coordinates(m.coordinates()), Some(m));
+        }
+        assert_eq!(Move::new((1, 1), (1, 1)).unwrap().to_string(), "B2b2");
+        assert_eq!(Move::new((0, 2), (2, 0)).unwrap().to_string(), "C1a3");
+        assert_eq!(Move::from_index(81), None);
+        assert_eq!(Move::new((3, 0), (0, 0)), None);
+        assert!("D1a1".parse::<Move>().is_err());
+        assert!("B2b".parse::<Move>().is_err());
+        assert!("b2B2".parse::<Move>().is_err());
+    }
+}
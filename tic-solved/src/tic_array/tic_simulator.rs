@@ -1,34 +1,244 @@
-use log::info;
-use crate::tic_array::tic::*;
+use std::{
+    fmt,
+    sync::{Arc, atomic::AtomicBool},
+    time::{Duration, Instant},
+};
 
-// TODO:
-// - Refactor this, such that it runs the game loop but doesn't have anything to do with terminal
-//   by making TerminalHumanPlayerInterface that implements HumanPlayerInterface
+use log::info;
+use crate::tic_array::{tic::*, tic_move::*};
 
 pub trait Strategy {
     fn get_move(&self, game_state: &Board) -> Option<Board>; // None if there are no moves to make.
+
+    // Strategies that know how they rated their move can report it here.
+    fn get_move_with_info(&self, game_state: &Board) -> Option<(Board, MoveInfo)> {
+        self.get_move(game_state).map(|board| (board, MoveInfo::default()))
+    }
+
+    // Like get_move_with_info, but the search may give up once `stop` is raised, because nobody
+    // is waiting for its move any more. Strategies that can't stop finish the search.
+    fn get_move_until(&self, game_state: &Board, _stop: &Arc<AtomicBool>) -> Option<(Board, MoveInfo)> {
+        self.get_move_with_info(game_state)
+    }
+}
+
+impl<S: Strategy + ?Sized> Strategy for &S {
+    fn get_move(&self, game_state: &Board) -> Option<Board> {
+        (**self).get_move(game_state)
+    }
+
+    fn get_move_with_info(&self, game_state: &Board) -> Option<(Board, MoveInfo)> {
+        (**self).get_move_with_info(game_state)
+    }
+
+    fn get_move_until(&self, game_state: &Board, stop: &Arc<AtomicBool>) -> Option<(Board, MoveInfo)> {
+        (**self).get_move_until(game_state, stop)
+    }
+}
+
+impl<S: Strategy + ?Sized> Strategy for Box<S> {
+    fn get_move(&self, game_state: &Board) -> Option<Board> {
+        (**self).get_move(game_state)
+    }
+
+    fn get_move_with_info(&self, game_state: &Board) -> Option<(Board, MoveInfo)> {
+        (**self).get_move_with_info(game_state)
+    }
+
+    fn get_move_until(&self, game_state: &Board, stop: &Arc<AtomicBool>) -> Option<(Board, MoveInfo)> {
+        (**self).get_move_until(game_state, stop)
+    }
 }
 
-pub fn run_game<T: Strategy, U: Strategy>(mut board: Board, strategy1: T, strategy2: U) {
+// What a player knows about the move it chose. Everything is optional.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MoveInfo {
+    pub evaluation: Option<i32>, // Positive favours player 2, like the heuristics.
+    pub depth: Option<usize>,
+    pub principal_variation: Vec<Move>, // Starts with the chosen move.
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decision {
+    pub chosen: Move,
+    pub info: MoveInfo,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AgentError {
+    NoMoves,
+    Illegal(IllegalMove),
+    Failed(String),
+}
+
+impl fmt::Display for AgentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AgentError::NoMoves => write!(f, "no move to play"),
+            AgentError::Illegal(illegal) => write!(f, "{}", illegal),
+            AgentError::Failed(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for AgentError {}
+
+// A player that keeps state across a game and is told what happens in it. Strategy is the
+// stateless version; wrap one in StrategyAgent to use it here.
+pub trait Agent {
+    fn name(&self) -> String {
+        "agent".to_string()
+    }
+
+    fn game_started(&mut self, _start: &Board, _side: Player) {}
+
+    fn choose_move(&mut self, board: &Board) -> Result<Decision, AgentError>;
+
+    // `after` is the position the agent has to move in next.
+    fn opponent_moved(&mut self, _opponent_move: Move, _after: &Board) {}
+
+    fn game_ended(&mut self, _result: GameResult) {}
+}
+
+impl<A: Agent + ?Sized> Agent for &mut A {
+    fn name(&self) -> String {
+        (**self).name()
+    }
+
+    fn game_started(&mut self, start: &Board, side: Player) {
+        (**self).game_started(start, side)
+    }
+
+    fn choose_move(&mut self, board: &Board) -> Result<Decision, AgentError> {
+        (**self).choose_move(board)
+    }
+
+    fn opponent_moved(&mut self, opponent_move: Move, after: &Board) {
+        (**self).opponent_moved(opponent_move, after)
+    }
+
+    fn game_ended(&mut self, result: GameResult) {
+        (**self).game_ended(result)
+    }
+}
+
+pub struct StrategyAgent<S> {
+    name: String,
+    strategy: S,
+}
+
+impl<S: Strategy> StrategyAgent<S> {
+    pub fn new(name: impl Into<String>, strategy: S) -> StrategyAgent<S> {
+        StrategyAgent { name: name.into(), strategy }
+    }
+
+    pub fn into_inner(self) -> S {
+        self.strategy
+    }
+}
+
+impl<S: Strategy> Agent for StrategyAgent<S> {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn choose_move(&mut self, board: &Board) -> Result<Decision, AgentError> {
+        let (next, info) = self.strategy.get_move_with_info(board).ok_or(AgentError::NoMoves)?;
+        board.move_to(&next)
+            .map(|chosen| Decision { chosen, info })
+            .ok_or(AgentError::Failed(format!("{} returned a board that no legal move leads to", self.name)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameResult {
+    Win(Player),
+    Draw,
+    // The player returned an illegal board, or none while there were legal moves.
+    Forfeit(Player),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MoveRecord {
+    pub player: Player,
+    pub board: Board, // Position after the move.
+    pub elapsed: Duration,
+    pub info: MoveInfo,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameRecord {
+    pub start: Board,
+    pub moves: Vec<MoveRecord>,
+    pub result: GameResult,
+}
+
+impl GameRecord {
+    pub fn winner(&self) -> Option<Player> {
+        match self.result {
+            GameResult::Win(player) => Some(player),
+            GameResult::Forfeit(player) => Some(player.get_other_player()),
+            GameResult::Draw => None,
+        }
+    }
+
+    pub fn final_board(&self) -> &Board {
+        self.moves.last().map_or(&self.start, |m| &m.board)
+    }
+
+    pub fn total_time(&self, player: Player) -> Duration {
+        self.moves.iter().filter(|m| m.player == player).map(|m| m.elapsed).sum()
+    }
+}
+
+// Plays from `board` until the game is over, with strategy1 moving for player 1 and strategy2 for player 2.
+pub fn run_game<T: Strategy, U: Strategy>(board: Board, strategy1: T, strategy2: U) -> GameRecord {
+    play_game(board, StrategyAgent::new("player 1", strategy1), StrategyAgent::new("player 2", strategy2))
+}
+
+// Like run_game, for agents. An agent that fails to produce a legal move forfeits.
+pub fn play_game<T: Agent, U: Agent>(board: Board, mut agent1: T, mut agent2: U) -> GameRecord {
+    let mut record = GameRecord { start: board.clone(), moves: Vec::new(), result: GameResult::Draw };
+    agent1.game_started(&board, Player::Player1);
+    agent2.game_started(&board, Player::Player2);
+    let mut board = board;
     loop {
-        info!("\n{}", board);
-        let legal_boards = board.get_legal_boards();
-        board = strategy1.get_move(&board).unwrap();
-        assert!(legal_boards.contains(&board), "Strategy 1 produced illegal board");
-        
-        if board.get_legal_boards().is_empty() {
-            break;
+        match board.outcome() {
+            GameOutcome::Win(player) => {
+                record.result = GameResult::Win(player);
+                break;
+            }
+            GameOutcome::Draw => {
+                record.result = GameResult::Draw;
+                break;
+            }
+            GameOutcome::Ongoing => {}
         }
-        
         info!("\n{}", board);
-        let legal_boards = board.get_legal_boards();
-        board = strategy2.get_move(&board).unwrap();
-        assert!(legal_boards.contains(&board), "Strategy 2 produced illegal board");
-
-        if board.get_legal_boards().is_empty() {
-            break;
+        let player = board.who_turn();
+        let (agent, opponent): (&mut dyn Agent, &mut dyn Agent) = match player {
+            Player::Player1 => (&mut agent1, &mut agent2),
+            Player::Player2 => (&mut agent2, &mut agent1),
+        };
+        let start = Instant::now();
+        let decision = agent.choose_move(&board)
+            .and_then(|decision| board.play(decision.chosen).map(|next| (decision, next)).map_err(AgentError::Illegal));
+        let elapsed = start.elapsed();
+        match decision {
+            Ok((decision, next)) => {
+                opponent.opponent_moved(decision.chosen, &next);
+                record.moves.push(MoveRecord { player, board: next.clone(), elapsed, info: decision.info });
+                board = next;
+            },
+            Err(e) => {
+                info!("{} ({:?}) forfeits: {}", agent.name(), player, e);
+                record.result = GameResult::Forfeit(player);
+                break;
+            }
         }
     }
-    println!("Game ended!");
-    println!("{}", board);
+    agent1.game_ended(record.result);
+    agent2.game_ended(record.result);
+    info!("Game ended: {:?}\n{}", record.result, board);
+    record
 }
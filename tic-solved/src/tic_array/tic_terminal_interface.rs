@@ -1,39 +1,144 @@
-use std::io::{self, Write};
+use std::io::{self, BufRead, Stdout, StdinLock, Write};
 
-use crate::tic_array::{tic_simulator::Strategy, tic::{Board, print_on_board}};
+use crate::{
+    tic_array::{tic_simulator::{Strategy, GameResult}, tic::*, tic_move::Move},
+    game::{HumanPlayerInterface, ask_for_move},
+};
+
+// Plays through text: shows the board, numbers the legal moves on it and reads either a number or
+// a move like B2b2 per line. The end of the input counts as giving up.
+pub struct TerminalInterface<R, W> {
+    input: R,
+    output: W,
+}
+
+impl TerminalInterface<StdinLock<'static>, Stdout> {
+    pub fn new() -> Self {
+        TerminalInterface { input: io::stdin().lock(), output: io::stdout() }
+    }
+}
+
+impl Default for TerminalInterface<StdinLock<'static>, Stdout> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R: BufRead, W: Write> TerminalInterface<R, W> {
+    pub fn with_io(input: R, output: W) -> Self {
+        TerminalInterface { input, output }
+    }
+
+    pub fn into_output(self) -> W {
+        self.output
+    }
+
+    // A terminal that has gone away can't be told anything anyway.
+    fn say(&mut self, message: &str) {
+        let _ = writeln!(self.output, "{}", message).and_then(|_| self.output.flush());
+    }
+}
+
+fn player_name(player: Player) -> &'static str {
+    match player {
+        Player::Player1 => "Player 1 (X)",
+        Player::Player2 => "Player 2 (O)",
+    }
+}
+
+impl<R: BufRead, W: Write> HumanPlayerInterface for TerminalInterface<R, W> {
+    fn show_position(&mut self, board: &Board) {
+        self.say(&format!("=============================================\n{}", board));
+    }
+
+    fn request_move(&mut self, board: &Board, player: Player, legal_moves: &[Move]) -> Option<Move> {
+        self.say("=============================================");
+        let _ = write_on_board(&mut self.output, board, (0..legal_moves.len()).collect());
+        self.say(&format!("{} to move, enter a number or a move like B2b2:", player_name(player)));
+        loop {
+            let mut input = String::new();
+            match self.input.read_line(&mut input) {
+                Ok(0) | Err(_) => return None,
+                Ok(_) => {}
+            }
+            let input = input.trim();
+            if let Ok(m) = input.parse::<Move>() {
+                return Some(m);
+            }
+            match input.parse::<usize>() {
+                Ok(index) => match legal_moves.get(index) {
+                    Some(m) => return Some(*m),
+                    None => self.report_illegal_input("This move doesn't exist."),
+                },
+                Err(_) => self.report_illegal_input("You have to enter a number or a move like B2b2."),
+            }
+        }
+    }
+
+    fn report_illegal_input(&mut self, message: &str) {
+        self.say(message);
+    }
+
+    fn show_result(&mut self, result: GameResult, board: &Board) {
+        let message = match result {
+            GameResult::Win(player) => format!("{} wins", player_name(player)),
+            GameResult::Draw => "Draw".to_string(),
+            GameResult::Forfeit(player) => format!("{} forfeits", player_name(player)),
+        };
+        self.say(&format!("=============================================\n{}Game over: {}", board, message));
+    }
+}
 
 pub struct HumanTerminalStrategy {}
 
 impl Strategy for HumanTerminalStrategy {
     fn get_move(&self, board: &Board) -> Option<Board> {
-        let moves = board.get_legal_boards();
-        if moves.is_empty() {
+        let outcome = board.outcome();
+        if outcome.is_over() {
+            println!("The game is over: {}", outcome);
             return None;
         }
+        let m = ask_for_move(&mut TerminalInterface::new(), board)?;
+        board.play(m).ok()
+    }
+}
 
-        let move_index = (0..moves.len()).collect();
-        println!("=============================================");
-        print_on_board(&board, move_index);
-
-        let board = loop {
-            let mut input: String = String::new();
-            io::stdout().flush().unwrap();
-            io::stdin().read_line(&mut input).expect("Failed to read line");
-            let index = match input.trim().parse::<usize>() {
-                Ok(num) => num,
-                Err(_) => {
-                    println!("You have to enter a number.");
-                    continue
-                },
-            };
-            match moves.get(index) {
-                Some(board) => break board,
-                None => {
-                    println!("This move doesn't exist.");
-                    continue
-                }
-            }
-        };
-        Some(board.to_owned())
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{play_local, Seat};
+
+    fn request(input: &str, board: &Board) -> (Option<Move>, String) {
+        let mut terminal = TerminalInterface::with_io(input.as_bytes(), Vec::new());
+        let m = ask_for_move(&mut terminal, board);
+        (m, String::from_utf8(terminal.into_output()).unwrap())
+    }
+
+    #[test]
+    fn test_reads_numbers_and_notation() {
+        let board = Board::new();
+        assert_eq!(request("0\n", &board).0, Move::new((0, 0), (0, 0)));
+        assert_eq!(request("B2b2\n", &board).0, Move::new((1, 1), (1, 1)));
+        assert_eq!(request("", &board).0, None);
+    }
+
+    #[test]
+    fn test_shows_each_position_once() {
+        let mut terminal = TerminalInterface::with_io("B2b2\n".as_bytes(), Vec::new());
+        play_local(Board::new(), Seat::Human, Seat::Human, &mut terminal);
+        let output = String::from_utf8(terminal.into_output()).unwrap();
+        // Two requests, the second given up on, and the result.
+        assert_eq!(output.lines().filter(|line| line.starts_with("====")).count(), 3);
+    }
+
+    #[test]
+    fn test_reports_illegal_input() {
+        let board = Board::new().play("B2b2".parse().unwrap()).unwrap();
+        let (m, output) = request("hello\n81\nB2b2\nA1a1\n3\n", &board);
+        assert_eq!(m, Some(board.legal_moves()[3]));
+        assert!(output.contains("You have to enter a number or a move like B2b2."));
+        assert!(output.contains("This move doesn't exist."));
+        assert!(output.contains("B2b2 is illegal: the cell is taken"));
+        assert!(output.contains("A1a1 is illegal: the move has to be played in another sub board"));
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,183 @@
+use std::{fmt, fs, io, path::Path};
+
+use log::info;
+
+use crate::tic_array::{tic_simulator::*, tic::*, random::Rng};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PairingResult {
+    pub player1_wins: u32,
+    pub player2_wins: u32,
+    pub draws: u32,
+}
+
+impl PairingResult {
+    pub fn games(&self) -> u32 {
+        self.player1_wins + self.player2_wins + self.draws
+    }
+
+    // Share of the points won by player 1, counting a draw as half a win.
+    pub fn player1_win_rate(&self) -> f64 {
+        if self.games() == 0 {
+            return 0.0;
+        }
+        (self.player1_wins as f64 + self.draws as f64 / 2.0) / self.games() as f64
+    }
+}
+
+// Plays `moves` random moves from the empty board, stopping early if the game ends.
+pub fn random_opening(moves: usize, seed: u64) -> Board {
+    let mut rng = Rng::new(seed);
+    let mut board = Board::new();
+    for _ in 0..moves {
+        let mut next = board.get_legal_boards();
+        if next.is_empty() {
+            break;
+        }
+        board = next.swap_remove(rng.below(next.len()));
+    }
+    board
+}
+
+// The same `games` random openings for any seed, one per game.
+pub fn openings(games: usize, opening_moves: usize, seed: u64) -> Vec<Board> {
+    (0..games)
+        .map(|game| random_opening(opening_moves, seed.wrapping_add(game as u64)))
+        .collect()
+}
+
+// Plays one game from each opening, `on_game` sees each record as it finishes.
+pub fn play_pairing(player1: &dyn Strategy, player2: &dyn Strategy, openings: &[Board], mut on_game: impl FnMut(&GameRecord)) -> PairingResult {
+    let mut pairing = PairingResult::default();
+    for opening in openings {
+        let record = run_game(opening.clone(), player1, player2);
+        match record.winner() {
+            Some(Player::Player1) => pairing.player1_wins += 1,
+            Some(Player::Player2) => pairing.player2_wins += 1,
+            None => pairing.draws += 1,
+        }
+        on_game(&record);
+    }
+    pairing
+}
+
+// Round robin between strategies. Every entrant plays every entrant (itself included) from both
+// sides, `games_per_pairing` times, each game starting from a different random opening.
+pub struct Tournament {
+    entrants: Vec<(String, Box<dyn Strategy>)>,
+    games_per_pairing: usize,
+    opening_moves: usize,
+    seed: u64,
+}
+
+impl Tournament {
+    pub fn new(games_per_pairing: usize) -> Tournament {
+        Tournament { entrants: Vec::new(), games_per_pairing, opening_moves: 0, seed: 0 }
+    }
+
+    pub fn with_entrant(mut self, name: impl Into<String>, strategy: impl Strategy + 'static) -> Tournament {
+        self.entrants.push((name.into(), Box::new(strategy)));
+        self
+    }
+
+    // Number of random moves played before the strategies take over, so deterministic
+    // strategies don't replay the same game.
+    pub fn with_opening_moves(mut self, opening_moves: usize) -> Tournament {
+        self.opening_moves = opening_moves;
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Tournament {
+        self.seed = seed;
+        self
+    }
+
+    pub fn run(&self) -> TournamentResults {
+        // Every pairing plays the same openings, so the matrix compares like with like.
+        let openings = openings(self.games_per_pairing, self.opening_moves, self.seed);
+        let n = self.entrants.len();
+        let mut results = vec![vec![PairingResult::default(); n]; n];
+        for (i, (name1, strategy1)) in self.entrants.iter().enumerate() {
+            for (j, (name2, strategy2)) in self.entrants.iter().enumerate() {
+                results[i][j] = play_pairing(strategy1.as_ref(), strategy2.as_ref(), &openings, |_| {});
+                info!("{} vs {}: {:?}", name1, name2, results[i][j]);
+            }
+        }
+        TournamentResults {
+            names: self.entrants.iter().map(|(name, _)| name.clone()).collect(),
+            results,
+        }
+    }
+}
+
+// Row i, column j holds the games entrant i played as player 1 against entrant j as player 2.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TournamentResults {
+    pub names: Vec<String>,
+    pub results: Vec<Vec<PairingResult>>,
+}
+
+impl TournamentResults {
+    pub fn get(&self, player1: usize, player2: usize) -> PairingResult {
+        self.results[player1][player2]
+    }
+
+    // One line per pairing and side.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("player1,player2,player1_wins,player2_wins,draws,player1_win_rate\n");
+        for (i, row) in self.results.iter().enumerate() {
+            for (j, pairing) in row.iter().enumerate() {
+                csv.push_str(&format!(
+                    "{},{},{},{},{},{:.3}\n",
+                    self.names[i], self.names[j], pairing.player1_wins, pairing.player2_wins, pairing.draws, pairing.player1_win_rate()
+                ));
+            }
+        }
+        csv
+    }
+
+    pub fn write_csv(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.to_csv())
+    }
+}
+
+// Player 1's win rate matrix, rows are player 1 and columns player 2.
+impl fmt::Display for TournamentResults {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self.names.iter().map(|name| name.len()).max().unwrap_or(0).max(6);
+        write!(f, "{:width$}", "P1\\P2", width = width)?;
+        for name in &self.names {
+            write!(f, " {:>width$}", name, width = width)?;
+        }
+        writeln!(f)?;
+        for (name, row) in self.names.iter().zip(&self.results) {
+            write!(f, "{:width$}", name, width = width)?;
+            for pairing in row {
+                write!(f, " {:>width$.3}", pairing.player1_win_rate(), width = width)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_win_rate() {
+        let pairing = PairingResult { player1_wins: 2, player2_wins: 1, draws: 1 };
+        assert_eq!(pairing.games(), 4);
+        assert_eq!(pairing.player1_win_rate(), 0.625);
+        assert_eq!(PairingResult::default().player1_win_rate(), 0.0);
+    }
+
+    #[test]
+    fn test_openings_differ() {
+        let openings = openings(4, 3, 11);
+        assert_eq!(openings, super::openings(4, 3, 11));
+        assert!(openings.iter().any(|opening| *opening != openings[0]));
+        assert!(openings.iter().all(|opening| opening.who_turn() == Player::Player2));
+    }
+}
@@ -0,0 +1,49 @@
+// Zobrist keys shared by Board and BitBoard, so both engines hash the same position to the same value.
+// Cells are indexed in bitboard order: i*27 + k*9 + j*3 + l.
+
+pub(crate) const fn splitmix64(state: u64) -> (u64, u64) {
+    let state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    (state, z ^ (z >> 31))
+}
+
+const fn generate_keys<const N: usize>(seed: u64) -> [u64; N] {
+    let mut keys = [0; N];
+    let mut state = seed;
+    let mut i = 0;
+    while i < N {
+        let (next_state, key) = splitmix64(state);
+        keys[i] = key;
+        state = next_state;
+        i += 1;
+    }
+    keys
+}
+
+pub const PLAYER1_KEYS: [u64; 81] = generate_keys(0x3D71_C7AC_0000_0001);
+pub const PLAYER2_KEYS: [u64; 81] = generate_keys(0x3D71_C7AC_0000_0002);
+// Indexed by the sub board the last move sends the opponent to: 3*x + y.
+pub const LAST_MOVE_KEYS: [u64; 9] = generate_keys(0x3D71_C7AC_0000_0003);
+
+pub fn cell_index(i: usize, j: usize, k: usize, l: usize) -> usize {
+    i*27 + k*9 + j*3 + l
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn test_keys_unique() {
+        let keys: HashSet<u64> = PLAYER1_KEYS.iter()
+            .chain(PLAYER2_KEYS.iter())
+            .chain(LAST_MOVE_KEYS.iter())
+            .copied()
+            .collect();
+        assert_eq!(keys.len(), 81 + 81 + 9);
+    }
+}
@@ -0,0 +1,368 @@
+use std::{
+    sync::{Arc, atomic::{AtomicBool, Ordering}, mpsc::{self, Receiver}},
+    task::{Context, Poll, Waker},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    tic_array::{
+        tic::*,
+        bitmap_tic::*,
+        tic_move::Move,
+        tic_simulator::*,
+        async_simulator::{MoveFuture, ThreadedStrategy},
+        engine_protocol::MAX_DEPTH,
+    },
+    minmax::{
+        MoveKey, alphabeta,
+        iterative::{Deadline, iterative_deepening_until},
+        transposition::{TranspositionTable, DEFAULT_CAPACITY},
+    },
+    session::Session,
+};
+
+pub type EnginePlayer = ThreadedStrategy<Box<dyn Strategy + Send + Sync>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Cursor(Direction),
+    Play,
+    Undo,
+    Redo,
+    Quit,
+}
+
+// What the background search thinks of the position so far, in AlmostWinHeuristic's terms.
+// Positive favours player 2, like the heuristics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Evaluation {
+    pub score: i32,
+    pub depth: usize,
+    pub best: Move,
+}
+
+struct Analysis {
+    stop: Arc<AtomicBool>,
+    updates: Receiver<Evaluation>,
+}
+
+impl Drop for Analysis {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+fn analyse(board: &Board, budget: Duration) -> Analysis {
+    let root: BitBoard = board.clone().into();
+    let maximize = board.who_turn() == Player::Player2;
+    let stop = Arc::new(AtomicBool::new(false));
+    let deadline = Deadline::after(budget).with_stop(Arc::clone(&stop));
+    let (sender, updates) = mpsc::channel();
+    thread::spawn(move || {
+        let mut table = TranspositionTable::new(DEFAULT_CAPACITY);
+        let search = alphabeta::Search::<BitBoard, AlmostWinHeuristic, _, _, _>::new().with_table(&mut table);
+        iterative_deepening_until(&root, maximize, MAX_DEPTH, &deadline, search, |iteration| {
+            let best = Move::from_index(root.move_key(&iteration.best)).expect("Boards differ by one move");
+            // The app has moved on when nobody is listening.
+            let _ = sender.send(Evaluation { score: iteration.score, depth: iteration.depth, best });
+        });
+    });
+    Analysis { stop, updates }
+}
+
+// Cells as they are drawn: rows and columns 0..9 over the whole board.
+pub fn screen_position(m: Move) -> (usize, usize) {
+    let (i, j, k, l) = m.coordinates();
+    (i * 3 + k, j * 3 + l)
+}
+
+pub fn move_at((row, col): (usize, usize)) -> Option<Move> {
+    Move::from_coordinates((row / 3, col / 3, row % 3, col % 3))
+}
+
+fn seat(player: Player) -> usize {
+    match player {
+        Player::Player1 => 0,
+        Player::Player2 => 1,
+    }
+}
+
+// Everything the full screen interface shows, without the terminal. Engines think on their own
+// threads and are polled by `tick`, so keys are handled while they search.
+pub struct App {
+    session: Session,
+    names: [String; 2],
+    engines: [Option<EnginePlayer>; 2],
+    cursor: (usize, usize),
+    turn_started: Instant,
+    thinking: Option<MoveFuture<'static>>,
+    forfeit: Option<Player>,
+    eval_budget: Option<Duration>,
+    analysis: Option<Analysis>,
+    evaluation: Option<Evaluation>,
+    message: String,
+}
+
+impl App {
+    pub fn new(start: Board) -> App {
+        let mut app = App {
+            session: Session::new(start),
+            names: ["human".to_string(), "human".to_string()],
+            engines: [None, None],
+            cursor: (4, 4),
+            turn_started: Instant::now(),
+            thinking: None,
+            forfeit: None,
+            eval_budget: Some(Duration::from_secs(2)),
+            analysis: None,
+            evaluation: None,
+            message: String::new(),
+        };
+        app.position_changed();
+        app
+    }
+
+    pub fn with_engine(mut self, player: Player, name: impl Into<String>, strategy: Box<dyn Strategy + Send + Sync>) -> App {
+        self.names[seat(player)] = name.into();
+        self.engines[seat(player)] = Some(ThreadedStrategy::new(strategy));
+        self
+    }
+
+    // How long the evaluation bar's search runs per position, None to turn it off.
+    pub fn with_eval_budget(mut self, budget: Option<Duration>) -> App {
+        self.eval_budget = budget;
+        self
+    }
+
+    // Every line played, undone ones included.
+    pub fn session(&self) -> &Session {
+        &self.session
+    }
+
+    pub fn start(&self) -> &Board {
+        self.session.start()
+    }
+
+    pub fn board(&self) -> &Board {
+        self.session.board()
+    }
+
+    pub fn moves(&self) -> Vec<Move> {
+        self.session.line()
+    }
+
+    pub fn last_move(&self) -> Option<Move> {
+        self.session.node(self.session.current()).chosen
+    }
+
+    pub fn name(&self, player: Player) -> &str {
+        &self.names[seat(player)]
+    }
+
+    pub fn cursor(&self) -> (usize, usize) {
+        self.cursor
+    }
+
+    pub fn cursor_move(&self) -> Option<Move> {
+        move_at(self.cursor)
+    }
+
+    pub fn evaluation(&self) -> Option<Evaluation> {
+        self.evaluation
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn is_thinking(&self) -> bool {
+        self.thinking.is_some()
+    }
+
+    pub fn human_to_move(&self) -> bool {
+        self.result().is_none() && self.engines[seat(self.board().who_turn())].is_none()
+    }
+
+    // None while the game goes on.
+    pub fn result(&self) -> Option<GameResult> {
+        if let Some(player) = self.forfeit {
+            return Some(GameResult::Forfeit(player));
+        }
+        match self.board().outcome() {
+            GameOutcome::Win(player) => Some(GameResult::Win(player)),
+            GameOutcome::Draw => Some(GameResult::Draw),
+            GameOutcome::Ongoing => None,
+        }
+    }
+
+    // The line on the board. Leaving before the end forfeits for whoever is to move.
+    pub fn record(&self) -> GameRecord {
+        GameRecord {
+            start: self.start().clone(),
+            moves: self.session.line_records(),
+            result: self.result().unwrap_or(GameResult::Forfeit(self.board().who_turn())),
+        }
+    }
+
+    // Returns false once the app should close.
+    pub fn handle(&mut self, action: Action) -> bool {
+        self.message.clear();
+        match action {
+            Action::Cursor(direction) => {
+                if self.human_to_move() {
+                    self.move_cursor(direction);
+                }
+            }
+            Action::Play => self.play_cursor(),
+            Action::Undo => self.undo(),
+            Action::Redo => self.redo(),
+            Action::Quit => return false,
+        }
+        true
+    }
+
+    // Picks up what the engine and the evaluation found since the last call. Never blocks.
+    pub fn tick(&mut self) {
+        self.poll_engine();
+        self.poll_analysis();
+    }
+
+    fn play_cursor(&mut self) {
+        if !self.human_to_move() {
+            return;
+        }
+        let Some(m) = self.cursor_move() else { return };
+        match self.board().play(m) {
+            Ok(_) => self.push(m, MoveInfo::default()),
+            Err(e) => self.message = e.to_string(),
+        }
+    }
+
+    fn push(&mut self, m: Move, info: MoveInfo) {
+        let elapsed = self.turn_started.elapsed();
+        self.session.play_with(m, elapsed, info).expect("Only legal moves are pushed");
+        self.position_changed();
+    }
+
+    fn engine_to_move(&self) -> bool {
+        self.engines[seat(self.board().who_turn())].is_some()
+    }
+
+    // Takes back moves until a human is to move again, so undoing against an engine takes back
+    // its reply as well as your move. A search in progress is dropped.
+    fn undo(&mut self) {
+        if !self.session.undo() {
+            self.message = "Nothing to undo".to_string();
+            return;
+        }
+        self.forfeit = None;
+        let has_human = self.engines.iter().any(Option::is_none);
+        while has_human && self.engine_to_move() && self.session.undo() {}
+        self.position_changed();
+    }
+
+    // Replays undone moves the same way undo takes them back.
+    fn redo(&mut self) {
+        if self.forfeit.is_some() || !self.session.redo() {
+            self.message = "Nothing to redo".to_string();
+            return;
+        }
+        let has_human = self.engines.iter().any(Option::is_none);
+        while has_human && self.engine_to_move() && self.session.redo() {}
+        self.position_changed();
+    }
+
+    fn position_changed(&mut self) {
+        self.thinking = None;
+        self.analysis = None;
+        self.evaluation = None;
+        self.turn_started = Instant::now();
+        self.place_cursor();
+    }
+
+    // Keeps the cursor on a legal cell, as close as possible to where it was.
+    fn place_cursor(&mut self) {
+        let (row, col) = self.cursor;
+        let nearest = self.board().legal_moves()
+            .into_iter()
+            .map(screen_position)
+            .min_by_key(|&(r, c)| (r.abs_diff(row) + c.abs_diff(col), r, c));
+        if let Some(position) = nearest {
+            self.cursor = position;
+        }
+    }
+
+    // Jumps to the closest legal cell in that direction, preferring ones in line with the cursor.
+    fn move_cursor(&mut self, direction: Direction) {
+        let (row, col) = (self.cursor.0 as isize, self.cursor.1 as isize);
+        let (dr, dc) = match direction {
+            Direction::Up => (-1, 0),
+            Direction::Down => (1, 0),
+            Direction::Left => (0, -1),
+            Direction::Right => (0, 1),
+        };
+        let next = self.board().legal_moves()
+            .into_iter()
+            .map(screen_position)
+            .filter_map(|(r, c)| {
+                let (r_offset, c_offset) = (r as isize - row, c as isize - col);
+                let (along, across) = if dr != 0 {
+                    (r_offset * dr, c_offset.abs())
+                } else {
+                    (c_offset * dc, r_offset.abs())
+                };
+                (along > 0).then_some(((across, along), (r, c)))
+            })
+            .min();
+        if let Some((_, position)) = next {
+            self.cursor = position;
+        }
+    }
+
+    fn poll_engine(&mut self) {
+        if self.result().is_some() {
+            return;
+        }
+        let board = self.board().clone();
+        let player = board.who_turn();
+        let Some(engine) = &self.engines[seat(player)] else { return };
+        let future = self.thinking.get_or_insert_with(|| engine.spawn_move(&board));
+        let Poll::Ready(answer) = future.as_mut().poll(&mut Context::from_waker(Waker::noop())) else { return };
+        self.thinking = None;
+        let chosen = answer.and_then(|(next, info)| board.move_to(&next).map(|m| (m, info)));
+        match chosen {
+            Some((m, info)) => {
+                self.message = format!("{} played {}", self.name(player), m);
+                self.push(m, info);
+            }
+            None => {
+                self.message = format!("{} found no move and forfeits", self.name(player));
+                self.forfeit = Some(player);
+            }
+        }
+    }
+
+    fn poll_analysis(&mut self) {
+        match &self.analysis {
+            Some(analysis) => {
+                while let Ok(evaluation) = analysis.updates.try_recv() {
+                    self.evaluation = Some(evaluation);
+                }
+            }
+            None => {
+                if let (Some(budget), None) = (self.eval_budget, self.result()) {
+                    self.analysis = Some(analyse(self.board(), budget));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,106 @@
+pub mod app;
+pub mod render;
+
+use std::{io::{self, Write}, time::Duration};
+
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    execute, queue,
+    style::{Attribute, Print, SetAttribute, SetBackgroundColor, SetForegroundColor},
+    terminal::{self, ClearType},
+};
+
+use crate::tui::{app::*, render::*};
+
+// How often engines and the evaluation are polled while no key is pressed.
+const FRAME: Duration = Duration::from_millis(50);
+
+pub fn action(key: KeyEvent) -> Option<Action> {
+    if key.kind != KeyEventKind::Press {
+        return None;
+    }
+    match key.code {
+        KeyCode::Up | KeyCode::Char('k') => Some(Action::Cursor(Direction::Up)),
+        KeyCode::Down | KeyCode::Char('j') => Some(Action::Cursor(Direction::Down)),
+        KeyCode::Left | KeyCode::Char('h') => Some(Action::Cursor(Direction::Left)),
+        KeyCode::Right | KeyCode::Char('l') => Some(Action::Cursor(Direction::Right)),
+        KeyCode::Enter | KeyCode::Char(' ') => Some(Action::Play),
+        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => Some(Action::Quit),
+        KeyCode::Char('u') | KeyCode::Backspace => Some(Action::Undo),
+        KeyCode::Char('r') => Some(Action::Redo),
+        KeyCode::Char('q') | KeyCode::Esc => Some(Action::Quit),
+        _ => None,
+    }
+}
+
+// Raw mode on the alternate screen for as long as it lives, so the terminal is given back
+// however the app ends.
+struct FullScreen;
+
+impl FullScreen {
+    fn enter() -> io::Result<FullScreen> {
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), terminal::EnterAlternateScreen, cursor::Hide)?;
+        Ok(FullScreen)
+    }
+}
+
+impl Drop for FullScreen {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+pub fn write_screen<W: Write>(out: &mut W, screen: &Screen) -> io::Result<()> {
+    let mut current = None;
+    for y in 0..screen.height() {
+        queue!(out, cursor::MoveTo(0, y as u16))?;
+        for x in 0..screen.width() {
+            let (c, style) = screen.get(x, y);
+            if current != Some(style) {
+                queue!(out, SetAttribute(Attribute::Reset), SetForegroundColor(style.fg), SetBackgroundColor(style.bg))?;
+                if style.bold {
+                    queue!(out, SetAttribute(Attribute::Bold))?;
+                }
+                current = Some(style);
+            }
+            queue!(out, Print(c))?;
+        }
+    }
+    queue!(out, SetAttribute(Attribute::Reset))?;
+    out.flush()
+}
+
+// Runs the app full screen until it's quit and hands it back, so the caller can keep the game.
+pub fn run(mut app: App) -> io::Result<App> {
+    let _full_screen = FullScreen::enter()?;
+    let mut out = io::stdout();
+    let mut shown: Option<Screen> = None;
+    loop {
+        app.tick();
+        let screen = draw(&app);
+        if shown.as_ref() != Some(&screen) {
+            write_screen(&mut out, &screen)?;
+            shown = Some(screen);
+        }
+        if !event::poll(FRAME)? {
+            continue;
+        }
+        match event::read()? {
+            Event::Key(key) => {
+                if let Some(action) = action(key) {
+                    if !app.handle(action) {
+                        return Ok(app);
+                    }
+                }
+            }
+            Event::Resize(..) => {
+                queue!(out, terminal::Clear(ClearType::All))?;
+                shown = None;
+            }
+            _ => {}
+        }
+    }
+}
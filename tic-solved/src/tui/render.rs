@@ -0,0 +1,261 @@
+use crossterm::style::Color;
+
+use crate::{
+    tic_array::{tic::*, tic_simulator::GameResult},
+    tui::app::{App, move_at},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Style {
+    pub fg: Color,
+    pub bg: Color,
+    pub bold: bool,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Style { fg: Color::Reset, bg: Color::Reset, bold: false }
+    }
+}
+
+impl Style {
+    fn fg(fg: Color) -> Style {
+        Style { fg, ..Style::default() }
+    }
+}
+
+// A frame in memory, so drawing doesn't need a terminal and can be compared with the last one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Screen {
+    width: usize,
+    height: usize,
+    cells: Vec<(char, Style)>,
+}
+
+impl Screen {
+    pub fn new(width: usize, height: usize) -> Screen {
+        Screen { width, height, cells: vec![(' ', Style::default()); width * height] }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    // Whatever falls outside the screen is cut off.
+    pub fn put(&mut self, x: usize, y: usize, text: &str, style: Style) {
+        if y >= self.height {
+            return;
+        }
+        for (offset, c) in text.chars().enumerate() {
+            if x + offset < self.width {
+                self.cells[y * self.width + x + offset] = (c, style);
+            }
+        }
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> (char, Style) {
+        self.cells[y * self.width + x]
+    }
+
+    pub fn line(&self, y: usize) -> String {
+        let line: String = self.cells[y * self.width..(y + 1) * self.width].iter().map(|(c, _)| c).collect();
+        line.trim_end().to_string()
+    }
+}
+
+pub const WIDTH: usize = 64;
+pub const HEIGHT: usize = 22;
+
+const BOARD_X: usize = 3;
+const BOARD_Y: usize = 2;
+const BOARD_WIDTH: usize = 29;
+const HISTORY_X: usize = 36;
+const BAR_WIDTH: usize = 25;
+// Scores at least this far from zero fill the evaluation bar. A sub board is worth 3 and two in
+// a row 1, so every point shows.
+const EVAL_RANGE: i32 = 10;
+
+// Where the cell in row `row` and column `col` of the 9x9 grid is drawn. Each cell is three
+// characters wide and sub boards are one character apart.
+pub fn cell_x(col: usize) -> usize {
+    BOARD_X + col * 3 + col / 3
+}
+
+pub fn cell_y(row: usize) -> usize {
+    BOARD_Y + row + row / 3
+}
+
+fn color(player: Player) -> Color {
+    match player {
+        Player::Player1 => Color::Red,
+        Player::Player2 => Color::Cyan,
+    }
+}
+
+fn mark(player: Player) -> char {
+    match player {
+        Player::Player1 => 'X',
+        Player::Player2 => 'O',
+    }
+}
+
+fn big_mark(player: Player) -> [&'static str; 3] {
+    match player {
+        Player::Player1 => ["  ╲   ╱  ", "    ╳    ", "  ╱   ╲  "],
+        Player::Player2 => ["  ╭───╮  ", "  │   │  ", "  ╰───╯  "],
+    }
+}
+
+fn player_name(player: Player) -> String {
+    match player {
+        Player::Player1 => "player 1 (X)".to_string(),
+        Player::Player2 => "player 2 (O)".to_string(),
+    }
+}
+
+fn describe(result: GameResult) -> String {
+    match result {
+        GameResult::Win(player) => format!("{} wins", player_name(player)),
+        GameResult::Draw => "draw".to_string(),
+        GameResult::Forfeit(player) => format!("{} forfeits", player_name(player)),
+    }
+}
+
+pub fn draw(app: &App) -> Screen {
+    let mut screen = Screen::new(WIDTH, HEIGHT);
+    let title = format!("tic-solved   X: {}   O: {}", app.name(Player::Player1), app.name(Player::Player2));
+    screen.put(1, 0, &title, Style { bold: true, ..Style::default() });
+    draw_board(&mut screen, app);
+    draw_evaluation(&mut screen, app);
+    draw_history(&mut screen, app);
+
+    let board = app.board();
+    let player = board.who_turn();
+    let status = match app.result() {
+        Some(result) => format!("Game over: {}", describe(result)),
+        None if app.is_thinking() => format!("{} ({}) is thinking...", app.name(player), mark(player)),
+        None if app.human_to_move() => match app.cursor_move() {
+            Some(m) => format!("{} ({}) to move: {}", app.name(player), mark(player), m),
+            None => format!("{} ({}) to move", app.name(player), mark(player)),
+        },
+        None => format!("{} ({}) to move", app.name(player), mark(player)),
+    };
+    screen.put(1, BOARD_Y + 15, &status, Style::default());
+    screen.put(1, BOARD_Y + 16, app.message(), Style::fg(Color::Yellow));
+    screen.put(1, HEIGHT - 1, "arrows/hjkl move  enter/space play  u undo  r redo  q quit", Style::fg(Color::DarkGrey));
+    screen
+}
+
+fn draw_board(screen: &mut Screen, app: &App) {
+    let board = app.board();
+    let (cells, _) = board.to_arr();
+    let ongoing = app.result().is_none();
+    let legal_moves = board.legal_moves();
+    let last = app.last_move();
+
+    for j in 0..3 {
+        screen.put(cell_x(j * 3) + 4, BOARD_Y - 1, &((b'A' + j as u8) as char).to_string(), Style::fg(Color::DarkGrey));
+    }
+    for i in 0..3 {
+        screen.put(BOARD_X - 2, cell_y(i * 3) + 1, &(i + 1).to_string(), Style::fg(Color::DarkGrey));
+    }
+    for y in BOARD_Y..BOARD_Y + 11 {
+        for x in [cell_x(3) - 1, cell_x(6) - 1] {
+            screen.put(x, y, "│", Style::fg(Color::DarkGrey));
+        }
+    }
+    for y in [cell_y(3) - 1, cell_y(6) - 1] {
+        for x in BOARD_X..BOARD_X + BOARD_WIDTH {
+            let c = if x == cell_x(3) - 1 || x == cell_x(6) - 1 { "┼" } else { "─" };
+            screen.put(x, y, c, Style::fg(Color::DarkGrey));
+        }
+    }
+
+    for (i, boards) in cells.iter().enumerate() {
+        for (j, sub_board) in boards.iter().enumerate() {
+            if let Some(winner) = board.single_board_winner(i, j) {
+                for (k, line) in big_mark(winner).iter().enumerate() {
+                    screen.put(cell_x(j * 3), cell_y(i * 3 + k), line, Style { fg: color(winner), bg: Color::Reset, bold: true });
+                }
+                continue;
+            }
+            let full = sub_board.iter().flatten().all(|&tile| tile != 0);
+            let target = ongoing && legal_moves.iter().any(|m| m.board() == (i, j));
+            for (k, tiles) in sub_board.iter().enumerate() {
+                for (l, tile) in tiles.iter().enumerate() {
+                    let (row, col) = (i * 3 + k, j * 3 + l);
+                    let (text, mut style) = match tile {
+                        1 => (" X ", Style::fg(color(Player::Player1))),
+                        2 => (" O ", Style::fg(color(Player::Player2))),
+                        _ => (" · ", Style::fg(Color::DarkGrey)),
+                    };
+                    if full {
+                        style.fg = Color::DarkGrey;
+                    }
+                    if target {
+                        style.bg = Color::DarkBlue;
+                    }
+                    if last.is_some() && last == move_at((row, col)) {
+                        style.bg = Color::DarkGrey;
+                        style.bold = true;
+                    }
+                    if app.human_to_move() && app.cursor() == (row, col) {
+                        style = Style { fg: Color::Black, bg: Color::Yellow, bold: true };
+                    }
+                    screen.put(cell_x(col), cell_y(row), text, style);
+                }
+            }
+        }
+    }
+}
+
+fn draw_evaluation(screen: &mut Screen, app: &App) {
+    let y = BOARD_Y + 12;
+    // Share of the bar that is player 1's, out of EVAL_RANGE * 2.
+    let (share, label) = match (app.result(), app.evaluation()) {
+        (Some(result), _) => {
+            let share = match result {
+                GameResult::Draw => EVAL_RANGE,
+                GameResult::Win(Player::Player1) | GameResult::Forfeit(Player::Player2) => EVAL_RANGE * 2,
+                _ => 0,
+            };
+            (share, describe(result))
+        }
+        (None, Some(evaluation)) => {
+            let leader = match evaluation.score {
+                0 => "even".to_string(),
+                score if score > 0 => format!("O +{}", score),
+                score => format!("X +{}", -score),
+            };
+            (EVAL_RANGE - evaluation.score, format!("eval {}  depth {}  best {}", leader, evaluation.depth, evaluation.best))
+        }
+        (None, None) => (EVAL_RANGE, "eval -".to_string()),
+    };
+    let filled = (share.clamp(0, EVAL_RANGE * 2) as usize * BAR_WIDTH + EVAL_RANGE as usize) / (EVAL_RANGE as usize * 2);
+    screen.put(BOARD_X - 2, y, "X", Style::fg(color(Player::Player1)));
+    screen.put(BOARD_X, y, &"█".repeat(filled), Style::fg(color(Player::Player1)));
+    screen.put(BOARD_X + filled, y, &"█".repeat(BAR_WIDTH - filled), Style::fg(color(Player::Player2)));
+    screen.put(BOARD_X + BAR_WIDTH + 1, y, "O", Style::fg(color(Player::Player2)));
+    screen.put(BOARD_X, y + 1, &label, Style::default());
+}
+
+fn draw_history(screen: &mut Screen, app: &App) {
+    screen.put(HISTORY_X, BOARD_Y - 1, "Moves", Style { bold: true, ..Style::default() });
+    let mut moves: Vec<String> = app.moves().iter().map(|m| m.to_string()).collect();
+    // Keeps X's moves in the left column when the game started with O to move.
+    if app.start().who_turn() == Player::Player2 {
+        moves.insert(0, "...".to_string());
+    }
+    let lines: Vec<String> = moves.chunks(2)
+        .enumerate()
+        .map(|(n, pair)| format!("{:>3}. {:<5} {}", n + 1, pair[0], pair.get(1).map_or("", |m| m.as_str())))
+        .collect();
+    let rows = HEIGHT - 2 - BOARD_Y;
+    for (y, line) in lines.iter().skip(lines.len().saturating_sub(rows)).enumerate() {
+        screen.put(HISTORY_X, BOARD_Y + y, line, Style::default());
+    }
+}
@@ -0,0 +1,124 @@
+use tic_solved::{
+    tic_array::{
+        tic::*,
+        tic_move::*,
+        naive_strategy::MinimaxStrategy,
+        alphabeta_strategy::AlphaBetaStrategy,
+        alphabeta_strategy_bitboard::AlphaBetaBitBoardStrategy,
+        mcts_strategy::MctsStrategy,
+        tic_simulator::*,
+    },
+    minmax::alphabeta,
+};
+
+use rstest::rstest;
+use std::fs;
+use std::path::PathBuf;
+
+fn read_board(file_path: PathBuf) -> Board {
+    let board_str = fs::read_to_string(file_path)
+        .expect("Failed to read board file");
+
+    let board_result: BoardResult = board_str.as_str().into();
+    board_result.into_inner().unwrap()
+}
+
+fn check_adapter<S: Strategy>(board: &Board, strategy: S) -> Option<Decision> {
+    let expected = strategy.get_move(board);
+    let mut agent = StrategyAgent::new("adapted", strategy);
+    match agent.choose_move(board) {
+        Ok(decision) => {
+            assert_eq!(board.play(decision.chosen).ok(), expected);
+            Some(decision)
+        },
+        Err(e) => {
+            assert_eq!(e, AgentError::NoMoves);
+            assert_eq!(expected, None);
+            None
+        }
+    }
+}
+
+#[rstest]
+fn test_adapters(#[files("positions/*.txt")] file_path: PathBuf) {
+    let board = read_board(file_path);
+    check_adapter(&board, MinimaxStrategy::new(2));
+    check_adapter(&board, AlphaBetaBitBoardStrategy::new(3));
+    check_adapter(&board, MctsStrategy::new(100));
+    if let Some(decision) = check_adapter(&board, AlphaBetaStrategy::new(3)) {
+        let (_, score) = match board.who_turn() {
+            Player::Player1 => alphabeta::min_score::<Board, AlmostWinHeuristic>(&board, 3, None, None),
+            Player::Player2 => alphabeta::max_score::<Board, AlmostWinHeuristic>(&board, 3, None, None),
+        };
+        assert_eq!(decision.info.evaluation, Some(score));
+        assert_eq!(decision.info.depth, Some(3));
+    }
+}
+
+#[test]
+fn test_mcts_principal_variation() {
+    let board = Board::new();
+    let (next, info) = MctsStrategy::new(200).get_move_with_info(&board).unwrap();
+    let mut position = board.clone();
+    for &m in &info.principal_variation {
+        position = position.play(m).unwrap();
+    }
+    assert_eq!(board.play(info.principal_variation[0]).unwrap(), next);
+    assert_eq!(info.depth, Some(info.principal_variation.len()));
+}
+
+// Plays the first legal move and remembers everything it was told.
+#[derive(Default)]
+struct Recorder {
+    side: Option<Player>,
+    opponent_moves: Vec<Move>,
+    own_moves: usize,
+    result: Option<GameResult>,
+    play_illegal: bool,
+}
+
+impl Agent for Recorder {
+    fn game_started(&mut self, _start: &Board, side: Player) {
+        self.side = Some(side);
+    }
+
+    fn choose_move(&mut self, board: &Board) -> Result<Decision, AgentError> {
+        self.own_moves += 1;
+        let chosen = if self.play_illegal {
+            (0..81).filter_map(Move::from_index).find(|m| board.play(*m).is_err()).unwrap()
+        } else {
+            *board.legal_moves().first().ok_or(AgentError::NoMoves)?
+        };
+        Ok(Decision { chosen, info: MoveInfo::default() })
+    }
+
+    fn opponent_moved(&mut self, opponent_move: Move, _after: &Board) {
+        self.opponent_moves.push(opponent_move);
+    }
+
+    fn game_ended(&mut self, result: GameResult) {
+        self.result = Some(result);
+    }
+}
+
+#[test]
+fn test_notifications() {
+    let mut recorder = Recorder::default();
+    let mut opponent = StrategyAgent::new("alphabeta", AlphaBetaBitBoardStrategy::new(2));
+    let record = play_game(Board::new(), &mut opponent, &mut recorder);
+    assert_eq!(recorder.side, Some(Player::Player2));
+    assert_eq!(recorder.result, Some(record.result));
+    let opponent_moves: Vec<&MoveRecord> = record.moves.iter().filter(|m| m.player == Player::Player1).collect();
+    assert_eq!(recorder.opponent_moves.len(), opponent_moves.len());
+    assert_eq!(recorder.own_moves, record.moves.len() - opponent_moves.len());
+    assert!(record.moves.iter().filter(|m| m.player == Player::Player1).all(|m| m.info.evaluation.is_some()));
+}
+
+#[test]
+fn test_illegal_move_forfeits() {
+    let start = Board::new().play("B2b2".parse().unwrap()).unwrap();
+    let recorder = Recorder { play_illegal: true, ..Recorder::default() };
+    let record = play_game(start, StrategyAgent::new("alphabeta", AlphaBetaBitBoardStrategy::new(1)), recorder);
+    assert_eq!(record.result, GameResult::Forfeit(Player::Player2));
+    assert!(record.moves.is_empty());
+}
@@ -1,19 +1,25 @@
 use tic_solved::{
     tic_array::{
         tic::*,
+        bitmap_tic::*,
         naive_strategy::MinimaxStrategy,
         alphabeta_strategy::AlphaBetaStrategy,
         tic_simulator::*,
     },
     minmax::{
         naive,
-        alphabeta
+        alphabeta::{self, Search},
+        iterative::Deadline,
+        ordering::{HistoryHeuristic, KillerMoves},
+        transposition::TranspositionTable,
+        Heuristic,
     },
 };
 
 use rstest::rstest;
 use std::fs;
 use std::path::PathBuf;
+use std::time::Duration;
 
 #[rstest]
 fn test_position_min(#[files("positions/*.txt")] file_path: PathBuf) {
@@ -53,4 +59,94 @@ fn test_strategy(#[files("positions/*.txt")] file_path: PathBuf) {
     let board1 = strategy1.get_move(&board).unwrap();
     let board2 = strategy2.get_move(&board).unwrap();
     assert_eq!(board1, board2);
-}
\ No newline at end of file
+}
+
+#[rstest]
+fn test_position_max_probed(#[files("positions/*.txt")] file_path: PathBuf) {
+    let board_str = fs::read_to_string(file_path)
+        .expect("Failed to read board file");
+
+    let board_result: BoardResult = board_str.as_str().into();
+    let board = board_result.into_inner().unwrap();
+    let no_probe = |_: &Board| None;
+    let (board1, score1) = alphabeta::max_score::<Board, AlmostWinHeuristic>(&board, 2, None, None);
+    let (board2, score2) = Search::<Board, AlmostWinHeuristic, _, _, _>::new().with_probe(no_probe).max_score(&board, 2, None, None).unwrap();
+    assert_eq!(board1, board2);
+    assert_eq!(score1, score2);
+
+    let target = board.get_legal_boards().last().unwrap().clone();
+    let probe = |b: &Board| if *b == target { Some(5000) } else { None };
+    let (board3, score3) = Search::<Board, AlmostWinHeuristic, _, _, _>::new().with_probe(probe).max_score(&board, 2, None, None).unwrap();
+    assert_eq!(board3, target);
+    assert_eq!(score3, 5000);
+}
+
+#[rstest]
+fn test_search_result(#[files("positions/*.txt")] file_path: PathBuf) {
+    let board_str = fs::read_to_string(file_path)
+        .expect("Failed to read board file");
+
+    let board_result: BoardResult = board_str.as_str().into();
+    let board = board_result.into_inner().unwrap();
+    let result = alphabeta::search::<Board, AlmostWinHeuristic>(&board, 3, false);
+    let (best, score) = alphabeta::min_score::<Board, AlmostWinHeuristic>(&board, 3, None, None);
+    assert_eq!(result.score, score);
+    assert_eq!(result.best_move(), Some(&best));
+    assert_eq!(result.depth, 3);
+    assert_eq!(result.root_scores.len(), board.get_legal_boards().len());
+    assert!(result.root_scores.iter().all(|(_, s)| *s >= score));
+    assert!(result.nodes > result.root_scores.len() as u64);
+
+    let mut position = board.clone();
+    for state in &result.principal_variation {
+        assert!(position.get_legal_boards().contains(state));
+        position = state.clone();
+    }
+    if result.principal_variation.len() == 3 {
+        assert_eq!(AlmostWinHeuristic::score(&position), score);
+    }
+    print_search_result(&board, &result);
+}
+
+#[rstest]
+fn test_search_result_bitboard(#[files("positions/*.txt")] file_path: PathBuf) {
+    let board_str = fs::read_to_string(file_path)
+        .expect("Failed to read board file");
+
+    let board_result: BoardResult = board_str.as_str().into();
+    let board = board_result.into_inner().unwrap();
+    let bitmap: BitBoard = board.clone().into();
+    let result = alphabeta::search::<BitBoard, WinHeuristic>(&bitmap, 4, true);
+    let (_, score) = alphabeta::max_score::<BitBoard, WinHeuristic>(&bitmap, 4, None, None);
+    assert_eq!(result.score, score);
+    print_search_result(&board, &result);
+}
+
+#[rstest]
+fn test_search_options_agree(#[files("positions/*.txt")] file_path: PathBuf) {
+    let board_str = fs::read_to_string(file_path)
+        .expect("Failed to read board file");
+
+    let board_result: BoardResult = board_str.as_str().into();
+    let board = board_result.into_inner().unwrap();
+    let bitmap: BitBoard = board.into();
+    let (_, expected) = alphabeta::max_score::<BitBoard, WinHeuristic>(&bitmap, 4, None, None);
+
+    let mut table = TranspositionTable::new(1 << 16);
+    let mut search = Search::<BitBoard, WinHeuristic, _, _, _>::new()
+        .with_ordering(KillerMoves::new(HistoryHeuristic::new()))
+        .with_table(&mut table)
+        .with_principal_variation();
+    let (score, line) = search.score(&bitmap, 4, true, None, None).unwrap();
+    assert_eq!(score, expected);
+    assert!(line.len() <= 4);
+    assert!(search.nodes() > 0);
+}
+
+#[test]
+fn test_search_gives_up_at_deadline() {
+    let deadline = Deadline::after(Duration::ZERO);
+    let mut search = Search::<BitBoard, WinHeuristic, _, _, _>::new().with_deadline(&deadline);
+    assert_eq!(search.score(&BitBoard::new(), 8, true, None, None), None);
+    assert!(search.nodes() <= 256);
+}
@@ -0,0 +1,182 @@
+use tic_solved::tic_array::{
+    tic::*,
+    tic_simulator::*,
+    async_simulator::*,
+    alphabeta_strategy::AlphaBetaStrategy,
+    alphabeta_strategy_bitboard::AlphaBetaBitBoardStrategy,
+    mcts_strategy::MctsStrategy,
+    game_record::SavedGame,
+};
+
+use std::{
+    sync::{Arc, mpsc, atomic::{AtomicBool, Ordering}},
+    thread,
+    time::{Duration, Instant},
+};
+
+// Thinks for a long time before playing the first legal move.
+struct SlowStrategy(Duration);
+
+impl Strategy for SlowStrategy {
+    fn get_move(&self, game_state: &Board) -> Option<Board> {
+        thread::sleep(self.0);
+        game_state.play(*game_state.legal_moves().first()?).ok()
+    }
+}
+
+// Thinks until it is told to stop, then says so.
+struct StoppableStrategy(mpsc::Sender<()>);
+
+impl Strategy for StoppableStrategy {
+    fn get_move(&self, _game_state: &Board) -> Option<Board> {
+        None
+    }
+
+    fn get_move_until(&self, _game_state: &Board, stop: &Arc<AtomicBool>) -> Option<(Board, MoveInfo)> {
+        while !stop.load(Ordering::Relaxed) {
+            thread::sleep(Duration::from_millis(1));
+        }
+        self.0.send(()).unwrap();
+        None
+    }
+}
+
+// Panics instead of moving.
+struct BrokenStrategy;
+
+impl Strategy for BrokenStrategy {
+    fn get_move(&self, _game_state: &Board) -> Option<Board> {
+        panic!("broken strategy")
+    }
+}
+
+fn final_positions(record: &GameRecord) -> Vec<Board> {
+    record.moves.iter().map(|m| m.board.clone()).collect()
+}
+
+#[test]
+fn test_threaded_game_matches_blocking_game() {
+    let blocking = run_game(Board::new(), AlphaBetaBitBoardStrategy::new(1), AlphaBetaBitBoardStrategy::new(2));
+    let threaded = block_on(run_game_async(
+        Board::new(),
+        ThreadedStrategy::new(AlphaBetaBitBoardStrategy::new(1)),
+        ThreadedStrategy::new(AlphaBetaBitBoardStrategy::new(2)),
+        &CancelToken::new(),
+    )).unwrap();
+    assert_eq!(threaded.result, blocking.result);
+    assert_eq!(final_positions(&threaded), final_positions(&blocking));
+}
+
+#[test]
+fn test_cancel_while_thinking() {
+    let cancel = CancelToken::new();
+    let canceller = cancel.clone();
+    thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        canceller.cancel();
+    });
+    let start = Instant::now();
+    let result = block_on(run_game_async(
+        Board::new(),
+        ThreadedStrategy::new(SlowStrategy(Duration::from_secs(30))),
+        ThreadedStrategy::new(SlowStrategy(Duration::from_secs(30))),
+        &cancel,
+    ));
+    assert!(start.elapsed() < Duration::from_secs(10));
+    assert_eq!(result, Err(AbandonedGame { start: Board::new(), moves: Vec::new() }));
+}
+
+#[test]
+fn test_cancelling_stops_the_search() {
+    let (stopped, searches_stopped) = mpsc::channel();
+    let cancel = CancelToken::new();
+    let canceller = cancel.clone();
+    thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        canceller.cancel();
+    });
+    let (_sender, player) = channel_player();
+    let result = block_on(run_game_async(Board::new(), ThreadedStrategy::new(StoppableStrategy(stopped)), player, &cancel));
+    assert!(result.is_err());
+    searches_stopped.recv_timeout(Duration::from_secs(10)).unwrap();
+}
+
+#[test]
+fn test_dropping_the_future_stops_the_search() {
+    let (stopped, searches_stopped) = mpsc::channel();
+    let engine = ThreadedStrategy::new(StoppableStrategy(stopped));
+    drop(engine.spawn_move(&Board::new()));
+    searches_stopped.recv_timeout(Duration::from_secs(10)).unwrap();
+}
+
+#[test]
+fn test_engines_give_up_when_stopped() {
+    let board = Board::new();
+    let stop = Arc::new(AtomicBool::new(true));
+    let start = Instant::now();
+    assert!(AlphaBetaStrategy::new(40).get_move_until(&board, &stop).is_none());
+    assert!(AlphaBetaBitBoardStrategy::new(40).get_move_until(&board, &stop).is_none());
+    // With a time budget the first iteration still gives a move.
+    let hour = Duration::from_secs(3600);
+    let (next, _) = AlphaBetaBitBoardStrategy::new(40).with_time_budget(hour).get_move_until(&board, &stop).unwrap();
+    assert!(board.get_legal_boards().contains(&next));
+    let (next, _) = MctsStrategy::new(1 << 30).get_move_until(&board, &stop).unwrap();
+    assert!(board.get_legal_boards().contains(&next));
+    assert!(start.elapsed() < Duration::from_secs(10));
+}
+
+#[test]
+fn test_cancelled_before_start() {
+    let cancel = CancelToken::new();
+    cancel.cancel();
+    let (_sender, player) = channel_player();
+    let result = block_on(run_game_async(Board::new(), player, ThreadedStrategy::new(AlphaBetaBitBoardStrategy::new(1)), &cancel));
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_broken_strategy_forfeits() {
+    let record = block_on(run_game_async(
+        Board::new(),
+        ThreadedStrategy::new(BrokenStrategy),
+        ThreadedStrategy::new(AlphaBetaBitBoardStrategy::new(1)),
+        &CancelToken::new(),
+    )).unwrap();
+    assert_eq!(record.result, GameResult::Forfeit(Player::Player1));
+}
+
+// Moves sent from another thread replay an engine game, skipping the illegal ones.
+#[test]
+fn test_channel_players() {
+    let engines = run_game(Board::new(), AlphaBetaBitBoardStrategy::new(1), AlphaBetaBitBoardStrategy::new(2));
+    let moves = SavedGame::new(engines.clone()).moves();
+    let (sender1, player1) = channel_player();
+    let (sender2, player2) = channel_player();
+    let feeder = thread::spawn(move || {
+        for (i, m) in moves.into_iter().enumerate() {
+            let sender = if i % 2 == 0 { &sender1 } else { &sender2 };
+            // The copy is read on the player's next turn, when the cell is taken.
+            sender.send(m);
+            sender.send(m);
+            thread::sleep(Duration::from_millis(1));
+        }
+    });
+    let record = block_on(run_game_async(Board::new(), player1, player2, &CancelToken::new())).unwrap();
+    feeder.join().unwrap();
+    assert_eq!(record.result, engines.result);
+    assert_eq!(final_positions(&record), final_positions(&engines));
+}
+
+#[test]
+fn test_dropped_sender_gives_up() {
+    let (sender, player) = channel_player();
+    drop(sender);
+    let record = block_on(run_game_async(
+        Board::new(),
+        ThreadedStrategy::new(AlphaBetaBitBoardStrategy::new(1)),
+        player,
+        &CancelToken::new(),
+    )).unwrap();
+    assert_eq!(record.result, GameResult::Forfeit(Player::Player2));
+    assert_eq!(record.moves.len(), 1);
+}
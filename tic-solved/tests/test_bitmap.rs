@@ -26,7 +26,6 @@ fn test_heuristic(#[files("positions/*.txt")] file_path: PathBuf) {
         let board_result: BoardResult = board_str.as_str().into();
         let board = board_result.into_inner().unwrap();
         let bitmap: BitBoard = board.clone().into();
-        todo!()
-        // println!("{}", board);
-        // assert_eq!(AlmostWinHeuristic::score(&board), AlmostWinHeuristic::score(&bitmap));
+        println!("{}", board);
+        assert_eq!(AlmostWinHeuristic::score(&board), AlmostWinHeuristic::score(&bitmap));
 }
\ No newline at end of file
@@ -0,0 +1,36 @@
+use tic_solved::{
+    tic_array::{tic::*, bitmap_tic::*},
+    minmax::alphabeta,
+    cli::{analyze, read_position},
+};
+
+use rstest::rstest;
+use std::path::PathBuf;
+
+#[rstest]
+fn test_analyze_scores_every_move(#[files("positions/*.txt")] file_path: PathBuf) {
+    let board = read_position(Some(&file_path)).unwrap();
+    let scores = analyze(&board, 3);
+    if board.outcome().is_over() {
+        assert!(scores.is_empty());
+        return;
+    }
+    let moves: Vec<_> = scores.iter().map(|(m, _)| *m).collect();
+    assert_eq!(moves, board.legal_moves());
+
+    // The best move scores what a full search from the position scores.
+    let bitboard: BitBoard = board.clone().into();
+    if let Some((_, best)) = scores.iter().max_by_key(|(_, score)| *score) {
+        let (_, score) = match board.who_turn() {
+            Player::Player1 => alphabeta::min_score::<BitBoard, WinHeuristic>(&bitboard, 3, None, None),
+            Player::Player2 => alphabeta::max_score::<BitBoard, WinHeuristic>(&bitboard, 3, None, None),
+        };
+        assert_eq!(*best, if board.who_turn() == Player::Player2 { score } else { -score });
+    }
+}
+
+#[test]
+fn test_read_position() {
+    assert_eq!(read_position(None), Ok(Board::new()));
+    assert!(read_position(Some(&PathBuf::from("positions/missing.txt"))).is_err());
+}
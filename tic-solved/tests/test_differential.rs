@@ -0,0 +1,74 @@
+use tic_solved::{
+    tic_array::{
+        tic::*,
+        bitmap_tic::*,
+        tic_move::Move,
+    },
+    minmax::Heuristic,
+};
+
+use proptest::prelude::*;
+
+// Plays the same game on both engines, checking after every move that they agree on everything
+// the rules decide. Each choice picks a move by index into the legal moves, so any vector of
+// numbers is a valid game and proptest can shrink a failing game down to the moves that matter.
+fn walk_in_lockstep(choices: &[usize]) -> Result<(), TestCaseError> {
+    let mut board = Board::new();
+    let mut bitboard = BitBoard::new();
+    let mut played: Vec<Move> = Vec::new();
+    for &choice in choices {
+        compare(&board, &bitboard, &played)?;
+        let moves = board.legal_moves();
+        if moves.is_empty() {
+            break;
+        }
+        let m = moves[choice % moves.len()];
+        board = board.play(m).map_err(|e| TestCaseError::fail(format!("board: {}", e)))?;
+        bitboard = bitboard.play(m).map_err(|e| TestCaseError::fail(format!("bitboard: {} after {}", e, line(&played))))?;
+        played.push(m);
+    }
+    compare(&board, &bitboard, &played)
+}
+
+fn line(moves: &[Move]) -> String {
+    moves.iter().map(|m| m.to_string()).collect::<Vec<_>>().join(" ")
+}
+
+fn compare(board: &Board, bitboard: &BitBoard, played: &[Move]) -> Result<(), TestCaseError> {
+    let at = || format!("after {}\n{}", line(played), board);
+    let mut board_moves = board.legal_moves();
+    let mut bitboard_moves = bitboard.legal_moves();
+    board_moves.sort();
+    bitboard_moves.sort();
+    prop_assert_eq!(board_moves, bitboard_moves, "legal moves {}", at());
+    prop_assert_eq!(board.board_winner(), bitboard.winner(), "winner {}", at());
+    prop_assert_eq!(board.who_turn(), bitboard.who_turn(), "turn {}", at());
+    prop_assert_eq!(board.outcome(), bitboard.outcome(), "outcome {}", at());
+    prop_assert_eq!(board.outcome().is_over(), board.legal_moves().is_empty(), "moves left {}", at());
+    for x0 in 0..3 {
+        for x1 in 0..3 {
+            prop_assert_eq!(
+                board.single_board_winner(x0, x1), bitboard.single_board_winner(x0, x1),
+                "sub board ({}, {}) {}", x0, x1, at()
+            );
+        }
+    }
+    prop_assert_eq!(
+        <AlmostWinHeuristic as Heuristic<Board>>::score(board),
+        <AlmostWinHeuristic as Heuristic<BitBoard>>::score(bitboard),
+        "AlmostWinHeuristic {}", at()
+    );
+    let converted: Board = bitboard.clone().into();
+    prop_assert_eq!(&converted, board, "conversion {}", at());
+    Ok(())
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(256))]
+
+    // Long enough to reach the end of most games.
+    #[test]
+    fn test_engines_agree(choices in prop::collection::vec(any::<usize>(), 0..81)) {
+        walk_in_lockstep(&choices)?;
+    }
+}
@@ -0,0 +1,97 @@
+use tic_solved::{
+    tic_array::{
+        tic::*,
+        bitmap_tic::*,
+        endgame_index::EndgameIndex,
+    },
+    minmax::GameState,
+    solve::Solution,
+};
+
+use proptest::prelude::*;
+use rstest::rstest;
+
+// Plain minimax over the whole game tree. The positions `EndgameIndex` builds needn't be legal in
+// the sense of `ranking`, so the solver won't take them.
+fn play_out(board: &BitBoard) -> Solution {
+    let outcome = board.outcome();
+    if outcome != GameOutcome::Ongoing {
+        return Solution { outcome, distance: 0 };
+    }
+    let player = board.who_turn();
+    board.next_states()
+        .iter()
+        .map(|next| {
+            let solution = play_out(next);
+            Solution { outcome: solution.outcome, distance: solution.distance + 1 }
+        })
+        .max_by_key(|solution| {
+            let distance = solution.distance as i16;
+            match solution.outcome {
+                GameOutcome::Win(p) if p == player => (2, -distance),
+                GameOutcome::Win(_) => (0, distance),
+                _ => (1, -distance),
+            }
+        })
+        .unwrap()
+}
+
+#[rstest]
+#[case(1, 1)]
+#[case(2, 9973)]
+fn test_index_round_trip(#[case] max_empty: usize, #[case] step: usize) {
+    let index = EndgameIndex::new(max_empty, u64::MAX).unwrap();
+    let mut valid = 0;
+    for number in (0..index.size()).step_by(step) {
+        if let Some(position) = index.position(number) {
+            assert_eq!(position.outcome(), GameOutcome::Ongoing);
+            assert_eq!(index.index_of(&position), Some(number));
+            valid += 1;
+        }
+    }
+    assert!(valid > 0);
+    assert!(index.position(index.size()).is_none());
+}
+
+#[test]
+fn test_fewer_empty_cells_come_first() {
+    let small = EndgameIndex::new(1, u64::MAX).unwrap();
+    let large = EndgameIndex::new(2, u64::MAX).unwrap();
+    for number in (0..small.size()).step_by(101) {
+        assert_eq!(large.position(number), small.position(number));
+    }
+}
+
+#[test]
+fn test_size_limit() {
+    assert!(EndgameIndex::new(2, 1000).is_err());
+    assert!(EndgameIndex::new(81, 1 << 40).is_err());
+}
+
+#[test]
+fn test_start_position_not_indexed() {
+    let index = EndgameIndex::new(2, u64::MAX).unwrap();
+    assert_eq!(index.index_of(&BitBoard::new()), None);
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    // Positions of a game with few playable cells play out like the position their number names.
+    #[test]
+    fn test_shared_numbers_have_one_solution(choices in prop::collection::vec(any::<usize>(), 81)) {
+        let index = EndgameIndex::new(2, u64::MAX).unwrap();
+        let mut board = BitBoard::new();
+        for &choice in &choices {
+            if let Some(number) = index.index_of(&board) {
+                let position = index.position(number).unwrap();
+                prop_assert_eq!(play_out(&position), play_out(&board));
+            }
+            let moves = board.legal_moves();
+            if moves.is_empty() {
+                break;
+            }
+            board = board.play(moves[choice % moves.len()]).unwrap();
+        }
+    }
+}
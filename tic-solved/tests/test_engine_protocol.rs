@@ -0,0 +1,111 @@
+use tic_solved::tic_array::{
+    tic::*,
+    tic_move::Move,
+    engine_protocol::Engine,
+};
+
+use rstest::rstest;
+use std::fs;
+use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, Instant};
+
+fn read_board(file_path: PathBuf) -> Board {
+    let board_str = fs::read_to_string(file_path)
+        .expect("Failed to read board file");
+
+    let board_result: BoardResult = board_str.as_str().into();
+    board_result.into_inner().unwrap()
+}
+
+fn run(input: &str) -> Vec<String> {
+    let mut engine = Engine::new(Vec::new());
+    engine.run(input.as_bytes()).unwrap();
+    String::from_utf8(engine.into_output()).unwrap().lines().map(str::to_string).collect()
+}
+
+fn best_move(output: &[String]) -> Option<Move> {
+    let last = output.last().expect("No output");
+    let notation = last.strip_prefix("bestmove ").expect("Search did not finish");
+    notation.parse().ok()
+}
+
+#[rstest]
+fn test_go_plays_a_legal_move(#[files("positions/*.txt")] file_path: PathBuf) {
+    let board = read_board(file_path);
+    let cells: String = board.to_string().split_whitespace().collect();
+    let output = run(&format!("position board {}\ngo depth 3\n", cells));
+    match best_move(&output) {
+        Some(m) => {
+            assert!(board.legal_moves().contains(&m));
+            assert!(output.iter().any(|line| line.starts_with("info depth 3 ")));
+        }
+        None => assert_eq!(output.last().unwrap(), "bestmove none"),
+    }
+}
+
+#[test]
+fn test_handshake_and_errors() {
+    let output = run("uti\nisready\nfoo\nposition startpos moves B2b2 B2b2\nquit\ngo depth 1\n");
+    assert_eq!(output, vec![
+        "id name tic-solved",
+        "utiok",
+        "readyok",
+        "info string Unknown command: foo",
+        "info string B2b2 is illegal: the cell is taken",
+    ]);
+}
+
+#[test]
+fn test_info_lines() {
+    let output = run("newgame\nposition startpos moves B2b2 B2a1\ngo depth 3\n");
+    let board = Board::new().play("B2b2".parse().unwrap()).unwrap().play("B2a1".parse().unwrap()).unwrap();
+    let infos: Vec<&String> = output.iter().filter(|line| line.starts_with("info depth")).collect();
+    assert_eq!(infos.len(), 3);
+    for (depth, info) in (1..).zip(&infos) {
+        let fields: Vec<&str> = info.split_whitespace().collect();
+        assert_eq!(fields[2], depth.to_string());
+        assert_eq!(fields[3], "score");
+        assert!(fields[4].parse::<i32>().is_ok());
+        assert_eq!(fields[5], "nodes");
+        assert!(fields[6].parse::<u64>().unwrap() > 0);
+        assert_eq!(fields[9], "pv");
+        assert!(fields.len() > 10);
+        // The principal variation is a line of legal moves starting with the best move.
+        let mut position = board.clone();
+        for notation in &fields[10..] {
+            position = position.play(notation.parse().unwrap()).unwrap();
+        }
+    }
+    let best = best_move(&output).unwrap();
+    assert!(infos.last().unwrap().contains(&format!(" pv {}", best)));
+}
+
+#[test]
+fn test_stop_ends_an_infinite_search() {
+    let mut engine = Engine::new(Vec::new());
+    engine.handle("go infinite");
+    thread::sleep(Duration::from_millis(50));
+    let start = Instant::now();
+    engine.handle("stop");
+    assert!(start.elapsed() < Duration::from_secs(5));
+    let output = String::from_utf8(engine.into_output()).unwrap();
+    let lines: Vec<String> = output.lines().map(str::to_string).collect();
+    assert!(Board::new().legal_moves().contains(&best_move(&lines).unwrap()));
+}
+
+#[test]
+fn test_input_ending_stops_an_infinite_search() {
+    let start = Instant::now();
+    let output = run("go infinite\n");
+    assert!(start.elapsed() < Duration::from_secs(5));
+    assert!(best_move(&output).is_some());
+}
+
+#[test]
+fn test_movetime() {
+    let start = Instant::now();
+    let output = run("go movetime 100\n");
+    assert!(start.elapsed() < Duration::from_secs(5));
+    assert!(best_move(&output).is_some());
+}
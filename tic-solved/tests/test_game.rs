@@ -0,0 +1,110 @@
+use tic_solved::{
+    tic_array::{
+        tic::*,
+        tic_move::Move,
+        tic_simulator::*,
+        alphabeta_strategy_bitboard::AlphaBetaBitBoardStrategy,
+        game_record::SavedGame,
+    },
+    game::*,
+};
+
+use std::collections::VecDeque;
+
+// Plays moves from a script and remembers everything it was asked to show.
+#[derive(Default)]
+struct ScriptedInterface {
+    moves: VecDeque<Move>,
+    positions: Vec<Board>,
+    requests: Vec<Player>,
+    illegal: Vec<String>,
+    result: Option<GameResult>,
+}
+
+impl ScriptedInterface {
+    fn new(moves: &str) -> ScriptedInterface {
+        ScriptedInterface {
+            moves: moves.split_whitespace().map(|m| m.parse().unwrap()).collect(),
+            ..Default::default()
+        }
+    }
+}
+
+impl HumanPlayerInterface for ScriptedInterface {
+    fn show_position(&mut self, board: &Board) {
+        self.positions.push(board.clone());
+    }
+
+    fn request_move(&mut self, _board: &Board, player: Player, _legal_moves: &[Move]) -> Option<Move> {
+        self.requests.push(player);
+        self.moves.pop_front()
+    }
+
+    fn report_illegal_input(&mut self, message: &str) {
+        self.illegal.push(message.to_string());
+    }
+
+    fn show_result(&mut self, result: GameResult, _board: &Board) {
+        assert!(self.result.is_none());
+        self.result = Some(result);
+    }
+}
+
+// Two people replaying a game the engines played get the same game.
+#[test]
+fn test_hot_seat_game() {
+    let engines = run_game(Board::new(), AlphaBetaBitBoardStrategy::new(1), AlphaBetaBitBoardStrategy::new(2));
+    let script: Vec<String> = SavedGame::new(engines.clone()).moves().iter().map(|m| m.to_string()).collect();
+    let mut interface = ScriptedInterface::new(&script.join(" "));
+    let record = play_local(Board::new(), Seat::Human, Seat::Human, &mut interface);
+    assert!(interface.illegal.is_empty());
+    assert_eq!(record.result, engines.result);
+    assert_eq!(record.final_board(), engines.final_board());
+    // Humans see the position when they're asked for a move, not a second time before it.
+    assert!(interface.positions.is_empty());
+    assert_eq!(interface.requests.len(), script.len());
+    assert_eq!(interface.requests[..2], [Player::Player1, Player::Player2]);
+    assert_eq!(interface.result, Some(engines.result));
+}
+
+#[test]
+fn test_illegal_moves_are_reported_and_asked_again() {
+    let mut interface = ScriptedInterface::new("B2b2 B2b2 A1a1 B2a1");
+    let record = play_local(Board::new(), Seat::Human, Seat::Human, &mut interface);
+    assert_eq!(interface.illegal, vec![
+        "B2b2 is illegal: the cell is taken",
+        "A1a1 is illegal: the move has to be played in another sub board",
+    ]);
+    // The script runs out on move three, so player 1 gives up.
+    assert_eq!(record.moves.len(), 2);
+    assert_eq!(record.result, GameResult::Forfeit(Player::Player1));
+    assert_eq!(interface.result, Some(GameResult::Forfeit(Player::Player1)));
+}
+
+#[test]
+fn test_human_against_engine() {
+    let mut interface = ScriptedInterface::new("B2b2");
+    let engine = Seat::Engine(Box::new(AlphaBetaBitBoardStrategy::new(2)));
+    let record = play_local(Board::new(), Seat::Human, engine, &mut interface);
+    // The human moves, the engine answers, then the human runs out of moves to play.
+    assert_eq!(record.moves.len(), 2);
+    assert_eq!(record.moves[1].player, Player::Player2);
+    assert_eq!(interface.requests, vec![Player::Player1, Player::Player1]);
+    assert_eq!(interface.positions.len(), 1);
+    assert_eq!(interface.result, Some(GameResult::Forfeit(Player::Player1)));
+}
+
+#[test]
+fn test_engine_against_engine() {
+    let mut interface = ScriptedInterface::new("");
+    let record = play_local(
+        Board::new(),
+        Seat::Engine(Box::new(AlphaBetaBitBoardStrategy::new(1))),
+        Seat::Engine(Box::new(AlphaBetaBitBoardStrategy::new(2))),
+        &mut interface,
+    );
+    assert!(interface.requests.is_empty());
+    assert_eq!(interface.positions.len(), record.moves.len());
+    assert!(record.final_board().outcome().is_over());
+    assert_eq!(interface.result, Some(record.result));
+}
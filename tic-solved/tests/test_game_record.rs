@@ -0,0 +1,66 @@
+use tic_solved::tic_array::{
+    tic::*,
+    alphabeta_strategy_bitboard::AlphaBetaBitBoardStrategy,
+    tic_simulator::*,
+    game_record::*,
+};
+
+use rstest::rstest;
+use std::fs;
+use std::path::PathBuf;
+
+fn read_board(file_path: PathBuf) -> Board {
+    let board_str = fs::read_to_string(file_path)
+        .expect("Failed to read board file");
+
+    let board_result: BoardResult = board_str.as_str().into();
+    board_result.into_inner().unwrap()
+}
+
+#[rstest]
+fn test_round_trip(#[files("positions/*.txt")] file_path: PathBuf) {
+    let board = read_board(file_path);
+    let record = run_game(board, AlphaBetaBitBoardStrategy::new(1), AlphaBetaBitBoardStrategy::new(2));
+    let game = SavedGame::new(record)
+        .with_header("Player1", "depth 1")
+        .with_header("Player2", "depth 2");
+
+    let text = game.to_string();
+    let parsed = SavedGame::parse(&text).unwrap();
+    assert_eq!(parsed.header("Player2"), Some("depth 2"));
+    assert_eq!(parsed.headers, game.headers);
+    assert_eq!(parsed.record.result, game.record.result);
+    assert_eq!(parsed.positions(), game.positions());
+    for (a, b) in parsed.record.moves.iter().zip(&game.record.moves) {
+        assert_eq!(a.player, b.player);
+        assert_eq!(a.elapsed.as_micros(), b.elapsed.as_micros());
+    }
+    assert_eq!(parsed.to_string(), text);
+}
+
+#[test]
+fn test_save_and_load() {
+    let record = run_game(Board::new(), AlphaBetaBitBoardStrategy::new(1), AlphaBetaBitBoardStrategy::new(1));
+    let game = SavedGame::new(record).with_header("Date", "0");
+    let path = std::env::temp_dir().join(format!("tic-solved-record-{}.txt", std::process::id()));
+    game.save(&path).unwrap();
+    let loaded = SavedGame::load(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(loaded.positions(), game.positions());
+    assert_eq!(loaded.moves(), game.moves());
+}
+
+#[test]
+fn test_parse_errors() {
+    let start = "Start: ".to_string() + &".".repeat(81);
+    let valid = format!("# tic-solved game record 1\n{}\nResult: draw\n\n1 B2b2 10us\n2 B2a1 10us\n", start);
+    let game = SavedGame::parse(&valid).unwrap();
+    assert_eq!(game.moves().iter().map(|m| m.to_string()).collect::<Vec<_>>(), vec!["B2b2", "B2a1"]);
+
+    assert!(SavedGame::parse("not a record").is_err());
+    assert!(SavedGame::parse(&valid.replace("Result: draw\n", "")).is_err());
+    assert!(SavedGame::parse(&valid.replace("2 B2a1", "3 B2a1")).is_err());
+    // B2a1 sends the next move to A1, so B2b2 is not allowed.
+    assert!(SavedGame::parse(&valid.replace("2 B2a1 10us\n", "2 B2a1 10us\n3 B2b2 10us\n")).is_err());
+    assert!(SavedGame::parse(&valid.replace("10us", "10")).is_err());
+}
@@ -0,0 +1,77 @@
+use tic_solved::{
+    tic_array::{
+        tic::*,
+        bitmap_tic::*,
+        alphabeta_strategy::AlphaBetaStrategy,
+        alphabeta_strategy_bitboard::AlphaBetaBitBoardStrategy,
+        tic_simulator::*,
+    },
+    minmax::{
+        alphabeta::{self, Search},
+        iterative::iterative_deepening,
+        transposition::TranspositionTable,
+    },
+};
+
+use rstest::rstest;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+fn read_board(file_path: PathBuf) -> Board {
+    let board_str = fs::read_to_string(file_path)
+        .expect("Failed to read board file");
+
+    let board_result: BoardResult = board_str.as_str().into();
+    board_result.into_inner().unwrap()
+}
+
+#[rstest]
+fn test_completed_search_matches_alphabeta(#[files("positions/*.txt")] file_path: PathBuf) {
+    let board = read_board(file_path);
+    let search = Search::<Board, AlmostWinHeuristic, _, _, _>::new();
+    let result = iterative_deepening(&board, true, 3, Duration::from_secs(3600), search).unwrap();
+    let (_, score) = alphabeta::max_score::<Board, AlmostWinHeuristic>(&board, 3, None, None);
+    assert_eq!(result.depth, 3);
+    assert_eq!(result.score, score);
+    assert!(board.get_legal_boards().contains(&result.best));
+}
+
+#[rstest]
+#[case("positions/empty_position.txt")]
+#[case("positions/early_game_position.txt")]
+#[case("positions/lategame.txt")]
+fn test_table_is_kept_across_iterations(#[case] file_path: &str) {
+    let board: BitBoard = read_board(PathBuf::from(file_path)).into();
+    let mut table = TranspositionTable::new(1 << 16);
+    let search = Search::<BitBoard, WinHeuristic, _, _, _>::new().with_table(&mut table);
+    let result = iterative_deepening(&board, true, 4, Duration::from_secs(3600), search).unwrap();
+    let (_, score) = alphabeta::max_score::<BitBoard, WinHeuristic>(&board, 4, None, None);
+    assert_eq!(result.depth, 4);
+    assert_eq!(result.score, score);
+    assert!(table.stats().hits > 0);
+}
+
+#[test]
+fn test_zero_budget_still_returns_a_move() {
+    let board: BitBoard = BitBoard::new();
+    let search = Search::<BitBoard, WinHeuristic, _, _, _>::new();
+    let result = iterative_deepening(&board, false, 20, Duration::ZERO, search).unwrap();
+    assert_eq!(result.depth, 1);
+}
+
+#[test]
+fn test_strategies_respect_budget() {
+    let board = Board::new();
+    let budget = Duration::from_millis(200);
+
+    let start = Instant::now();
+    let next = AlphaBetaBitBoardStrategy::new(40).with_time_budget(budget).get_move(&board).unwrap();
+    assert!(start.elapsed() < Duration::from_secs(5));
+    assert!(board.get_legal_boards().contains(&next));
+
+    let start = Instant::now();
+    let next = AlphaBetaStrategy::new(40).with_time_budget(budget).get_move(&board).unwrap();
+    assert!(start.elapsed() < Duration::from_secs(5));
+    assert!(board.get_legal_boards().contains(&next));
+}
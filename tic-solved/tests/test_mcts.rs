@@ -0,0 +1,71 @@
+use tic_solved::{
+    tic_array::{
+        tic::*,
+        bitmap_tic::*,
+        mcts_strategy::*,
+        alphabeta_strategy_bitboard::AlphaBetaBitBoardStrategy,
+        tic_simulator::*,
+    },
+    minmax::GameState,
+};
+
+use rstest::rstest;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+fn read_board(file_path: PathBuf) -> Board {
+    let board_str = fs::read_to_string(file_path)
+        .expect("Failed to read board file");
+
+    let board_result: BoardResult = board_str.as_str().into();
+    board_result.into_inner().unwrap()
+}
+
+#[rstest]
+fn test_legal_and_deterministic(#[files("positions/*.txt")] file_path: PathBuf) {
+    let board = read_board(file_path);
+    let bitmap: BitBoard = board.into();
+    let strategy = MctsStrategy::new(300).with_seed(7);
+    let first = strategy.best_move(&bitmap);
+    assert_eq!(first, strategy.best_move(&bitmap));
+    match first {
+        Some(next) => assert!(bitmap.next_states().contains(&next)),
+        None => assert!(bitmap.next_states().is_empty()),
+    }
+}
+
+#[test]
+fn test_takes_immediate_win() {
+    // The first position below lategame.txt where the side to move can win the game outright.
+    let board = read_board(PathBuf::from("positions/lategame.txt"));
+    let mut frontier = vec![BitBoard::from(board)];
+    let position = loop {
+        let position = frontier.remove(0);
+        let player = position.who_turn();
+        if position.next_states().iter().any(|next| next.winner() == Some(player)) {
+            break position;
+        }
+        if position.winner().is_none() {
+            frontier.extend(position.next_states());
+        }
+    };
+    for rollout in [Rollout::Random, Rollout::Heuristic] {
+        let strategy = MctsStrategy::new(2000).with_rollout(rollout).with_seed(1);
+        let next = strategy.best_move(&position).unwrap();
+        assert_eq!(next.winner(), Some(position.who_turn()));
+    }
+}
+
+#[test]
+fn test_time_budget() {
+    let strategy = MctsStrategy::new(0).with_time_budget(Duration::from_millis(50));
+    assert!(strategy.get_move(&Board::new()).is_some());
+}
+
+#[test]
+fn test_against_alphabeta() {
+    let mcts = MctsStrategy::new(200).with_rollout(Rollout::Heuristic).with_seed(3);
+    let record = run_game(Board::new(), mcts, AlphaBetaBitBoardStrategy::new(2));
+    assert!(!matches!(record.result, GameResult::Forfeit(_)));
+}
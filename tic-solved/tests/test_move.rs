@@ -0,0 +1,64 @@
+use tic_solved::{
+    tic_array::{
+        tic::*,
+        bitmap_tic::*,
+        tic_move::*,
+    },
+    minmax::GameState,
+};
+
+use rstest::rstest;
+use std::fs;
+use std::path::PathBuf;
+
+fn read_board(file_path: PathBuf) -> Board {
+    let board_str = fs::read_to_string(file_path)
+        .expect("Failed to read board file");
+
+    let board_result: BoardResult = board_str.as_str().into();
+    board_result.into_inner().unwrap()
+}
+
+fn all_moves() -> impl Iterator<Item = Move> {
+    (0..81).filter_map(Move::from_index)
+}
+
+#[rstest]
+fn test_board_play(#[files("positions/*.txt")] file_path: PathBuf) {
+    let board = read_board(file_path);
+    let moves = board.legal_moves();
+    let boards: Vec<Board> = moves.iter().map(|&m| board.play(m).unwrap()).collect();
+    assert_eq!(boards, board.get_legal_boards());
+    for m in all_moves().filter(|m| !moves.contains(m)) {
+        assert_eq!(board.play(m).unwrap_err().attempted, m);
+    }
+}
+
+#[rstest]
+fn test_bitboard_play(#[files("positions/*.txt")] file_path: PathBuf) {
+    let bitmap: BitBoard = read_board(file_path).into();
+    let moves = bitmap.legal_moves();
+    let boards: Vec<BitBoard> = moves.iter().map(|&m| bitmap.play(m).unwrap()).collect();
+    assert_eq!(boards, bitmap.next_states());
+    for m in all_moves().filter(|m| !moves.contains(m)) {
+        assert_eq!(bitmap.play(m).unwrap_err().attempted, m);
+    }
+}
+
+#[test]
+fn test_illegal_reasons() {
+    let centre: Move = "B2b2".parse().unwrap();
+    let board = Board::new().play(centre).unwrap();
+    let bitmap = BitBoard::from(Board::new()).play(centre).unwrap();
+    assert_eq!(bitmap, BitBoard::from(board.clone()));
+
+    let occupied = board.play(centre).unwrap_err();
+    assert_eq!(occupied.reason, IllegalMoveReason::Occupied);
+    assert_eq!(bitmap.play(centre).unwrap_err(), occupied);
+
+    let elsewhere: Move = "A1a1".parse().unwrap();
+    let wrong_board = board.play(elsewhere).unwrap_err();
+    assert_eq!(wrong_board.reason, IllegalMoveReason::WrongBoard);
+    assert_eq!(bitmap.play(elsewhere).unwrap_err(), wrong_board);
+    assert_eq!(wrong_board.to_string(), "A1a1 is illegal: the move has to be played in another sub board");
+}
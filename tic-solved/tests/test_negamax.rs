@@ -0,0 +1,69 @@
+use tic_solved::{
+    tic_array::{
+        tic::*,
+        bitmap_tic::*,
+    },
+    minmax::{
+        naive,
+        negamax::*,
+        GameState,
+        Heuristic,
+    },
+};
+
+use rstest::rstest;
+use std::fs;
+use std::path::PathBuf;
+
+fn read_board(file_path: PathBuf) -> Board {
+    let board_str = fs::read_to_string(file_path)
+        .expect("Failed to read board file");
+
+    let board_result: BoardResult = board_str.as_str().into();
+    board_result.into_inner().unwrap()
+}
+
+// Every variant must find the naive minimax score, and a move that is one of the legal moves.
+fn check_variants<T, U>(board: &T, depth: usize, maximize: bool, expected: U::Score)
+where
+    T: GameState + Clone + PartialEq + std::fmt::Debug,
+    U: Heuristic<T, Score = i32>
+{
+    let results = [
+        negamax_score::<T, U>(board, depth, maximize),
+        pvs_score::<T, U>(board, depth, maximize),
+        mtdf_score::<T, U>(board, depth, maximize, 0),
+        mtdf_score::<T, U>(board, depth, maximize, 500),
+        aspiration_score::<T, U>(board, depth, maximize, 0, 1),
+        aspiration_score::<T, U>(board, depth, maximize, -700, 3),
+    ];
+    let moves = board.next_states();
+    for (best, score) in results {
+        assert_eq!(score, expected);
+        assert!(moves.is_empty() || moves.contains(&best));
+    }
+}
+
+#[rstest]
+fn test_variants_max(#[files("positions/*.txt")] file_path: PathBuf) {
+    let board = read_board(file_path);
+    let expected = naive::max_score::<Board, AlmostWinHeuristic>(&board, 3);
+    check_variants::<Board, AlmostWinHeuristic>(&board, 3, true, expected);
+}
+
+#[rstest]
+fn test_variants_min(#[files("positions/*.txt")] file_path: PathBuf) {
+    let board = read_board(file_path);
+    let expected = naive::min_score::<Board, AlmostWinHeuristic>(&board, 3);
+    check_variants::<Board, AlmostWinHeuristic>(&board, 3, false, expected);
+}
+
+#[rstest]
+fn test_variants_bitboard(#[files("positions/*.txt")] file_path: PathBuf) {
+    let board = read_board(file_path);
+    let bitmap: BitBoard = board.into();
+    let expected = naive::max_score::<BitBoard, WinHeuristic>(&bitmap, 4);
+    check_variants::<BitBoard, WinHeuristic>(&bitmap, 4, true, expected);
+    let expected = naive::min_score::<BitBoard, WinHeuristic>(&bitmap, 4);
+    check_variants::<BitBoard, WinHeuristic>(&bitmap, 4, false, expected);
+}
@@ -0,0 +1,267 @@
+use tic_solved::{
+    tic_array::{
+        tic::*,
+        tic_move::Move,
+        tic_simulator::*,
+        alphabeta_strategy_bitboard::AlphaBetaBitBoardStrategy,
+    },
+    game::HumanPlayerInterface,
+    net::{*, server::Server, client::*},
+};
+
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::{SocketAddr, TcpStream},
+    sync::mpsc,
+    thread,
+    time::Duration,
+};
+
+fn start_server() -> SocketAddr {
+    let server = Server::bind("127.0.0.1:0").unwrap();
+    let address = server.local_addr().unwrap();
+    thread::spawn(move || server.run());
+    address
+}
+
+// A client speaking the protocol by hand.
+struct RawClient {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl RawClient {
+    fn connect(address: SocketAddr) -> RawClient {
+        let writer = TcpStream::connect(address).unwrap();
+        // A test that would hang fails instead.
+        writer.set_read_timeout(Some(Duration::from_secs(30))).unwrap();
+        writer.set_write_timeout(Some(Duration::from_secs(30))).unwrap();
+        RawClient { reader: BufReader::new(writer.try_clone().unwrap()), writer }
+    }
+
+    fn send(&mut self, line: &str) {
+        writeln!(self.writer, "{}", line).unwrap();
+    }
+
+    fn receive(&mut self) -> ServerMessage {
+        let mut line = String::new();
+        self.reader.read_line(&mut line).unwrap();
+        ServerMessage::parse(&line).unwrap()
+    }
+}
+
+// Two raw players that have both been told the game started.
+fn raw_game(address: SocketAddr) -> (RawClient, RawClient) {
+    let mut player1 = RawClient::connect(address);
+    player1.send("join");
+    assert_eq!(player1.receive(), ServerMessage::Welcome(0, Role::Player(Player::Player1)));
+    let mut player2 = RawClient::connect(address);
+    player2.send("join player");
+    assert_eq!(player2.receive(), ServerMessage::Welcome(0, Role::Player(Player::Player2)));
+    for player in [&mut player1, &mut player2] {
+        assert_eq!(player.receive(), ServerMessage::Start);
+        assert_eq!(player.receive(), ServerMessage::Board(Board::new()));
+    }
+    (player1, player2)
+}
+
+#[derive(Default)]
+struct Recorder {
+    positions: Vec<Board>,
+    result: Option<GameResult>,
+    shown: Option<mpsc::Sender<()>>,
+}
+
+impl HumanPlayerInterface for Recorder {
+    fn show_position(&mut self, board: &Board) {
+        self.positions.push(board.clone());
+        if let Some(shown) = &self.shown {
+            let _ = shown.send(());
+        }
+    }
+
+    fn request_move(&mut self, _board: &Board, _player: Player, _legal_moves: &[Move]) -> Option<Move> {
+        None
+    }
+
+    fn report_illegal_input(&mut self, _message: &str) {}
+
+    fn show_result(&mut self, result: GameResult, _board: &Board) {
+        self.result = Some(result);
+    }
+}
+
+#[test]
+fn test_engines_play_over_the_network() {
+    let address = start_server();
+    let expected = run_game(Board::new(), AlphaBetaBitBoardStrategy::new(2), AlphaBetaBitBoardStrategy::new(2));
+
+    // The clients wait for the spectator, so it sees the game from the start.
+    let (joined, wait_for_players) = mpsc::channel();
+    let mut go = Vec::new();
+    let clients: Vec<_> = (0..2)
+        .map(|_| {
+            let joined = joined.clone();
+            let (start, wait_for_spectator) = mpsc::channel();
+            go.push(start);
+            thread::spawn(move || {
+                let remote = RemoteGame::join(address).unwrap();
+                joined.send(()).unwrap();
+                wait_for_spectator.recv().unwrap();
+                let engine = AlphaBetaBitBoardStrategy::new(2);
+                let record = match remote.seat {
+                    Player::Player1 => run_game(remote.start, engine, &remote.opponent),
+                    Player::Player2 => run_game(remote.start, &remote.opponent, engine),
+                };
+                let result = remote.opponent.finish(record.final_board()).unwrap();
+                (remote.seat, record, result)
+            })
+        })
+        .collect();
+    for _ in 0..2 {
+        wait_for_players.recv().unwrap();
+    }
+
+    let (shown, wait_for_spectator) = mpsc::channel();
+    let watching = thread::spawn(move || {
+        let mut spectator = Recorder { shown: Some(shown), ..Recorder::default() };
+        let watched = spectate(address, Some(0), &mut spectator).unwrap();
+        (watched, spectator)
+    });
+    wait_for_spectator.recv().unwrap();
+    for start in go {
+        start.send(()).unwrap();
+    }
+    let (watched, spectator) = watching.join().unwrap();
+    assert_eq!(watched, Some(expected.result));
+    assert_eq!(spectator.result, Some(expected.result));
+    assert_eq!(spectator.positions.first(), Some(&Board::new()));
+    assert_eq!(spectator.positions.last(), expected.moves.last().map(|m| &m.board));
+
+    let mut seats = Vec::new();
+    for client in clients {
+        let (seat, record, result) = client.join().unwrap();
+        seats.push(seat);
+        assert_eq!(record.result, expected.result);
+        assert_eq!(record.moves.last().map(|m| &m.board), expected.moves.last().map(|m| &m.board));
+        assert_eq!(result, expected.result);
+    }
+    assert_ne!(seats[0], seats[1]);
+}
+
+#[test]
+fn test_moves_are_validated_and_relayed() {
+    let address = start_server();
+    let (mut player1, mut player2) = raw_game(address);
+
+    player2.send("move B2b2");
+    assert!(matches!(player2.receive(), ServerMessage::Error(_)));
+    player1.send("move sideways");
+    assert!(matches!(player1.receive(), ServerMessage::Error(_)));
+
+    player1.send("move B2b2");
+    let after = Board::new().play("B2b2".parse().unwrap()).unwrap();
+    for player in [&mut player1, &mut player2] {
+        assert_eq!(player.receive(), ServerMessage::Played(Player::Player1, "B2b2".parse().unwrap()));
+        assert_eq!(player.receive(), ServerMessage::Board(after.clone()));
+    }
+
+    // Player 2 is sent to the middle board.
+    player2.send("move A1a1");
+    assert!(matches!(player2.receive(), ServerMessage::Illegal(_)));
+    player2.send("move B2b2");
+    assert!(matches!(player2.receive(), ServerMessage::Illegal(_)));
+
+    let mut spectator = RawClient::connect(address);
+    spectator.send("join spectator");
+    assert_eq!(spectator.receive(), ServerMessage::Welcome(0, Role::Spectator));
+    assert_eq!(spectator.receive(), ServerMessage::Board(after.clone()));
+    spectator.send("move B2a1");
+    assert!(matches!(spectator.receive(), ServerMessage::Error(_)));
+
+    player2.send("resign");
+    for client in [&mut player1, &mut player2, &mut spectator] {
+        assert_eq!(client.receive(), ServerMessage::GameOver(GameResult::Forfeit(Player::Player2)));
+    }
+    player1.send("move B2a1");
+    assert!(matches!(player1.receive(), ServerMessage::Error(_)));
+}
+
+#[test]
+fn test_leaving_forfeits() {
+    let address = start_server();
+    let (mut player1, player2) = raw_game(address);
+    drop(player2);
+    assert_eq!(player1.receive(), ServerMessage::GameOver(GameResult::Forfeit(Player::Player2)));
+
+    // The next players start a new game.
+    let mut next = RawClient::connect(address);
+    next.send("join");
+    assert_eq!(next.receive(), ServerMessage::Welcome(1, Role::Player(Player::Player1)));
+}
+
+#[test]
+fn test_finished_games_are_forgotten() {
+    let address = start_server();
+    let (mut player1, mut player2) = raw_game(address);
+    player1.send("resign");
+    for player in [&mut player1, &mut player2] {
+        assert_eq!(player.receive(), ServerMessage::GameOver(GameResult::Forfeit(Player::Player1)));
+    }
+
+    let mut spectator = RawClient::connect(address);
+    spectator.send("join spectator 0");
+    assert!(matches!(spectator.receive(), ServerMessage::Error(_)));
+    player2.send("move B2b2");
+    assert!(matches!(player2.receive(), ServerMessage::Error(_)));
+}
+
+#[test]
+fn test_a_client_that_stops_reading_holds_up_nobody() {
+    let address = start_server();
+    let (mut player1, mut player2) = raw_game(address);
+    // A spectator that never reads, sent more than its connection can buffer: unknown commands
+    // are echoed back.
+    let mut stalled = RawClient::connect(address);
+    stalled.send("join spectator 0");
+    let noise = "x".repeat(1 << 16);
+    for _ in 0..1000 {
+        stalled.send(&noise);
+    }
+
+    player1.send("move B2b2");
+    for player in [&mut player1, &mut player2] {
+        assert_eq!(player.receive(), ServerMessage::Played(Player::Player1, "B2b2".parse().unwrap()));
+    }
+}
+
+#[test]
+fn test_remote_opponent_reports_forfeit() {
+    let address = start_server();
+    let mut player1 = RawClient::connect(address);
+    player1.send("join");
+    let joining = thread::spawn(move || RemoteGame::join(address).unwrap());
+    assert_eq!(player1.receive(), ServerMessage::Welcome(0, Role::Player(Player::Player1)));
+    let remote = joining.join().unwrap();
+    assert_eq!(remote.seat, Player::Player2);
+
+    player1.send("resign");
+    assert_eq!(remote.opponent.get_move(&remote.start), None);
+    assert_eq!(remote.opponent.result(), Some(GameResult::Forfeit(Player::Player1)));
+    assert_eq!(remote.opponent.finish(&remote.start).unwrap(), GameResult::Forfeit(Player::Player1));
+}
+
+#[test]
+fn test_giving_up_locally_resigns() {
+    let address = start_server();
+    let mut player1 = RawClient::connect(address);
+    player1.send("join");
+    let joining = thread::spawn(move || RemoteGame::join(address).unwrap());
+    assert_eq!(player1.receive(), ServerMessage::Welcome(0, Role::Player(Player::Player1)));
+    let remote = joining.join().unwrap();
+    assert_eq!(player1.receive(), ServerMessage::Start);
+    assert_eq!(player1.receive(), ServerMessage::Board(Board::new()));
+
+    assert_eq!(remote.opponent.finish(&remote.start).unwrap(), GameResult::Forfeit(Player::Player2));
+    assert_eq!(player1.receive(), ServerMessage::GameOver(GameResult::Forfeit(Player::Player2)));
+}
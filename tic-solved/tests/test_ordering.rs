@@ -0,0 +1,68 @@
+use tic_solved::{
+    tic_array::{
+        tic::*,
+        bitmap_tic::*,
+    },
+    minmax::{
+        alphabeta,
+        GameState,
+        MoveKey,
+        ordering::*,
+        transposition::TranspositionTable,
+    },
+};
+
+use rstest::rstest;
+use std::fs;
+use std::path::PathBuf;
+
+fn read_board(file_path: PathBuf) -> Board {
+    let board_str = fs::read_to_string(file_path)
+        .expect("Failed to read board file");
+
+    let board_result: BoardResult = board_str.as_str().into();
+    board_result.into_inner().unwrap()
+}
+
+#[rstest]
+fn test_orderings_agree(#[files("positions/*.txt")] file_path: PathBuf) {
+    let board = read_board(file_path);
+    let bitmap: BitBoard = board.into();
+    let expected = alphabeta::search::<BitBoard, WinHeuristic>(&bitmap, 4, true).score;
+
+    let mut table = TranspositionTable::new(1 << 16);
+    let results = [
+        alphabeta::search_ordered::<_, WinHeuristic, _>(&bitmap, 4, true, &mut GenerationOrder, None),
+        alphabeta::search_ordered::<_, WinHeuristic, _>(&bitmap, 4, true, &mut HeuristicOrder::<WinHeuristic>::new(), None),
+        alphabeta::search_ordered::<_, WinHeuristic, _>(&bitmap, 4, true, &mut KillerMoves::new(GenerationOrder), None),
+        alphabeta::search_ordered::<_, WinHeuristic, _>(&bitmap, 4, true, &mut HistoryHeuristic::new(), None),
+        alphabeta::search_ordered::<_, WinHeuristic, _>(&bitmap, 4, true, &mut KillerMoves::new(HistoryHeuristic::new()), Some(&mut table)),
+    ];
+    for result in results {
+        assert_eq!(result.score, expected);
+        if let Some(best) = result.best_move() {
+            assert!(result.root_scores.iter().any(|(state, score)| state == best && *score == expected));
+        }
+    }
+}
+
+#[test]
+fn test_move_key_agrees() {
+    let board = read_board(PathBuf::from("positions/early_game_position.txt"));
+    let bitmap: BitBoard = board.clone().into();
+    let mut keys: Vec<usize> = board.get_legal_boards().iter().map(|next| board.move_key(next)).collect();
+    let mut bitmap_keys: Vec<usize> = bitmap.next_states().iter().map(|next| bitmap.move_key(next)).collect();
+    keys.sort();
+    bitmap_keys.sort();
+    assert_eq!(keys, bitmap_keys);
+}
+
+#[test]
+fn test_ordering_reduces_nodes() {
+    let board = read_board(PathBuf::from("positions/early_game_position.txt"));
+    let bitmap: BitBoard = board.into();
+    let plain = alphabeta::search_ordered::<_, WinHeuristic, _>(&bitmap, 5, true, &mut GenerationOrder, None);
+    let ordered = alphabeta::search_ordered::<_, WinHeuristic, _>(&bitmap, 5, true, &mut KillerMoves::new(HistoryHeuristic::new()), None);
+    assert_eq!(plain.score, ordered.score);
+    assert!(ordered.nodes < plain.nodes, "{} >= {}", ordered.nodes, plain.nodes);
+}
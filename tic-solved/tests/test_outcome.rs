@@ -0,0 +1,65 @@
+use tic_solved::{
+    tic_array::{
+        tic::*,
+        bitmap_tic::*,
+        tic_simulator::*,
+        alphabeta_strategy_bitboard::AlphaBetaBitBoardStrategy,
+    },
+    minmax::Heuristic,
+};
+
+use rstest::rstest;
+use std::fs;
+use std::path::PathBuf;
+
+fn read_board(file_path: PathBuf) -> Board {
+    let board_str = fs::read_to_string(file_path)
+        .expect("Failed to read board file");
+
+    let board_result: BoardResult = board_str.as_str().into();
+    board_result.into_inner().unwrap()
+}
+
+#[rstest]
+fn test_finished_games(#[files("positions/gameover/*.txt")] file_path: PathBuf) {
+    let is_tie = file_path.to_string_lossy().contains("tie");
+    let board = read_board(file_path);
+    let bitboard: BitBoard = board.clone().into();
+    let outcome = board.outcome();
+    assert_eq!(outcome, bitboard.outcome());
+    assert!(outcome.is_over());
+    if is_tie {
+        assert_eq!(outcome, GameOutcome::Draw);
+        assert_eq!(<AlmostWinHeuristic as Heuristic<Board>>::score(&board), 0);
+        assert_eq!(<AlmostWinHeuristic as Heuristic<BitBoard>>::score(&bitboard), 0);
+    } else {
+        assert_eq!(outcome, GameOutcome::Win(board.board_winner().unwrap()));
+        assert_eq!(outcome.winner(), board.board_winner());
+    }
+}
+
+#[rstest]
+fn test_ongoing_games(#[files("positions/*.txt")] file_path: PathBuf) {
+    let board = read_board(file_path);
+    let bitboard: BitBoard = board.clone().into();
+    assert_eq!(board.outcome(), bitboard.outcome());
+    assert_eq!(board.outcome().is_over(), board.legal_moves().is_empty());
+}
+
+#[test]
+fn test_empty_board_is_ongoing() {
+    assert_eq!(Board::new().outcome(), GameOutcome::Ongoing);
+    assert_eq!(GameOutcome::Ongoing.winner(), None);
+    assert!(!GameOutcome::Ongoing.is_over());
+}
+
+#[test]
+fn test_run_game_reports_the_outcome() {
+    let record = run_game(Board::new(), AlphaBetaBitBoardStrategy::new(1), AlphaBetaBitBoardStrategy::new(2));
+    let outcome = record.final_board().outcome();
+    match record.result {
+        GameResult::Win(player) => assert_eq!(outcome, GameOutcome::Win(player)),
+        GameResult::Draw => assert_eq!(outcome, GameOutcome::Draw),
+        GameResult::Forfeit(_) => panic!("Nobody should forfeit"),
+    }
+}
@@ -0,0 +1,83 @@
+use tic_solved::{
+    tic_array::{
+        tic::*,
+        bitmap_tic::*,
+        tournament::random_opening,
+    },
+    minmax::perft::{perft, perft_divide},
+};
+
+use rstest::rstest;
+use std::fs;
+use std::path::PathBuf;
+
+fn read_board(file_path: PathBuf) -> Board {
+    let board_str = fs::read_to_string(file_path)
+        .expect("Failed to read board file");
+
+    let board_result: BoardResult = board_str.as_str().into();
+    board_result.into_inner().unwrap()
+}
+
+// Both engines split the same way at the root, to `depth` plies.
+fn assert_engines_agree(board: &Board, depth: usize) {
+    let bitboard: BitBoard = board.clone().into();
+    let mut board_divide = perft_divide(board, depth);
+    let mut bitboard_divide = perft_divide(&bitboard, depth);
+    board_divide.sort();
+    bitboard_divide.sort();
+    assert_eq!(board_divide, bitboard_divide, "divide differs at depth {} for\n{}", depth, board);
+}
+
+// Known counts for ultimate tic tac toe from the empty board.
+const EMPTY_BOARD_PERFT: [u64; 5] = [81, 720, 6336, 55080, 473256];
+
+#[test]
+fn test_empty_board_board() {
+    for (depth, &expected) in (1..=4).zip(&EMPTY_BOARD_PERFT) {
+        assert_eq!(perft(&Board::new(), depth), expected);
+    }
+}
+
+#[test]
+fn test_empty_board_bitboard() {
+    for (depth, &expected) in (1..).zip(&EMPTY_BOARD_PERFT) {
+        assert_eq!(perft(&BitBoard::new(), depth), expected);
+    }
+}
+
+#[test]
+fn test_empty_board_divide() {
+    assert_engines_agree(&Board::new(), 3);
+    // A first move sends the opponent to an empty board with 9 replies, unless it sends them back
+    // to the board it was played on.
+    for m in Board::new().legal_moves() {
+        let expected = if m.board() == m.target_board() { 8 } else { 9 };
+        assert_eq!(perft(&Board::new().play(m).unwrap(), 1), expected, "after {}", m);
+    }
+}
+
+#[rstest]
+fn test_positions(#[files("positions/*.txt")] file_path: PathBuf) {
+    let board = read_board(file_path);
+    for depth in 1..=3 {
+        assert_engines_agree(&board, depth);
+    }
+}
+
+#[rstest]
+fn test_finished_games_have_no_moves(#[files("positions/gameover/*.txt")] file_path: PathBuf) {
+    let board = read_board(file_path);
+    let bitboard: BitBoard = board.clone().into();
+    assert_eq!(perft(&board, 1), 0);
+    assert_eq!(perft(&bitboard, 1), 0);
+}
+
+// Random openings reach sent-to-a-decided-board positions the files don't cover.
+#[test]
+fn test_random_openings() {
+    for seed in 0..30 {
+        let board = random_opening(20 + (seed as usize % 30), seed);
+        assert_engines_agree(&board, 2);
+    }
+}
@@ -0,0 +1,115 @@
+use tic_solved::{
+    tic_array::{
+        tic::*,
+        bitmap_tic::*,
+        ranking::{self, position_count},
+    },
+    minmax::GameState,
+};
+
+use proptest::prelude::*;
+use rstest::rstest;
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+
+fn read_board(file_path: PathBuf) -> Board {
+    let board_str = fs::read_to_string(file_path)
+        .expect("Failed to read board file");
+
+    let board_result: BoardResult = board_str.as_str().into();
+    board_result.into_inner().unwrap()
+}
+
+// The pieces without the forced sub board, which is drawn in upper case.
+fn pieces(board: &BitBoard) -> String {
+    let board: Board = board.clone().into();
+    board.to_string().to_lowercase().replace('`', ".")
+}
+
+// The same position as `board`, with a last move into a won or full sub board read as a free move.
+fn assert_same_position(unranked: &BitBoard, board: &BitBoard) {
+    let mut unranked_moves = unranked.legal_moves();
+    let mut moves = board.legal_moves();
+    unranked_moves.sort();
+    moves.sort();
+    assert_eq!(unranked_moves, moves);
+    assert_eq!(pieces(unranked), pieces(board));
+}
+
+#[test]
+fn test_start_position() {
+    let start = BitBoard::new();
+    let index = ranking::rank(&start).unwrap();
+    assert_eq!(ranking::unrank(index), Some(start));
+}
+
+#[test]
+fn test_out_of_range() {
+    assert!(ranking::unrank(position_count() - 1).is_some());
+    assert_eq!(ranking::unrank(position_count()), None);
+    assert_eq!(ranking::unrank(u128::MAX), None);
+}
+
+#[rstest]
+fn test_position_files_round_trip(#[files("positions/**/*.txt")] file_path: PathBuf) {
+    let board: BitBoard = read_board(file_path).into();
+    let index = ranking::rank(&board).unwrap();
+    let unranked = ranking::unrank(index).unwrap();
+    assert_same_position(&unranked, &board);
+    assert_eq!(ranking::rank(&unranked), Some(index));
+}
+
+#[test]
+fn test_illegal_positions_not_ranked() {
+    // Two rows in the top left sub board, which no single move completes. Characters go row by
+    // row over the whole grid, so the sub board's rows start at 0 and 18.
+    let mut text = vec!['.'; 81];
+    for n in [0, 1, 2, 18, 19, 20] {
+        text[n] = 'x';
+    }
+    for n in [3, 6, 27, 30, 33, 54] {
+        text[n] = 'o';
+    }
+    let text: String = text.into_iter().collect();
+    let board: BitBoard = BoardResult::from(text.as_str()).into_inner().unwrap().into();
+    assert_eq!(ranking::rank(&board), None);
+}
+
+#[test]
+fn test_reachable_positions_get_distinct_ranks() {
+    let mut frontier = vec![BitBoard::new()];
+    for _ in 0..3 {
+        frontier = frontier.iter().flat_map(|b| b.next_states()).collect();
+        let positions: HashSet<BitBoard> = frontier.iter().cloned().collect();
+        let indices: HashSet<u128> = positions.iter().map(|b| ranking::rank(b).unwrap()).collect();
+        assert_eq!(indices.len(), positions.len());
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(256))]
+
+    // Every position of a game ranks, and unranks back to itself.
+    #[test]
+    fn test_game_positions_round_trip(choices in prop::collection::vec(any::<usize>(), 0..81)) {
+        let mut board = BitBoard::new();
+        for &choice in &choices {
+            let index = ranking::rank(&board);
+            prop_assert!(index.is_some(), "not ranked\n{}", pieces(&board));
+            let unranked = ranking::unrank(index.unwrap()).unwrap();
+            assert_same_position(&unranked, &board);
+            let moves = board.legal_moves();
+            if moves.is_empty() {
+                break;
+            }
+            board = board.play(moves[choice % moves.len()]).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_indices_round_trip(index in 0..position_count()) {
+        let board = ranking::unrank(index).unwrap();
+        prop_assert_eq!(ranking::rank(&board), Some(index));
+    }
+}
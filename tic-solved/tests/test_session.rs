@@ -0,0 +1,144 @@
+use tic_solved::{
+    tic_array::{
+        tic::*,
+        tic_move::Move,
+        tic_simulator::*,
+        alphabeta_strategy_bitboard::AlphaBetaBitBoardStrategy,
+        game_record::*,
+    },
+    session::*,
+};
+
+fn moves(notation: &str) -> Vec<Move> {
+    notation.split_whitespace().map(|m| m.parse().unwrap()).collect()
+}
+
+fn session(notation: &str) -> Session {
+    let mut session = Session::new(Board::new());
+    for m in moves(notation) {
+        session.play(m).unwrap();
+    }
+    session
+}
+
+#[test]
+fn test_undo_redo() {
+    let mut session = session("B2b2 B2a1 A1c3");
+    let after_two = Board::new().play(moves("B2b2")[0]).unwrap().play(moves("B2a1")[0]).unwrap();
+    assert!(!session.redo());
+    assert!(session.undo());
+    assert!(session.undo());
+    assert!(session.redo());
+    assert_eq!(session.board(), &after_two);
+    assert_eq!(session.line(), moves("B2b2 B2a1"));
+    assert!(session.undo() && session.undo() && !session.undo());
+    assert_eq!(session.board(), &Board::new());
+    assert!(!session.can_undo());
+    while session.redo() {}
+    assert_eq!(session.line(), moves("B2b2 B2a1 A1c3"));
+}
+
+#[test]
+fn test_variations() {
+    let mut session = session("B2b2 B2a1");
+    let first = session.path()[0];
+    session.undo();
+    session.play(moves("B2c3")[0]).unwrap();
+    assert_eq!(session.node(first).children.len(), 2);
+    // Redo follows the line last undone.
+    session.undo();
+    session.redo();
+    assert_eq!(session.line(), moves("B2b2 B2c3"));
+    // Playing a move that is already in the tree goes back into that line.
+    session.undo();
+    let existing = session.node(first).children[0];
+    assert_eq!(session.play(moves("B2a1")[0]), Ok(existing));
+    assert_eq!(session.node(first).children.len(), 2);
+    let main: Vec<Move> = session.main_line().iter().filter_map(|&id| session.node(id).chosen).collect();
+    assert_eq!(main, moves("B2b2 B2a1"));
+}
+
+#[test]
+fn test_branch_from_earlier_position_and_promote() {
+    let mut session = session("B2b2 B2a1 A1c3 C3b2");
+    let first = session.path()[0];
+    assert!(session.go_to(first));
+    assert!(!session.go_to(100));
+    let branch = session.play(moves("B2c1")[0]).unwrap();
+    assert_eq!(session.line(), moves("B2b2 B2c1"));
+    session.promote(branch);
+    let main: Vec<Move> = session.main_line().iter().filter_map(|&id| session.node(id).chosen).collect();
+    assert_eq!(main, moves("B2b2 B2c1"));
+    assert_eq!(session.node(first).children.len(), 2);
+    // Redo from the start follows the branch we jumped to.
+    session.go_to(session.root());
+    while session.redo() {}
+    assert_eq!(session.line(), moves("B2b2 B2c1"));
+}
+
+#[test]
+fn test_line_records() {
+    let session = session("B2b2 B2a1 A1c3");
+    let records = session.line_records();
+    assert_eq!(records.iter().map(|r| r.player).collect::<Vec<_>>(), vec![Player::Player1, Player::Player2, Player::Player1]);
+    assert_eq!(&records[2].board, session.board());
+}
+
+#[test]
+fn test_export_round_trip() {
+    let mut session = session("B2b2 B2a1 A1c3");
+    session.undo();
+    session.undo();
+    session.play(moves("B2c3")[0]).unwrap();
+    session.play(moves("C3a1")[0]).unwrap();
+    session.undo();
+    session.play(moves("C3b2")[0]).unwrap();
+    session.go_to(session.root());
+    session.play(moves("A1a1")[0]).unwrap();
+
+    let tree = session.export().with_header("Player1", "human");
+    assert_eq!(tree.result, None);
+    let text = tree.to_string();
+    assert!(!text.contains("Result"));
+    let parsed = SavedTree::parse(&text).unwrap();
+    assert_eq!(parsed, tree);
+    assert_eq!(parsed.to_string(), text);
+
+    let imported = Session::import(&parsed).unwrap();
+    assert_eq!(imported.line(), moves("B2b2 B2a1 A1c3"));
+    assert_eq!(imported.export(), session.export());
+}
+
+#[test]
+fn test_export_finished_game() {
+    let record = run_game(Board::new(), AlphaBetaBitBoardStrategy::new(1), AlphaBetaBitBoardStrategy::new(2));
+    let game = SavedGame::new(record.clone());
+    let mut session = Session::new(Board::new());
+    for m in game.moves() {
+        session.play(m).unwrap();
+    }
+    let tree = session.export();
+    assert_eq!(tree.result, Some(record.result));
+    // Without variations the tree reads back as a plain game.
+    let parsed = SavedGame::parse(&tree.to_string()).unwrap();
+    assert_eq!(parsed.positions(), game.positions());
+}
+
+#[test]
+fn test_saved_game_skips_variations() {
+    let start = "Start: ".to_string() + &".".repeat(81);
+    let text = format!(
+        "# tic-solved game record 1\n{}\nResult: draw\n\n1 B2b2 10us\n(\n1 A1a1 10us\n(\n1 C3c3 0us\n)\n2 A1b2 5us\n)\n2 B2a1 10us\n",
+        start
+    );
+    let tree = SavedTree::parse(&text).unwrap();
+    assert_eq!(tree.moves.len(), 3);
+    assert_eq!(tree.main_line().iter().map(|m| m.chosen).collect::<Vec<_>>(), moves("B2b2 B2a1"));
+    let game = SavedGame::parse(&text).unwrap();
+    assert_eq!(game.moves(), moves("B2b2 B2a1"));
+
+    assert!(SavedTree::parse(&text.replace("2 A1b2 5us\n)\n", "2 A1b2 5us\n")).is_err());
+    assert!(SavedTree::parse(&text.replace("(\n1 C3c3 0us\n)\n", "(\n)\n")).is_err());
+    assert!(SavedTree::parse(&text.replace("1 A1a1", "2 A1a1")).is_err());
+    assert!(SavedTree::parse(&text.replace("2 B2a1 10us\n", "2 B2a1 10us\n)\n")).is_err());
+}
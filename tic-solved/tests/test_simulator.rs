@@ -0,0 +1,55 @@
+use tic_solved::tic_array::{
+    tic::*,
+    alphabeta_strategy_bitboard::AlphaBetaBitBoardStrategy,
+    tic_simulator::*,
+};
+
+struct PassStrategy;
+
+impl Strategy for PassStrategy {
+    fn get_move(&self, game_state: &Board) -> Option<Board> {
+        Some(game_state.clone())
+    }
+}
+
+struct NoMoveStrategy;
+
+impl Strategy for NoMoveStrategy {
+    fn get_move(&self, _game_state: &Board) -> Option<Board> {
+        None
+    }
+}
+
+#[test]
+fn test_game_record() {
+    let record = run_game(Board::new(), AlphaBetaBitBoardStrategy::new(1), AlphaBetaBitBoardStrategy::new(2));
+    assert_eq!(record.start, Board::new());
+    let mut board = record.start.clone();
+    for (i, m) in record.moves.iter().enumerate() {
+        assert_eq!(m.player, if i % 2 == 0 { Player::Player1 } else { Player::Player2 });
+        assert!(board.get_legal_boards().contains(&m.board));
+        board = m.board.clone();
+    }
+    assert_eq!(record.final_board(), &board);
+    assert!(board.get_legal_boards().is_empty());
+    assert_eq!(record.winner(), board.board_winner());
+    match record.result {
+        GameResult::Win(player) => assert_eq!(Some(player), board.board_winner()),
+        GameResult::Draw => assert_eq!(board.board_winner(), None),
+        GameResult::Forfeit(_) => panic!("Alphabeta played an illegal move"),
+    }
+    let total = record.total_time(Player::Player1) + record.total_time(Player::Player2);
+    assert_eq!(total, record.moves.iter().map(|m| m.elapsed).sum());
+}
+
+#[test]
+fn test_illegal_move_forfeits() {
+    let record = run_game(Board::new(), PassStrategy, AlphaBetaBitBoardStrategy::new(1));
+    assert_eq!(record.result, GameResult::Forfeit(Player::Player1));
+    assert_eq!(record.winner(), Some(Player::Player2));
+    assert!(record.moves.is_empty());
+
+    let record = run_game(Board::new(), AlphaBetaBitBoardStrategy::new(1), NoMoveStrategy);
+    assert_eq!(record.result, GameResult::Forfeit(Player::Player2));
+    assert_eq!(record.moves.len(), 1);
+}
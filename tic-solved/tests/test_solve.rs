@@ -0,0 +1,116 @@
+use tic_solved::{
+    tic_array::{
+        tic::*,
+        bitmap_tic::*,
+        symmetry::*,
+    },
+    minmax::{
+        alphabeta,
+        GameState,
+        Heuristic,
+    },
+    solve::{*, checkpoint},
+};
+
+use rstest::rstest;
+use std::fs;
+use std::path::PathBuf;
+
+fn read_board(file_path: PathBuf) -> Board {
+    let board_str = fs::read_to_string(file_path)
+        .expect("Failed to read board file");
+
+    let board_result: BoardResult = board_str.as_str().into();
+    board_result.into_inner().unwrap()
+}
+
+struct OutcomeHeuristic {}
+
+impl Heuristic<BitBoard> for OutcomeHeuristic {
+    type Score = i32;
+    fn score(game_state: &BitBoard) -> Self::Score {
+        match game_state.winner() {
+            Some(Player::Player1) => -1,
+            Some(Player::Player2) => 1,
+            None => 0,
+        }
+    }
+}
+
+fn outcome_score(outcome: GameOutcome) -> i32 {
+    match outcome {
+        GameOutcome::Win(Player::Player1) => -1,
+        GameOutcome::Win(Player::Player2) => 1,
+        GameOutcome::Draw | GameOutcome::Ongoing => 0,
+    }
+}
+
+// Two plies after lategame.txt, where player 1 needs five plies to win.
+fn late_position() -> BitBoard {
+    let board: BitBoard = read_board(PathBuf::from("positions/lategame.txt")).into();
+    let next = board.next_states();
+    next[1].next_states()[1].clone()
+}
+
+#[rstest]
+fn test_solved_game_over(#[files("positions/gameover/*.txt")] file_path: PathBuf) {
+    let board = read_board(file_path);
+    let bitmap: BitBoard = board.clone().into();
+    if let Some(winner) = board.board_winner() {
+        let solution = Solver::new().solve(&bitmap).unwrap();
+        assert_eq!(solution, Solution { outcome: GameOutcome::Win(winner), distance: 0 });
+    }
+}
+
+#[test]
+fn test_solve_agrees_with_full_depth_alphabeta() {
+    let board = late_position();
+    let solution = Solver::new().solve(&board).unwrap();
+    let (_, score) = match board.who_turn() {
+        Player::Player1 => alphabeta::min_score::<BitBoard, OutcomeHeuristic>(&board, 81, None, None),
+        Player::Player2 => alphabeta::max_score::<BitBoard, OutcomeHeuristic>(&board, 81, None, None),
+    };
+    assert_eq!(outcome_score(solution.outcome), score);
+}
+
+#[test]
+fn test_principal_line_proves_distance() {
+    let board = late_position();
+    let mut solver = Solver::new();
+    let solution = solver.solve(&board).unwrap();
+    let line = solver.principal_line(&board).unwrap();
+    assert_eq!(line.len(), solution.distance as usize);
+    let last = line.last().unwrap_or(&board);
+    match solution.outcome {
+        GameOutcome::Win(player) => assert_eq!(last.winner(), Some(player)),
+        GameOutcome::Draw => assert!(last.winner().is_none() && last.next_states().is_empty()),
+        GameOutcome::Ongoing => panic!("A solution is never ongoing"),
+    }
+}
+
+#[test]
+fn test_solution_invariant_under_symmetry() {
+    let board = late_position();
+    let solution = Solver::new().solve(&board).unwrap();
+    for (_, image) in board.symmetries() {
+        assert_eq!(Solver::new().solve(&image).unwrap(), solution);
+    }
+}
+
+#[test]
+fn test_checkpoint_resume() {
+    let path = std::env::temp_dir().join(format!("tic_solved_checkpoint_{}.bin", std::process::id()));
+    let board = late_position();
+
+    let mut solver = Solver::with_checkpoint(&path, 100).unwrap();
+    let solution = solver.solve(&board).unwrap();
+    solver.save_checkpoint().unwrap();
+    assert!(solver.stats().checkpoints_written > 1);
+
+    let mut resumed = Solver::with_checkpoint(&path, 100).unwrap();
+    assert_eq!(resumed.solved_positions(), solver.solved_positions());
+    assert_eq!(resumed.solve(&board).unwrap(), solution);
+    assert_eq!(resumed.stats().nodes, 0);
+    assert_eq!(checkpoint::load(&path).unwrap().len(), solver.solved_positions());
+    fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,78 @@
+use tic_solved::tic_array::{
+    tic::*,
+    bitmap_tic::*,
+    symmetry::*,
+};
+
+use rstest::rstest;
+use std::fs;
+use std::path::PathBuf;
+
+fn read_board(file_path: PathBuf) -> Board {
+    let board_str = fs::read_to_string(file_path)
+        .expect("Failed to read board file");
+
+    let board_result: BoardResult = board_str.as_str().into();
+    board_result.into_inner().unwrap()
+}
+
+#[rstest]
+fn test_transform_agrees(#[files("positions/**/*.txt")] file_path: PathBuf) {
+    let board = read_board(file_path);
+    let bitmap: BitBoard = board.clone().into();
+    for s in Symmetry::ALL {
+        let from_board: BitBoard = board.transform(s).into();
+        assert_eq!(from_board, bitmap.transform(s));
+    }
+}
+
+#[rstest]
+fn test_transform_round_trip(#[files("positions/**/*.txt")] file_path: PathBuf) {
+    let board = read_board(file_path);
+    let bitmap: BitBoard = board.clone().into();
+    for s in Symmetry::ALL {
+        assert_eq!(board.transform(s).transform(s.inverse()), board);
+        assert_eq!(bitmap.transform(s).transform(s.inverse()), bitmap);
+    }
+}
+
+#[rstest]
+fn test_canonical_invariant(#[files("positions/**/*.txt")] file_path: PathBuf) {
+    let board = read_board(file_path);
+    let bitmap: BitBoard = board.clone().into();
+    let (canonical_board, s) = board.canonical();
+    let (canonical_bitmap, t) = bitmap.canonical();
+    assert_eq!(board.transform(s), canonical_board);
+    assert_eq!(bitmap.transform(t), canonical_bitmap);
+    for (_, image) in board.symmetries() {
+        assert_eq!(image.canonical().0, canonical_board);
+        let image: BitBoard = image.into();
+        assert_eq!(image.canonical().0, canonical_bitmap);
+    }
+}
+
+#[rstest]
+fn test_legal_moves_map_through_transform(#[files("positions/*.txt")] file_path: PathBuf) {
+    let board = read_board(file_path);
+    let (canonical_board, s) = board.canonical();
+    let mut mapped_back: Vec<Board> = canonical_board.get_legal_boards()
+        .into_iter()
+        .map(|b| b.transform(s.inverse()))
+        .collect();
+    let mut legal = board.get_legal_boards();
+    mapped_back.sort();
+    legal.sort();
+    assert_eq!(mapped_back, legal);
+}
+
+#[test]
+fn test_unapply_cell() {
+    let board = Board::new().get_legal_boards()[5].clone();
+    let (canonical_board, s) = board.canonical();
+    for next in canonical_board.get_legal_boards() {
+        let cell = next.to_arr().1.unwrap();
+        let original = next.transform(s.inverse());
+        assert_eq!(original.to_arr().1, Some(s.unapply_cell(cell)));
+        assert!(board.get_legal_boards().contains(&original));
+    }
+}
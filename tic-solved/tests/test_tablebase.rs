@@ -0,0 +1,100 @@
+use tic_solved::{
+    tic_array::{
+        tic::*,
+        bitmap_tic::*,
+        alphabeta_strategy_bitboard::AlphaBetaBitBoardStrategy,
+        tic_simulator::*,
+    },
+    minmax::GameState,
+    solve::{*, tablebase::Tablebase},
+};
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+fn read_board(file_path: PathBuf) -> Board {
+    let board_str = fs::read_to_string(file_path)
+        .expect("Failed to read board file");
+
+    let board_result: BoardResult = board_str.as_str().into();
+    board_result.into_inner().unwrap()
+}
+
+fn tablebase() -> &'static Tablebase {
+    static TABLEBASE: OnceLock<Tablebase> = OnceLock::new();
+    TABLEBASE.get_or_init(|| Tablebase::generate(1).unwrap())
+}
+
+// Every position that can follow two plies after lategame.txt.
+fn late_positions() -> Vec<BitBoard> {
+    let board: BitBoard = read_board(PathBuf::from("positions/lategame.txt")).into();
+    let mut frontier = vec![board.next_states()[1].next_states()[1].clone()];
+    let mut positions = Vec::new();
+    while !frontier.is_empty() {
+        let next = frontier.iter()
+            .filter(|b| b.winner().is_none())
+            .flat_map(|b| b.next_states())
+            .collect();
+        positions.append(&mut frontier);
+        frontier = next;
+    }
+    positions
+}
+
+#[test]
+fn test_probe_agrees_with_solver() {
+    let mut solver = Solver::new();
+    let mut probed = 0;
+    for position in late_positions() {
+        if let Some(solution) = tablebase().probe(&position) {
+            assert_eq!(solution, solver.solve(&position).unwrap());
+            probed += 1;
+        }
+    }
+    assert!(probed > 0);
+}
+
+#[test]
+fn test_finished_games_not_probed() {
+    for position in late_positions().iter().filter(|b| b.outcome() != GameOutcome::Ongoing) {
+        assert_eq!(tablebase().probe(position), None);
+    }
+}
+
+#[test]
+fn test_too_many_entries() {
+    assert!(Tablebase::generate(3).is_err());
+}
+
+#[test]
+fn test_save_and_load() {
+    let path = std::env::temp_dir().join(format!("tic_solved_tablebase_{}.bin", std::process::id()));
+    tablebase().save(&path).unwrap();
+    let loaded = Tablebase::load(&path).unwrap();
+    fs::remove_file(path).unwrap();
+    assert_eq!(loaded.max_empty(), 1);
+    assert_eq!(loaded.len(), tablebase().len());
+    for position in late_positions() {
+        assert_eq!(loaded.probe(&position), tablebase().probe(&position));
+    }
+}
+
+#[test]
+fn test_strategy_plays_tablebase_move() {
+    let positions = late_positions();
+    let (base, solution) = positions.iter()
+        .find_map(|b| tablebase().probe(b).filter(|s| s.outcome != GameOutcome::Draw).map(|s| (b.clone(), s)))
+        .unwrap();
+    let strategy = AlphaBetaBitBoardStrategy::new(1).with_tablebase(Tablebase::generate(1).unwrap());
+    let board: Board = base.into();
+    let next: BitBoard = strategy.get_move(&board).unwrap().into();
+
+    let next_solution = match next.outcome() {
+        GameOutcome::Ongoing => tablebase().probe(&next).unwrap(),
+        outcome => Solution { outcome, distance: 0 },
+    };
+    assert_eq!(next_solution.outcome, solution.outcome);
+    assert_eq!(next_solution.distance + 1, solution.distance);
+}
+
@@ -0,0 +1,43 @@
+use tic_solved::tic_array::{
+    alphabeta_strategy_bitboard::AlphaBetaBitBoardStrategy,
+    mcts_strategy::MctsStrategy,
+    tournament::*,
+};
+
+#[test]
+fn test_round_robin() {
+    let tournament = Tournament::new(3)
+        .with_opening_moves(4)
+        .with_seed(5)
+        .with_entrant("depth 1", AlphaBetaBitBoardStrategy::new(1))
+        .with_entrant("depth 2", AlphaBetaBitBoardStrategy::new(2))
+        .with_entrant("mcts", MctsStrategy::new(50));
+    let results = tournament.run();
+    assert_eq!(results.names, vec!["depth 1", "depth 2", "mcts"]);
+    assert!(results.results.iter().flatten().all(|pairing| pairing.games() == 3));
+    assert_eq!(results, tournament.run());
+
+    let csv = results.to_csv();
+    assert_eq!(csv.lines().count(), 1 + 9);
+    assert!(csv.lines().nth(2).unwrap().starts_with("depth 1,depth 2,"));
+    let table = results.to_string();
+    assert_eq!(table.lines().count(), 1 + 3);
+    assert!(table.contains(&format!("{:.3}", results.get(1, 0).player1_win_rate())));
+}
+
+#[test]
+fn test_play_pairing() {
+    let mut moves = Vec::new();
+    let openings = openings(2, 2, 7);
+    let pairing = play_pairing(&AlphaBetaBitBoardStrategy::new(1), &AlphaBetaBitBoardStrategy::new(2), &openings, |record| {
+        moves.push(record.moves.len());
+    });
+    assert_eq!(pairing.games(), 2);
+    assert_eq!(moves.len(), 2);
+    let tournament = Tournament::new(2)
+        .with_opening_moves(2)
+        .with_seed(7)
+        .with_entrant("depth 1", AlphaBetaBitBoardStrategy::new(1))
+        .with_entrant("depth 2", AlphaBetaBitBoardStrategy::new(2));
+    assert_eq!(tournament.run().get(0, 1), pairing);
+}
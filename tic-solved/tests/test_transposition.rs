@@ -0,0 +1,63 @@
+use tic_solved::{
+    tic_array::{
+        tic::*,
+        bitmap_tic::*,
+    },
+    minmax::{
+        alphabeta,
+        transposition::TranspositionTable,
+        ZobristHash,
+    },
+};
+
+use rstest::rstest;
+use std::fs;
+use std::path::PathBuf;
+
+fn read_board(file_path: PathBuf) -> Board {
+    let board_str = fs::read_to_string(file_path)
+        .expect("Failed to read board file");
+
+    let board_result: BoardResult = board_str.as_str().into();
+    board_result.into_inner().unwrap()
+}
+
+#[rstest]
+fn test_hash_agrees(#[files("positions/**/*.txt")] file_path: PathBuf) {
+    let board = read_board(file_path);
+    let bitmap: BitBoard = board.clone().into();
+    assert_eq!(board.zobrist_hash(), bitmap.zobrist_hash());
+}
+
+#[rstest]
+fn test_position_min_tt(#[files("positions/*.txt")] file_path: PathBuf) {
+    let board = read_board(file_path);
+    let mut table = TranspositionTable::new(1 << 16);
+    let (_, score1) = alphabeta::min_score::<Board, AlmostWinHeuristic>(&board, 3, None, None);
+    let (suggested_board, score2) = alphabeta::min_score_tt::<Board, AlmostWinHeuristic>(&board, 3, None, None, &mut table);
+    assert_eq!(score1, score2);
+    assert!(board.get_legal_boards().contains(&suggested_board));
+}
+
+#[rstest]
+fn test_position_max_tt_bitboard(#[files("positions/*.txt")] file_path: PathBuf) {
+    let board: BitBoard = read_board(file_path).into();
+    let mut table = TranspositionTable::new(1 << 16);
+    let (_, score1) = alphabeta::max_score::<BitBoard, WinHeuristic>(&board, 4, None, None);
+    let (_, score2) = alphabeta::max_score_tt::<BitBoard, WinHeuristic>(&board, 4, None, None, &mut table);
+    assert_eq!(score1, score2);
+}
+
+#[test]
+fn test_table_reused_between_searches() {
+    let board: BitBoard = read_board(PathBuf::from("positions/late_game_position.txt")).into();
+    let mut table = TranspositionTable::new(1 << 16);
+    let (_, score1) = alphabeta::max_score_tt::<BitBoard, WinHeuristic>(&board, 6, None, None, &mut table);
+    assert!(table.stats().hits > 0);
+
+    table.reset_stats();
+    let (_, score2) = alphabeta::max_score_tt::<BitBoard, WinHeuristic>(&board, 6, None, None, &mut table);
+    assert_eq!(score1, score2);
+    assert_eq!(table.stats().probes, 1);
+    assert_eq!(table.stats().hit_rate(), 1.0);
+}
@@ -0,0 +1,212 @@
+use tic_solved::{
+    tic_array::{
+        tic::*,
+        tic_move::Move,
+        tic_simulator::GameResult,
+        alphabeta_strategy_bitboard::AlphaBetaBitBoardStrategy,
+    },
+    tui::{action, app::*, render::*},
+};
+
+use crossterm::{event::{KeyCode, KeyEvent, KeyModifiers}, style::Color};
+use std::{thread, time::{Duration, Instant}};
+
+fn hot_seat() -> App {
+    App::new(Board::new()).with_eval_budget(None)
+}
+
+fn play(app: &mut App, notation: &str) {
+    let target = screen_position(notation.parse().unwrap());
+    // Walks the cursor there the way a player would.
+    for _ in 0..20 {
+        let (row, col) = app.cursor();
+        let direction = if row < target.0 {
+            Direction::Down
+        } else if row > target.0 {
+            Direction::Up
+        } else if col < target.1 {
+            Direction::Right
+        } else if col > target.1 {
+            Direction::Left
+        } else {
+            break;
+        };
+        app.handle(Action::Cursor(direction));
+    }
+    assert_eq!(app.cursor(), target);
+    app.handle(Action::Play);
+}
+
+// Ticks until `done` holds, failing after a few seconds.
+fn tick_until(app: &mut App, done: impl Fn(&App) -> bool) {
+    let start = Instant::now();
+    while !done(app) {
+        assert!(start.elapsed() < Duration::from_secs(10), "Timed out");
+        app.tick();
+        thread::sleep(Duration::from_millis(1));
+    }
+}
+
+#[test]
+fn test_play_with_cursor() {
+    let mut app = hot_seat();
+    assert_eq!(app.cursor_move(), "B2b2".parse().ok());
+    app.handle(Action::Play);
+    play(&mut app, "B2c3");
+    play(&mut app, "C3a1");
+    assert_eq!(app.moves(), vec!["B2b2".parse::<Move>().unwrap(), "B2c3".parse().unwrap(), "C3a1".parse().unwrap()]);
+    assert_eq!(app.board(), &app.record().moves[2].board);
+}
+
+#[test]
+fn test_cursor_stays_on_legal_cells() {
+    let mut app = hot_seat();
+    app.handle(Action::Play);
+    let legal: Vec<(usize, usize)> = app.board().legal_moves().into_iter().map(screen_position).collect();
+    assert!(legal.contains(&app.cursor()));
+    let directions = [Direction::Right, Direction::Right, Direction::Down, Direction::Down, Direction::Left, Direction::Up, Direction::Up, Direction::Up];
+    for direction in directions {
+        app.handle(Action::Cursor(direction));
+        assert!(legal.contains(&app.cursor()), "{:?} left the legal cells", app.cursor());
+    }
+    // Nowhere further right to go within B2.
+    app.handle(Action::Cursor(Direction::Right));
+    app.handle(Action::Cursor(Direction::Right));
+    assert_eq!(app.cursor().1, 5);
+}
+
+#[test]
+fn test_undo() {
+    let mut app = hot_seat();
+    app.handle(Action::Undo);
+    assert_eq!(app.message(), "Nothing to undo");
+    app.handle(Action::Play);
+    play(&mut app, "B2a1");
+    app.handle(Action::Undo);
+    assert_eq!(app.moves(), vec!["B2b2".parse().unwrap()]);
+    app.handle(Action::Undo);
+    assert_eq!(app.board(), &Board::new());
+}
+
+#[test]
+fn test_redo() {
+    let mut app = hot_seat();
+    app.handle(Action::Redo);
+    assert_eq!(app.message(), "Nothing to redo");
+    app.handle(Action::Play);
+    play(&mut app, "B2a1");
+    app.handle(Action::Undo);
+    app.handle(Action::Undo);
+    app.handle(Action::Redo);
+    app.handle(Action::Redo);
+    assert_eq!(app.moves(), vec!["B2b2".parse::<Move>().unwrap(), "B2a1".parse().unwrap()]);
+    // A different move after undoing keeps the old one as a variation.
+    app.handle(Action::Undo);
+    play(&mut app, "B2c3");
+    assert_eq!(app.session().export().moves[0].next.len(), 2);
+}
+
+#[test]
+fn test_engine_replies_and_undo_takes_back_both() {
+    let mut app = hot_seat().with_engine(Player::Player2, "bitboard:1", Box::new(AlphaBetaBitBoardStrategy::new(1)));
+    app.handle(Action::Play);
+    assert!(!app.human_to_move());
+    tick_until(&mut app, |app| app.human_to_move());
+    assert_eq!(app.moves().len(), 2);
+    assert!(app.message().starts_with("bitboard:1 played"));
+    app.handle(Action::Undo);
+    assert_eq!(app.board(), &Board::new());
+    assert!(app.human_to_move());
+    app.handle(Action::Redo);
+    assert_eq!(app.moves().len(), 2);
+    assert!(app.human_to_move());
+}
+
+#[test]
+fn test_evaluation() {
+    let mut app = App::new(Board::new()).with_eval_budget(Some(Duration::from_millis(50)));
+    tick_until(&mut app, |app| app.evaluation().is_some());
+    assert!(app.evaluation().unwrap().depth >= 1);
+    app.handle(Action::Play);
+    assert_eq!(app.evaluation(), None);
+}
+
+// How much of the evaluation bar is player 1's, and the label under it.
+fn evaluation_bar(screen: &Screen) -> (usize, String) {
+    let y = (0..screen.height()).find(|&y| screen.line(y).contains('█')).unwrap();
+    let bar: Vec<_> = (0..screen.line(y).chars().count())
+        .map(|x| screen.get(x, y))
+        .filter(|(c, _)| *c == '█')
+        .collect();
+    let x_share = bar.iter().filter(|(_, style)| style.fg == bar[0].1.fg).count();
+    (x_share, screen.line(y + 1))
+}
+
+#[test]
+fn test_evaluation_bar_leans_towards_the_side_ahead() {
+    // Nobody has won a sub board, but X has two in a row twice and O has nothing to show for
+    // its moves.
+    let mut cells = [[[[0; 3]; 3]; 3]; 3];
+    cells[0][0][0] = [1, 1, 0];
+    cells[1][1][0] = [1, 1, 0];
+    cells[2][2][2][2] = 2;
+    cells[0][2][2][0] = 2;
+    cells[2][0][0][2] = 2;
+    let board = Board::create_board(cells, None).unwrap();
+    assert_eq!(board.outcome(), GameOutcome::Ongoing);
+    let mut app = App::new(board).with_eval_budget(Some(Duration::from_millis(50)));
+    tick_until(&mut app, |app| app.evaluation().is_some());
+    assert!(app.evaluation().unwrap().score < 0);
+
+    let (x_share, label) = evaluation_bar(&draw(&app));
+    let (even_share, _) = evaluation_bar(&draw(&hot_seat()));
+    assert!(x_share > even_share);
+    assert!(label.contains("eval X +"));
+}
+
+#[test]
+fn test_quit_forfeits_unfinished_game() {
+    let mut app = hot_seat();
+    app.handle(Action::Play);
+    assert!(!app.handle(Action::Quit));
+    assert_eq!(app.record().result, GameResult::Forfeit(Player::Player2));
+}
+
+#[test]
+fn test_draw_highlights_forced_board() {
+    let mut app = hot_seat();
+    app.handle(Action::Play);
+    let screen = draw(&app);
+    // The top left cell of the centre board is legal and not under the cursor.
+    let (c, style) = screen.get(cell_x(3) + 1, cell_y(3));
+    assert_eq!((c, style.bg), ('·', Color::DarkBlue));
+    assert_eq!(screen.get(cell_x(0) + 1, cell_y(0)).1.bg, Color::Reset);
+    assert_eq!(screen.get(cell_x(4) + 1, cell_y(4)).0, 'X');
+    assert!((0..screen.height()).any(|y| screen.line(y).contains("1. B2b2")));
+}
+
+#[test]
+fn test_draw_won_sub_board() {
+    let mut cells = [[[[0; 3]; 3]; 3]; 3];
+    cells[0][0][0] = [1, 1, 1];
+    cells[2][2][0][0] = 2;
+    cells[2][2][0][2] = 2;
+    cells[2][2][1][1] = 2;
+    let board = Board::create_board(cells, None).unwrap();
+    let screen = draw(&App::new(board).with_eval_budget(None));
+    assert_eq!(screen.get(cell_x(1) + 1, cell_y(1)).0, '╳');
+    assert!(!screen.line(cell_y(0)).contains('X'));
+}
+
+#[test]
+fn test_keys() {
+    let key = |code| action(KeyEvent::new(code, KeyModifiers::NONE));
+    assert_eq!(key(KeyCode::Left), Some(Action::Cursor(Direction::Left)));
+    assert_eq!(key(KeyCode::Char('j')), Some(Action::Cursor(Direction::Down)));
+    assert_eq!(key(KeyCode::Enter), Some(Action::Play));
+    assert_eq!(key(KeyCode::Char('u')), Some(Action::Undo));
+    assert_eq!(key(KeyCode::Char('r')), Some(Action::Redo));
+    assert_eq!(key(KeyCode::Char('q')), Some(Action::Quit));
+    assert_eq!(action(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)), Some(Action::Quit));
+    assert_eq!(key(KeyCode::Char('x')), None);
+}